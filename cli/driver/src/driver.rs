@@ -28,6 +28,7 @@ extern crate rustc_target;
 extern crate rustc_type_ir;
 
 mod exporter;
+mod purity;
 
 use std::collections::HashSet;
 
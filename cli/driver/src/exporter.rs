@@ -87,6 +87,8 @@ impl Callbacks for ExtractionCallbacks {
 
         use hax_frontend_exporter::ThirBody;
         use hax_types::cli_options::Command;
+        use hax_types::driver_api::{EmitHaxMetaMessage, HaxDriverMessage, HaxDriverMessages};
+        use hax_types::engine_api::ProfilingData;
         use rustc_session::config::CrateType;
         use serde::{Deserialize, Serialize};
         use std::fs::File;
@@ -114,54 +116,85 @@ impl Callbacks for ExtractionCallbacks {
 
         let output_dir = compiler.sess.io.output_dir.clone().unwrap();
         let haxmeta_path = output_dir.join(format!("{crate_name}-{cg_metadata}.haxmeta",));
-
-        let mut file = BufWriter::new(File::create(&haxmeta_path).unwrap());
-
-        use hax_types::driver_api::{with_kind_type, HaxMeta};
-        with_kind_type!(
-            self.body_types.clone(),
-            <Body>|| {
-                let (spans, def_ids, impl_infos, items, cache_map) =
-                    convert_thir(&self.clone().into(), tcx);
-                let files: HashSet<PathBuf> = HashSet::from_iter(
-                    items
-                        .iter()
-                        .flat_map(|item| item.span.filename.to_path().map(|path| path.to_path_buf()))
+        let rmeta_path =
+            output_dir.join(format!("lib{crate_name}{}.rmeta", opts.cg.extra_filename));
+
+        crate::purity::check(tcx);
+
+        let mut messages = vec![];
+        let start = std::time::Instant::now();
+        let mut quantity = 0;
+        match File::create(&haxmeta_path) {
+            Ok(file) => {
+                let mut file = BufWriter::new(file);
+                use hax_types::driver_api::{with_kind_type, HaxMeta};
+                with_kind_type!(
+                    self.body_types.clone(),
+                    <Body>|| {
+                        let (spans, def_ids, impl_infos, items, cache_map) =
+                            convert_thir(&self.clone().into(), tcx);
+                        quantity = items.len() as u32;
+                        let files: HashSet<PathBuf> = HashSet::from_iter(
+                            items
+                                .iter()
+                                .flat_map(|item| item.span.filename.to_path().map(|path| path.to_path_buf()))
+                        );
+                        let haxmeta: HaxMeta<Body> = HaxMeta {
+                            crate_name: crate_name.clone(),
+                            cg_metadata,
+                            externs,
+                            impl_infos,
+                            items,
+                            comments: files.into_iter()
+                                .flat_map(|path|hax_frontend_exporter::comments::comments_of_file(path).ok())
+                                .flatten()
+                                .collect(),
+                            def_ids,
+                            hax_version: hax_types::HAX_VERSION.into(),
+                        };
+                        haxmeta.write(&mut file, cache_map);
+                    }
                 );
-                let haxmeta: HaxMeta<Body> = HaxMeta {
-                    crate_name,
-                    cg_metadata,
-                    externs,
-                    impl_infos,
-                    items,
-                    comments: files.into_iter()
-                        .flat_map(|path|hax_frontend_exporter::comments::comments_of_file(path).ok())
-                        .flatten()
-                        .collect(),
-                    def_ids,
-                    hax_version: hax_types::HAX_VERSION.into(),
-                };
-                haxmeta.write(&mut file, cache_map);
+
+                let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+                let manifest_dir = std::path::Path::new(&manifest_dir);
+
+                messages.push(HaxDriverMessage::EmitHaxMeta(EmitHaxMetaMessage {
+                    manifest_dir: manifest_dir.to_path_buf(),
+                    working_dir: opts
+                        .working_dir
+                        .to_path(rustc_span::FileNameDisplayPreference::Local)
+                        .to_path_buf(),
+                    path: haxmeta_path,
+                }));
             }
-        );
-
-        let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
-        let manifest_dir = std::path::Path::new(&manifest_dir);
-
-        let data = hax_types::driver_api::EmitHaxMetaMessage {
-            manifest_dir: manifest_dir.to_path_buf(),
-            working_dir: opts
-                .working_dir
-                .to_path(rustc_span::FileNameDisplayPreference::Local)
-                .to_path_buf(),
-            path: haxmeta_path,
+            Err(err) => messages.push(HaxDriverMessage::Error {
+                crate_name: crate_name.clone(),
+                message: format!("could not create file {}: {err}", haxmeta_path.display()),
+            }),
+        }
+        messages.push(HaxDriverMessage::ProfilingData(ProfilingData {
+            context: format!("driver({crate_name})"),
+            time_ns: start.elapsed().as_nanos() as u64,
+            memory: 0,
+            quantity,
+            errored: false,
+        }));
+
+        let messages = HaxDriverMessages {
+            key: HaxDriverMessages::key(
+                &std::env::var(ENV_VAR_OPTIONS_FRONTEND).unwrap_or_default(),
+                &std::env::var("HAX_CARGO_CACHE_KEY").unwrap_or_default(),
+            ),
+            messages,
         };
-        eprintln!(
-            "{}{}",
-            hax_types::driver_api::HAX_DRIVER_STDERR_PREFIX,
-            &serde_json::to_string(&hax_types::driver_api::HaxDriverMessage::EmitHaxMeta(data))
-                .unwrap()
-        );
+        let messages_path = HaxDriverMessages::path(&rmeta_path);
+        if let Err(err) = messages.write(&messages_path) {
+            tcx.dcx().err(format!(
+                "hax: could not write the messages of the driver to {}: {err}",
+                messages_path.display()
+            ));
+        }
 
         Compilation::Stop
     }
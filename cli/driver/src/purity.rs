@@ -0,0 +1,362 @@
+//! Frontend checks of the `pure` attribute (see `hax_lib::pure`): a
+//! pure function calls only pure functions, calls those that may panic
+//! only under a precondition, and is recursive (maybe mutually) only
+//! with a termination measure; a specification calling a function
+//! that is not pure is warned about.
+//!
+//! The macros of `hax_lib` check the body of pure functions, but cannot
+//! see the functions they call: this is checked here, on THIR.
+
+use hax_frontend_exporter::SafeTyCtxtBodies;
+use hax_lib_macros_types::{AssociationRole, AttrPayload};
+use rustc_ast::attr::AttributeExt;
+use rustc_ast::LitKind;
+use rustc_hir::def::DefKind;
+use rustc_middle::thir::visit::{walk_expr, Visitor};
+use rustc_middle::thir::{Expr, ExprKind, Thir};
+use rustc_middle::ty::{self, TyCtxt};
+use rustc_span::def_id::{DefId, LocalDefId};
+use rustc_span::Span;
+use std::collections::{HashMap, HashSet};
+
+/// The hax payloads (`#[_hax::json(PAYLOAD)]`) of a local item.
+fn payloads(tcx: TyCtxt<'_>, def_id: LocalDefId) -> Vec<AttrPayload> {
+    tcx.hir_attrs(tcx.local_def_id_to_hir_id(def_id))
+        .iter()
+        .filter(|attr| {
+            let path = attr.path();
+            path.len() == 2
+                && path[0].as_str() == hax_lib_macros_types::HAX_TOOL
+                && path[1].as_str() == "json"
+        })
+        .filter_map(|attr| {
+            let LitKind::Str(payload, _) = attr.meta_item_list()?.first()?.lit()?.kind else {
+                return None;
+            };
+            serde_json::from_str(payload.as_str()).ok()
+        })
+        .collect()
+}
+
+/// The local functions and methods of a crate, with their payloads.
+fn functions(tcx: TyCtxt<'_>) -> Vec<(LocalDefId, Vec<AttrPayload>)> {
+    tcx.hir_body_owners()
+        .filter(|def_id| matches!(tcx.def_kind(*def_id), DefKind::Fn | DefKind::AssocFn))
+        .map(|def_id| (def_id, payloads(tcx, def_id)))
+        .collect()
+}
+
+/// The crates whose functions may be called by pure functions, except
+/// for the functions of the modules `IMPURE_MODULES`.
+const PURE_CRATES: &[&str] = &["core", "alloc", "hax_lib"];
+
+/// The modules of `PURE_CRATES` with side effects: interior
+/// mutability, raw pointers, atomics and intrinsics.
+const IMPURE_MODULES: &[&str] = &["arch", "cell", "intrinsics", "ptr", "sync"];
+
+/// The functions of `hax_lib` whose arguments are loop specifications
+/// (see `loop_invariant!` and `loop_decreases!`).
+const LOOP_SPECIFICATIONS: &[&str] = &[
+    "_internal_loop_invariant",
+    "_internal_iterator_loop_invariant",
+    "_internal_while_loop_invariant",
+    "_internal_loop_decreases",
+];
+
+/// The methods of `PURE_CRATES` that panic on some of their inputs
+/// (e.g. `Option::unwrap`, or `Index::index` out of bounds), besides
+/// the functions of their module `panicking` (e.g. those of `panic!`
+/// and `assert!`).
+const PANICKING_METHODS: &[&str] = &[
+    "expect",
+    "expect_err",
+    "index",
+    "index_mut",
+    "unwrap",
+    "unwrap_err",
+];
+
+/// The top-level module of the non-local item `def_id`, if any.
+fn module(tcx: TyCtxt<'_>, def_id: DefId) -> Option<rustc_span::Symbol> {
+    tcx.def_path(def_id)
+        .data
+        .first()
+        .and_then(|data| data.data.get_opt_name())
+}
+
+/// Can the non-local function `def_id` be called by pure functions?
+fn is_pure_foreign(tcx: TyCtxt<'_>, def_id: DefId) -> bool {
+    PURE_CRATES.contains(&tcx.crate_name(def_id.krate).as_str())
+        && !module(tcx, def_id).is_some_and(|module| IMPURE_MODULES.contains(&module.as_str()))
+}
+
+/// May the non-local pure function `def_id` panic? Like panicking
+/// macros and methods in the body of pure functions (see
+/// `hax_lib::pure`), it may only be called under a precondition.
+fn may_panic_foreign(tcx: TyCtxt<'_>, def_id: DefId) -> bool {
+    module(tcx, def_id).is_some_and(|module| module.as_str() == "panicking")
+        || PANICKING_METHODS.contains(&tcx.item_name(def_id).as_str())
+}
+
+/// A call in the body of a function.
+struct Call {
+    callee: DefId,
+    span: Span,
+    /// Is the call part of a loop specification?
+    in_loop_specification: bool,
+}
+
+struct CallCollector<'thir, 'tcx> {
+    tcx: TyCtxt<'tcx>,
+    thir: &'thir Thir<'tcx>,
+    in_loop_specification: bool,
+    calls: Vec<Call>,
+}
+
+impl<'thir, 'tcx> Visitor<'thir, 'tcx> for CallCollector<'thir, 'tcx> {
+    fn thir(&self) -> &'thir Thir<'tcx> {
+        self.thir
+    }
+
+    fn visit_expr(&mut self, expr: &'thir Expr<'tcx>) {
+        match &expr.kind {
+            ExprKind::Call { ty, fn_span, .. } => {
+                if let ty::FnDef(callee, _) = ty.kind() {
+                    self.calls.push(Call {
+                        callee: *callee,
+                        span: *fn_span,
+                        in_loop_specification: self.in_loop_specification,
+                    });
+                    let is_loop_specification = self.tcx.crate_name(callee.krate).as_str()
+                        == "hax_lib"
+                        && LOOP_SPECIFICATIONS.contains(&self.tcx.item_name(*callee).as_str());
+                    if is_loop_specification {
+                        let outer = std::mem::replace(&mut self.in_loop_specification, true);
+                        walk_expr(self, expr);
+                        self.in_loop_specification = outer;
+                        return;
+                    }
+                }
+            }
+            ExprKind::Closure(closure) => {
+                let in_loop_specification = self.in_loop_specification;
+                self.calls
+                    .extend(
+                        calls(self.tcx, closure.closure_id)
+                            .into_iter()
+                            .map(|call| Call {
+                                in_loop_specification: call.in_loop_specification
+                                    || in_loop_specification,
+                                ..call
+                            }),
+                    );
+            }
+            _ => (),
+        }
+        walk_expr(self, expr)
+    }
+}
+
+/// The calls of the body of `def_id` (including the bodies of its
+/// closures).
+fn calls(tcx: TyCtxt<'_>, def_id: LocalDefId) -> Vec<Call> {
+    // The bodies of ill-typed functions cannot be inspected.
+    if tcx.typeck(def_id).tainted_by_errors.is_some() {
+        return vec![];
+    }
+    let Ok((thir, expr)) = tcx.thir_body_safe(def_id) else {
+        return vec![];
+    };
+    let mut collector = CallCollector {
+        tcx,
+        thir: &thir,
+        in_loop_specification: false,
+        calls: vec![],
+    };
+    collector.visit_expr(&thir[expr]);
+    collector.calls
+}
+
+/// Finds a pure function called by `def_id` through which `def_id`
+/// calls itself, if any.
+fn recursion(
+    graph: &HashMap<LocalDefId, Vec<LocalDefId>>,
+    def_id: LocalDefId,
+) -> Option<LocalDefId> {
+    graph.get(&def_id)?.iter().copied().find(|callee| {
+        let mut visited = HashSet::new();
+        let mut stack = vec![*callee];
+        while let Some(current) = stack.pop() {
+            if current == def_id {
+                return true;
+            }
+            if visited.insert(current) {
+                stack.extend(graph.get(&current).into_iter().flatten());
+            }
+        }
+        false
+    })
+}
+
+/// Checks that pure functions call only pure functions (of the crate,
+/// or of `PURE_CRATES`) and are recursive only with a termination
+/// measure, and warns about the specifications (pre- and
+/// postconditions, termination measures, refinements and loop
+/// specifications) calling functions that are not pure.
+pub(crate) fn check(tcx: TyCtxt<'_>) {
+    // The errors of the macros of `hax_lib` (e.g. of `pure`) are not
+    // reported twice.
+    if tcx.dcx().has_errors().is_some() {
+        return;
+    }
+    let functions = functions(tcx);
+    let specification_uids: HashSet<String> = tcx
+        .hir_crate_items(())
+        .definitions()
+        .flat_map(|def_id| payloads(tcx, def_id))
+        .filter_map(|payload| match payload {
+            AttrPayload::AssociatedItem {
+                role:
+                    AssociationRole::Requires
+                    | AssociationRole::Ensures
+                    | AssociationRole::Decreases
+                    | AssociationRole::Refine,
+                item,
+            } => Some(item.uid),
+            _ => None,
+        })
+        .collect();
+    let pure: HashSet<LocalDefId> = functions
+        .iter()
+        .filter(|(_, payloads)| payloads.iter().any(|p| matches!(p, AttrPayload::Pure)))
+        .map(|(def_id, _)| *def_id)
+        .collect();
+    // Functions of `hax_lib` (e.g. `future`) and specifications may
+    // be called by specifications.
+    let allowed: HashSet<LocalDefId> = functions
+        .iter()
+        .filter(|(_, payloads)| {
+            payloads
+                .iter()
+                .any(|p| matches!(p, AttrPayload::Language | AttrPayload::Uid(_)))
+        })
+        .map(|(def_id, _)| *def_id)
+        .collect();
+
+    // The calls between pure functions.
+    let mut graph: HashMap<LocalDefId, Vec<LocalDefId>> = HashMap::new();
+    for (def_id, payloads) in &functions {
+        let is_pure = pure.contains(def_id);
+        let is_specification = payloads.iter().any(
+            |payload| matches!(payload, AttrPayload::Uid(uid) if specification_uids.contains(&uid.uid)),
+        );
+        let has_requires = payloads.iter().any(|payload| {
+            matches!(
+                payload,
+                AttrPayload::AssociatedItem {
+                    role: AssociationRole::Requires,
+                    ..
+                }
+            )
+        });
+        for call in calls(tcx, *def_id) {
+            let is_specification = is_specification || call.in_loop_specification;
+            if !is_pure && !is_specification {
+                continue;
+            }
+            let is_pure_callee = match call.callee.as_local() {
+                // Trait methods are resolved at the use site: their
+                // implementations are not known here.
+                Some(callee) => {
+                    if is_pure && pure.contains(&callee) {
+                        graph.entry(*def_id).or_default().push(callee);
+                    }
+                    pure.contains(&callee)
+                        || allowed.contains(&callee)
+                        || tcx.trait_of_item(call.callee).is_some()
+                }
+                None => is_pure_foreign(tcx, call.callee),
+            };
+            if is_pure_callee {
+                if is_pure
+                    && !has_requires
+                    && !call.callee.is_local()
+                    && may_panic_foreign(tcx, call.callee)
+                {
+                    let caller = tcx.def_path_str(def_id.to_def_id());
+                    let callee = tcx.def_path_str(call.callee);
+                    tcx.dcx()
+                        .struct_span_err(
+                            call.span,
+                            format!(
+                                "the pure function `{caller}` calls `{callee}`, which may panic"
+                            ),
+                        )
+                        .with_help(
+                            "a pure function may panic only under a precondition: \
+                             add one with `#[hax_lib::requires(...)]`",
+                        )
+                        .emit();
+                }
+                continue;
+            }
+            let callee = tcx.def_path_str(call.callee);
+            let help = if call.callee.is_local() {
+                format!("mark `{callee}` with `#[hax_lib::pure]`")
+            } else {
+                "outside of the crate, only the functions of `core`, `alloc` and `hax_lib` \
+                 without interior mutability, raw pointers or atomics are pure"
+                    .to_string()
+            };
+            if is_pure {
+                let caller = tcx.def_path_str(def_id.to_def_id());
+                tcx.dcx()
+                    .struct_span_err(
+                        call.span,
+                        format!("the pure function `{caller}` calls `{callee}`, which is not pure"),
+                    )
+                    .with_help(help)
+                    .emit();
+            } else {
+                tcx.dcx()
+                    .struct_span_warn(
+                        call.span,
+                        format!("this specification calls `{callee}`, which is not pure"),
+                    )
+                    .with_help(help)
+                    .emit();
+            }
+        }
+    }
+
+    for (def_id, payloads) in &functions {
+        let has_decreases = payloads.iter().any(|payload| {
+            matches!(
+                payload,
+                AttrPayload::AssociatedItem {
+                    role: AssociationRole::Decreases,
+                    ..
+                }
+            )
+        });
+        if !pure.contains(def_id) || has_decreases {
+            continue;
+        }
+        let Some(callee) = recursion(&graph, *def_id) else {
+            continue;
+        };
+        let caller = tcx.def_path_str(def_id.to_def_id());
+        let message = if callee == *def_id {
+            format!("the pure function `{caller}` is recursive, but has no termination measure")
+        } else {
+            let callee = tcx.def_path_str(callee.to_def_id());
+            format!(
+                "the pure function `{caller}` is mutually recursive with `{callee}`, but has no termination measure"
+            )
+        };
+        tcx.dcx()
+            .struct_span_err(tcx.def_span(*def_id), message)
+            .with_help("add a termination measure with `#[hax_lib::decreases(...)]`")
+            .emit();
+    }
+}
@@ -1,20 +1,23 @@
 #![feature(rustc_private)]
 use annotate_snippets::{Level, Renderer};
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches};
 use colored::Colorize;
 use hax_types::cli_options::*;
 use hax_types::driver_api::*;
 use hax_types::engine_api::*;
+use hax_types::translation_report::*;
 use is_terminal::IsTerminal;
 use serde_jsonlines::BufReadExt;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::io::BufRead;
 use std::io::Write;
 use std::path::PathBuf;
 use std::process;
 
+mod config;
 mod engine_debug_webapp;
+mod locate;
 use hax_frontend_exporter::id_table;
 
 /// Return a toolchain argument to pass to `cargo`: when the correct nightly is
@@ -214,6 +217,62 @@ impl HaxMessage {
                 );
                 eprintln!("{}", renderer.render(Level::Info.title(&title)));
             }
+            Self::TranslationReport { path, summary } => {
+                let width = summary
+                    .iter()
+                    .map(|module| module.module.len())
+                    .max()
+                    .unwrap_or(0);
+                let mut title = format!("hax: wrote translation report to {}", path.display());
+                for module in summary {
+                    title += &format!(
+                        "\n  {:<width$}  {} translated, {} signature-only, {} opaque, {} failed, {} excluded",
+                        module.module,
+                        module.translated,
+                        module.signature_only,
+                        module.opaque,
+                        module.failed,
+                        module.excluded,
+                    );
+                }
+                eprintln!("{}", renderer.render(Level::Info.title(&title)));
+            }
+            Self::TranslationReportFailure { path, message } => {
+                let title = format!(
+                    "hax: could not write the translation report to {}: {message}",
+                    path.display()
+                );
+                eprintln!("{}", renderer.render(Level::Error.title(&title)));
+            }
+            Self::DriverError {
+                crate_name,
+                message,
+            } => {
+                let title = format!("hax: the driver failed on crate `{crate_name}`: {message}");
+                eprintln!("{}", renderer.render(Level::Error.title(&title)));
+            }
+            Self::SuppressedDiagnostics {
+                duplicates,
+                over_budget,
+            } => {
+                let mut title = "hax: some errors were not displayed:".to_string();
+                if duplicates > 0 {
+                    title += &format!("\n  {duplicates} duplicated error(s)");
+                }
+                for (code, count) in over_budget {
+                    title += &format!("\n  {count} error(s) [{code}] over budget");
+                }
+                eprintln!("{}", renderer.render(Level::Note.title(&title)));
+            }
+            Self::Located { position, found } => {
+                if found.is_empty() {
+                    let title = format!("hax: no source map entry found for {position}");
+                    eprintln!("{}", renderer.render(Level::Error.title(&title)));
+                }
+                for position in found {
+                    println!("{position}");
+                }
+            }
             Self::CargoBuildFailure => {
                 let title =
                     "hax: running `cargo build` was not successful, continuing anyway.".to_string();
@@ -230,6 +289,80 @@ impl HaxMessage {
     }
 }
 
+/// What to do with a diagnostic sent by the engine
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DiagnosticVerdict {
+    Report,
+    /// An identical diagnostic was seen already
+    Duplicate,
+    /// The diagnostic exceeds `--max-errors-per-item` or
+    /// `--max-errors-per-code`
+    OverBudget,
+}
+
+/// Deduplicates the diagnostics of the engine (by code, span and
+/// context) and enforces the error budgets given on the command line.
+#[derive(Debug, Default)]
+struct DiagnosticsFilter {
+    max_per_item: Option<usize>,
+    max_per_code: Option<usize>,
+    seen: HashSet<(String, Vec<hax_frontend_exporter::Span>, String)>,
+    per_item: HashMap<hax_frontend_exporter::DefId, usize>,
+    per_code: HashMap<String, usize>,
+    duplicates: usize,
+    over_budget: BTreeMap<String, usize>,
+}
+
+impl DiagnosticsFilter {
+    fn new(backend: &BackendOptions<()>) -> Self {
+        Self {
+            max_per_item: backend.max_errors_per_item,
+            max_per_code: backend.max_errors_per_code,
+            ..Self::default()
+        }
+    }
+
+    fn verdict(&mut self, diagnostic: &hax_types::diagnostics::Diagnostics) -> DiagnosticVerdict {
+        let code = diagnostic.kind.code();
+        let key = (
+            code.clone(),
+            diagnostic.span.clone(),
+            diagnostic.context.clone(),
+        );
+        if !self.seen.insert(key) {
+            self.duplicates += 1;
+            return DiagnosticVerdict::Duplicate;
+        }
+        let item_count = diagnostic
+            .owner_id
+            .as_ref()
+            .map(|owner_id| *self.per_item.get(owner_id).unwrap_or(&0));
+        let code_count = *self.per_code.get(&code).unwrap_or(&0);
+        let over_item_budget =
+            matches!((item_count, self.max_per_item), (Some(n), Some(max)) if n >= max);
+        let over_code_budget = self.max_per_code.is_some_and(|max| code_count >= max);
+        if over_item_budget || over_code_budget {
+            *self.over_budget.entry(code).or_default() += 1;
+            return DiagnosticVerdict::OverBudget;
+        }
+        if let Some(owner_id) = &diagnostic.owner_id {
+            *self.per_item.entry(owner_id.clone()).or_default() += 1;
+        }
+        *self.per_code.entry(code).or_default() += 1;
+        DiagnosticVerdict::Report
+    }
+
+    /// A message summarizing the suppressed diagnostics, if any.
+    fn summary(self) -> Option<HaxMessage> {
+        (self.duplicates > 0 || !self.over_budget.is_empty()).then(|| {
+            HaxMessage::SuppressedDiagnostics {
+                duplicates: self.duplicates,
+                over_budget: self.over_budget.into_iter().collect(),
+            }
+        })
+    }
+}
+
 /// Runs `hax-engine`
 fn run_engine(
     haxmeta: HaxMeta<hax_frontend_exporter::ThirBody>,
@@ -238,6 +371,7 @@ fn run_engine(
     manifest_dir: PathBuf,
     backend: &BackendOptions<()>,
     message_format: MessageFormat,
+    report: Option<&mut TranslationReport>,
 ) -> bool {
     let engine_options = EngineOptions {
         hax_version: haxmeta.hax_version,
@@ -299,6 +433,9 @@ fn run_engine(
 
         let stdout = std::io::BufReader::new(engine_subprocess.stdout.take().unwrap());
         let mut errors_per_item: HashMap<_, usize> = HashMap::new();
+        let mut error_codes_per_item: HashMap<_, Vec<String>> = HashMap::new();
+        let mut inclusions = vec![];
+        let mut diagnostics_filter = DiagnosticsFilter::new(backend);
         for msg in stdout.json_lines() {
             let msg = msg.expect(
                 "Hax engine sent an invalid json value. \
@@ -310,17 +447,27 @@ fn run_engine(
                 FromEngine::Exit => break,
                 FromEngine::Diagnostic(diagnostic) => {
                     error = true;
+                    let verdict = diagnostics_filter.verdict(&diagnostic);
+                    if verdict == DiagnosticVerdict::Duplicate {
+                        continue;
+                    }
                     if backend.dry_run {
                         output.diagnostics.push(diagnostic.clone())
                     }
                     if let Some(owner_id) = &diagnostic.owner_id {
                         *errors_per_item.entry(owner_id.clone()).or_default() += 1;
+                        error_codes_per_item
+                            .entry(owner_id.clone())
+                            .or_default()
+                            .push(diagnostic.kind.code());
                     }
-                    HaxMessage::Diagnostic {
-                        diagnostic,
-                        working_dir: working_dir.clone(),
+                    if verdict == DiagnosticVerdict::Report {
+                        HaxMessage::Diagnostic {
+                            diagnostic,
+                            working_dir: working_dir.clone(),
+                        }
+                        .report(message_format, Some(&mut rctx));
                     }
-                    .report(message_format, Some(&mut rctx));
                 }
                 FromEngine::File(file) => {
                     if backend.dry_run {
@@ -382,11 +529,26 @@ fn run_engine(
                         errors_per_item.insert(item, 0);
                     }
                 }
+                FromEngine::ItemInclusions(items) => {
+                    inclusions.extend(items);
+                }
                 FromEngine::Ping => {
                     send!(&ToEngine::Pong);
                 }
             }
         }
+        if let Some(summary) = diagnostics_filter.summary() {
+            summary.report(message_format, None)
+        }
+        if let Some(report) = report {
+            for (def_id, inclusion) in inclusions {
+                let mut codes = error_codes_per_item.remove(&def_id).unwrap_or_default();
+                codes.sort();
+                codes.dedup();
+                let status = ItemStatus::new(inclusion, codes);
+                report.items.push(ItemReport::new(&def_id, status));
+            }
+        }
         if backend.stats {
             HaxMessage::Stats {
                 errors_per_item: errors_per_item.into_iter().collect(),
@@ -474,60 +636,173 @@ fn get_hax_rustc_driver_path() -> PathBuf {
         .join("driver-hax-frontend-exporter")
 }
 
+const MSG_FMT_FLAG: &str = "--message-format";
+
+/// Splits the flags given by the user to `cargo` into the message
+/// format set with `--message-format`, if any, and the other flags.
+fn take_message_format(cargo_flags: &[String]) -> (Option<String>, Vec<String>) {
+    let mut flags = cargo_flags.iter();
+    let mut format = None;
+    let mut others = vec![];
+    while let Some(flag) = flags.next() {
+        if flag == MSG_FMT_FLAG {
+            format = flags.next().cloned();
+        } else if let Some(value) = flag
+            .strip_prefix(MSG_FMT_FLAG)
+            .and_then(|rest| rest.strip_prefix('='))
+        {
+            format = Some(value.to_string());
+        } else {
+            others.push(flag.clone());
+        }
+    }
+    (format, others)
+}
+
+/// Does the `cargo` message format `format` (e.g. `json` or
+/// `json-diagnostic-short,json-render-diagnostics`) produce JSON
+/// messages?
+fn is_json_message_format(format: &str) -> bool {
+    format.split(',').any(|format| format.starts_with("json"))
+}
+
+/// The JSON message format asked to `cargo` when the user chose the
+/// message format `format` (e.g. `short`), whose diagnostics are then
+/// rendered by `cargo hax`.
+fn json_message_format(format: Option<&str>, colored: bool) -> String {
+    let mut formats = vec![];
+    if format.is_some_and(|format| format.split(',').any(|format| format == "short")) {
+        formats.push("json-diagnostic-short");
+    }
+    if colored {
+        formats.push("json-diagnostic-rendered-ansi");
+    }
+    if formats.is_empty() {
+        formats.push("json");
+    }
+    formats.join(",")
+}
+
 /// Calls `cargo` with a custom driver which computes `haxmeta` files
 /// in `TARGET`. One `haxmeta` file is produced by crate. Each
 /// `haxmeta` file contains the full AST of one crate.
+///
+/// The driver doesn't communicate with `cargo hax` via stderr: for
+/// each crate, it writes its messages in a file next to the crate's
+/// `.rmeta` artifact (see [`HaxDriverMessages`]). We always ask `cargo`
+/// for JSON messages so that we know every artifact of the build,
+/// fresh or not, and collect the messages of the driver from them. If
+/// the user chose a message format that is not JSON (e.g. `short`),
+/// the diagnostics are rendered in that format by `cargo hax`.
 fn compute_haxmeta_files(options: &Options) -> (Vec<EmitHaxMetaMessage>, i32) {
+    let options_json =
+        serde_json::to_string(&options).expect("Options could not be converted to a JSON string");
+    let cache_key = get_hax_version();
+    let (explicit_msg_fmt, cargo_flags) = take_message_format(&options.cargo_flags);
+    let forward_json = explicit_msg_fmt
+        .as_deref()
+        .is_some_and(is_json_message_format);
     let mut cmd = {
         let mut cmd = process::Command::new("cargo");
         if let Some(toolchain) = toolchain() {
             cmd.env("RUSTUP_TOOLCHAIN", toolchain);
         }
-        cmd.args(["check".into()].iter().chain(options.cargo_flags.iter()));
+        cmd.args(["check".into()].iter().chain(cargo_flags.iter()));
         const COLOR_FLAG: &str = "--color";
-        let explicit_color_flag = options.cargo_flags.iter().any(|flag| flag == COLOR_FLAG);
-        if !explicit_color_flag && std::io::stderr().is_terminal() {
+        let explicit_color_flag = cargo_flags.iter().any(|flag| flag == COLOR_FLAG);
+        let colored = !explicit_color_flag && std::io::stderr().is_terminal();
+        if colored {
             cmd.args([COLOR_FLAG, "always"]);
         }
-        const MSG_FMT_FLAG: &str = "--message-format";
-        let explicit_msg_fmt_flag = options.cargo_flags.iter().any(|flag| flag == MSG_FMT_FLAG);
-        if !explicit_msg_fmt_flag && options.message_format == MessageFormat::Json {
-            cmd.args([MSG_FMT_FLAG, "json"]);
-        }
-        cmd.stderr(std::process::Stdio::piped());
+        let format = match &explicit_msg_fmt {
+            Some(format) if forward_json => format.clone(),
+            Some(format) => json_message_format(Some(format), colored),
+            None => json_message_format(
+                None,
+                colored && options.message_format == MessageFormat::Human,
+            ),
+        };
+        cmd.args([MSG_FMT_FLAG, &format]);
+        cmd.stdout(std::process::Stdio::piped());
         if !options.no_custom_target_directory {
             cmd.env("CARGO_TARGET_DIR", target_dir("hax"));
         };
         cmd.env("RUSTC_WORKSPACE_WRAPPER", get_hax_rustc_driver_path())
             .env(RUST_LOG_STYLE, rust_log_style())
             .env(RUSTFLAGS, rustflags())
-            .env("HAX_CARGO_CACHE_KEY", get_hax_version())
-            .env(
-                ENV_VAR_OPTIONS_FRONTEND,
-                serde_json::to_string(&options)
-                    .expect("Options could not be converted to a JSON string"),
-            );
+            .env("HAX_CARGO_CACHE_KEY", &cache_key)
+            .env(ENV_VAR_OPTIONS_FRONTEND, &options_json);
         cmd
     };
 
+    let key = HaxDriverMessages::key(&options_json, &cache_key);
+    let profile = matches!(&options.command, Command::Backend(backend) if backend.profile);
+    let render_messages = if explicit_msg_fmt.is_some() {
+        !forward_json
+    } else {
+        options.message_format == MessageFormat::Human
+    };
+    let mut driver_error = false;
     let mut child = cmd.spawn().unwrap();
     let haxmeta_files = {
         let mut haxmeta_files = vec![];
-        let stderr = child.stderr.take().unwrap();
-        let stderr = std::io::BufReader::new(stderr);
-        for line in std::io::BufReader::new(stderr).lines() {
-            if let Ok(line) = line {
-                if let Some(msg) = line.strip_prefix(HAX_DRIVER_STDERR_PREFIX) {
-                    use HaxDriverMessage;
-                    let msg = serde_json::from_str(msg).unwrap();
+        let stdout = std::io::BufReader::new(child.stdout.take().unwrap());
+        for line in stdout.lines() {
+            let Ok(line) = line else { continue };
+            let message = serde_json::from_str(&line).ok();
+            // The messages of `cargo` are forwarded as is when the user
+            // chose a JSON format. Otherwise, only the diagnostics are
+            // kept: we render them ourselves in human mode (or in the
+            // format chosen by the user), and forward them in JSON mode.
+            match &message {
+                _ if forward_json => println!("{line}"),
+                Some(cargo_metadata::Message::CompilerMessage(msg)) if render_messages => {
+                    if let Some(rendered) = &msg.message.rendered {
+                        eprint!("{rendered}");
+                    }
+                }
+                Some(cargo_metadata::Message::CompilerMessage(_)) | None => println!("{line}"),
+                Some(_) => (),
+            }
+            let Some(cargo_metadata::Message::CompilerArtifact(artifact)) = message else {
+                continue;
+            };
+            for rmeta in artifact
+                .filenames
+                .iter()
+                .filter(|path| path.extension() == Some("rmeta"))
+            {
+                let path = HaxDriverMessages::path(rmeta.as_std_path());
+                let Some(HaxDriverMessages { messages, .. }) = HaxDriverMessages::read(&path, key)
+                else {
+                    continue;
+                };
+                for msg in messages {
                     match msg {
                         HaxDriverMessage::EmitHaxMeta(data) => haxmeta_files.push(data),
+                        HaxDriverMessage::Error {
+                            crate_name,
+                            message,
+                        } => {
+                            driver_error = true;
+                            HaxMessage::DriverError {
+                                crate_name,
+                                message,
+                            }
+                            .report(options.message_format, None)
+                        }
+                        HaxDriverMessage::ProfilingData(data) => {
+                            if profile {
+                                HaxMessage::ProfilingData(data).report(options.message_format, None)
+                            }
+                        }
                     }
-                } else {
-                    eprintln!("{}", line);
                 }
             }
         }
+        // Make the order of the `haxmeta` files independent of the
+        // order in which `cargo` built the crates.
+        haxmeta_files.sort_by(|a, b| a.path.cmp(&b.path));
         haxmeta_files
     };
 
@@ -538,6 +813,8 @@ fn compute_haxmeta_files(options: &Options) -> (Vec<EmitHaxMetaMessage>, i32) {
     let exit_code = if !status.success() {
         HaxMessage::CargoBuildFailure.report(options.message_format, None);
         status.code().unwrap_or(254)
+    } else if driver_error {
+        1
     } else {
         0
     };
@@ -589,6 +866,12 @@ fn run_command(options: &Options, haxmeta_files: Vec<EmitHaxMetaMessage>) -> boo
             });
             false
         }
+        Command::Locate { position, dir } => {
+            let found = locate::locate(&position, &dir);
+            let error = found.is_empty();
+            HaxMessage::Located { position, found }.report(options.message_format, None);
+            error
+        }
         Command::Backend(backend) => {
             use hax_frontend_exporter::ThirBody as Body;
             use Backend;
@@ -600,6 +883,11 @@ fn run_command(options: &Options, haxmeta_files: Vec<EmitHaxMetaMessage>) -> boo
                 .report(options.message_format, None);
             }
 
+            let mut report = backend.report.as_ref().map(|_| TranslationReport {
+                hax_version: hax_types::HAX_VERSION.to_string(),
+                backend: backend.backend.to_string(),
+                items: vec![],
+            });
             let mut error = false;
             for EmitHaxMetaMessage {
                 working_dir,
@@ -618,8 +906,26 @@ fn run_command(options: &Options, haxmeta_files: Vec<EmitHaxMetaMessage>) -> boo
                         manifest_dir,
                         &backend,
                         options.message_format,
+                        report.as_mut(),
                     );
             }
+            if let (Some(path), Some(report)) = (&backend.report, report) {
+                match report.write(path) {
+                    Ok(()) => HaxMessage::TranslationReport {
+                        path: path.clone(),
+                        summary: report.summary(),
+                    }
+                    .report(options.message_format, None),
+                    Err(err) => {
+                        error = true;
+                        HaxMessage::TranslationReportFailure {
+                            path: path.clone(),
+                            message: err.to_string(),
+                        }
+                        .report(options.message_format, None)
+                    }
+                }
+            }
             error
         }
     }
@@ -642,11 +948,23 @@ fn main() {
                 ENV_VAR_OPTIONS_FRONTEND
             )
         }),
-        _ => Options::parse_from(args.iter()),
+        _ => {
+            let command = std::env::current_dir()
+                .map_err(|err| clap::Error::raw(clap::error::ErrorKind::Io, err))
+                .and_then(|dir| config::load(&dir, &args))
+                .and_then(|config| config::apply(Options::command(), &config))
+                .unwrap_or_else(|err| err.exit());
+            Options::from_arg_matches(&command.get_matches_from(args.iter()))
+                .unwrap_or_else(|err| err.exit())
+        }
     };
     options.normalize_paths();
 
-    let (haxmeta_files, exit_code) = compute_haxmeta_files(&options);
+    let (haxmeta_files, exit_code) = match options.command {
+        // `cargo hax locate` only reads source maps, no need to run cargo
+        Command::Locate { .. } => (vec![], 0),
+        _ => compute_haxmeta_files(&options),
+    };
     let error = run_command(&options, haxmeta_files);
 
     std::process::exit(if exit_code == 0 && error {
@@ -655,3 +973,159 @@ fn main() {
         exit_code
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flags(flags: &[&str]) -> Vec<String> {
+        flags.iter().map(|flag| flag.to_string()).collect()
+    }
+
+    #[test]
+    fn message_format_flag() {
+        assert_eq!(
+            take_message_format(&flags(&["-p", "krate"])),
+            (None, flags(&["-p", "krate"]))
+        );
+        assert_eq!(
+            take_message_format(&flags(&["--message-format", "short", "-p", "krate"])),
+            (Some("short".to_string()), flags(&["-p", "krate"]))
+        );
+        assert_eq!(
+            take_message_format(&flags(&["--message-format=json", "--release"])),
+            (Some("json".to_string()), flags(&["--release"]))
+        );
+        assert!(is_json_message_format("json"));
+        assert!(is_json_message_format(
+            "json-diagnostic-short,json-render-diagnostics"
+        ));
+        assert!(!is_json_message_format("short"));
+        assert!(!is_json_message_format("human"));
+    }
+
+    #[test]
+    fn json_message_format_of_user_format() {
+        assert_eq!(json_message_format(None, false), "json");
+        assert_eq!(
+            json_message_format(None, true),
+            "json-diagnostic-rendered-ansi"
+        );
+        assert_eq!(json_message_format(Some("human"), false), "json");
+        assert_eq!(
+            json_message_format(Some("short"), true),
+            "json-diagnostic-short,json-diagnostic-rendered-ansi"
+        );
+    }
+
+    fn def_id(name: &str) -> hax_frontend_exporter::DefId {
+        serde_json::from_value(serde_json::json!({
+            "contents": {
+                "id": 0,
+                "value": {
+                    "krate": "krate",
+                    "path": [{"data": {"ValueNs": name}, "disambiguator": 0}],
+                    "parent": null,
+                    "index": [0, 0, null],
+                    "is_local": true,
+                    "kind": "Fn",
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    fn diagnostic(details: &str, owner: Option<&str>) -> hax_types::diagnostics::Diagnostics {
+        hax_types::diagnostics::Diagnostics {
+            kind: hax_types::diagnostics::Kind::AssertionFailure {
+                details: details.to_string(),
+            },
+            span: vec![],
+            context: "context".to_string(),
+            owner_id: owner.map(def_id),
+        }
+    }
+
+    #[test]
+    fn diagnostics_filter_duplicates() {
+        let mut filter = DiagnosticsFilter::default();
+        let f = Some("f");
+        assert_eq!(
+            filter.verdict(&diagnostic("a", f)),
+            DiagnosticVerdict::Report
+        );
+        assert_eq!(
+            filter.verdict(&diagnostic("a", f)),
+            DiagnosticVerdict::Duplicate
+        );
+        // Diagnostics are identified by code, span and context.
+        assert_eq!(
+            filter.verdict(&diagnostic("b", f)),
+            DiagnosticVerdict::Duplicate
+        );
+        let mut other_context = diagnostic("a", f);
+        other_context.context = "other".to_string();
+        assert_eq!(filter.verdict(&other_context), DiagnosticVerdict::Report);
+        let Some(HaxMessage::SuppressedDiagnostics {
+            duplicates,
+            over_budget,
+        }) = filter.summary()
+        else {
+            panic!("expected a summary")
+        };
+        assert_eq!((duplicates, over_budget), (2, vec![]));
+    }
+
+    #[test]
+    fn diagnostics_filter_budgets() {
+        let mut filter = DiagnosticsFilter {
+            max_per_item: Some(1),
+            max_per_code: Some(2),
+            ..DiagnosticsFilter::default()
+        };
+        let with_context = |owner: Option<&str>, context: &str| {
+            let mut diagnostic = diagnostic("a", owner);
+            diagnostic.context = context.to_string();
+            diagnostic
+        };
+        let verdicts: Vec<_> = [
+            with_context(Some("f"), "1"),
+            with_context(Some("f"), "2"),
+            with_context(Some("g"), "3"),
+            with_context(None, "4"),
+        ]
+        .iter()
+        .map(|diagnostic| filter.verdict(diagnostic))
+        .collect();
+        assert_eq!(
+            verdicts,
+            [
+                DiagnosticVerdict::Report,
+                // Over the budget of `f`
+                DiagnosticVerdict::OverBudget,
+                DiagnosticVerdict::Report,
+                // Over the budget of the code
+                DiagnosticVerdict::OverBudget,
+            ]
+        );
+        let code = diagnostic("a", None).kind.code();
+        let Some(HaxMessage::SuppressedDiagnostics {
+            duplicates,
+            over_budget,
+        }) = filter.summary()
+        else {
+            panic!("expected a summary")
+        };
+        assert_eq!((duplicates, over_budget), (0, vec![(code, 2)]));
+    }
+
+    #[test]
+    fn diagnostics_filter_without_suppression() {
+        let mut filter = DiagnosticsFilter::default();
+        assert_eq!(
+            filter.verdict(&diagnostic("a", None)),
+            DiagnosticVerdict::Report
+        );
+        assert!(filter.summary().is_none());
+    }
+}
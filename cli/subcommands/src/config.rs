@@ -0,0 +1,404 @@
+//! Configuration files for `cargo hax`.
+//!
+//! The options of `cargo hax` can be set in the tables
+//! `[workspace.metadata.hax]` and `[package.metadata.hax]` of the
+//! manifests of the workspace and of the package, and in `hax.toml`
+//! files next to these manifests. The manifest of the package is found
+//! as `cargo` does: it is given by `--manifest-path`, or it is the
+//! first `Cargo.toml` in the current directory or its ancestors. Its
+//! workspace root is the first ancestor manifest with a `[workspace]`
+//! table (or the one given by `package.workspace`).
+//!
+//! From the lowest to the highest precedence, configurations are read
+//! from `[workspace.metadata.hax]`, `[package.metadata.hax]`, the
+//! `hax.toml` of the workspace root and the `hax.toml` of the
+//! package. Options given on the command line take precedence over
+//! all of them.
+//!
+//! A configuration is a TOML table whose keys are long option names
+//! of `cargo hax`. Options of subcommands are given in nested tables
+//! named after the subcommand, which allows for per-backend profiles:
+//!
+//! ```toml
+//! cargo-args = ["-p", "my-crate"]
+//!
+//! [into]
+//! include-namespaces = ["+my_crate::**", "-my_crate::internal::**"]
+//!
+//! [into.fstar]
+//! z3rlimit = 100
+//! interfaces = "+!my_crate::**"
+//! ```
+//!
+//! Values from configurations are installed as default values of the
+//! options of the `clap` command.
+
+use clap::error::ErrorKind;
+use clap::{Arg, ArgAction, Command};
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+
+/// Name of the configuration file of `cargo hax`
+const CONFIG_FILE: &str = "hax.toml";
+
+/// Name of the manifests of `cargo`
+const MANIFEST: &str = "Cargo.toml";
+
+/// Reads the TOML file `path`, if it exists.
+fn read(path: &Path) -> Result<Option<Table>, clap::Error> {
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return Ok(None);
+    };
+    contents.parse::<Table>().map(Some).map_err(|err| {
+        clap::Error::raw(
+            ErrorKind::InvalidValue,
+            format!("could not parse `{}`: {err}\n", path.display()),
+        )
+    })
+}
+
+/// The value of the flag `--manifest-path` in the arguments `args` of
+/// `cargo hax`, if any.
+fn manifest_path_arg(args: &[String]) -> Option<&str> {
+    const FLAG: &str = "--manifest-path";
+    let mut args = args.iter();
+    let mut path = None;
+    while let Some(arg) = args.next() {
+        if arg == FLAG {
+            path = args.next().map(String::as_str);
+        } else if let Some(value) = arg
+            .strip_prefix(FLAG)
+            .and_then(|rest| rest.strip_prefix('='))
+        {
+            path = Some(value);
+        }
+    }
+    path
+}
+
+/// The workspace root of the manifest `manifest`, whose contents are
+/// `contents`.
+fn workspace_root(manifest: &Path, contents: &Table) -> Result<Option<PathBuf>, clap::Error> {
+    let dir = manifest.parent().unwrap_or(Path::new(""));
+    if contents.contains_key("workspace") {
+        return Ok(Some(manifest.to_path_buf()));
+    }
+    if let Some(Value::String(root)) = contents
+        .get("package")
+        .and_then(|package| package.get("workspace"))
+    {
+        return Ok(Some(dir.join(root).join(MANIFEST)));
+    }
+    for ancestor in dir.ancestors().skip(1) {
+        let candidate = ancestor.join(MANIFEST);
+        if read(&candidate)?.is_some_and(|contents| contents.contains_key("workspace")) {
+            return Ok(Some(candidate));
+        }
+    }
+    Ok(None)
+}
+
+/// Reads the configuration of `cargo hax` run in the directory `dir`
+/// with the arguments `args`.
+pub fn load(dir: &Path, args: &[String]) -> Result<Table, clap::Error> {
+    let manifest = match manifest_path_arg(args) {
+        Some(path) => Some(dir.join(path)),
+        None => dir
+            .ancestors()
+            .map(|ancestor| ancestor.join(MANIFEST))
+            .find(|manifest| manifest.is_file()),
+    };
+    let Some(manifest) = manifest else {
+        return Ok(read(&dir.join(CONFIG_FILE))?.unwrap_or_default());
+    };
+    let contents = read(&manifest)?.unwrap_or_default();
+    let root = workspace_root(&manifest, &contents)?;
+    let root_contents = match &root {
+        Some(root) if *root != manifest => read(root)?.unwrap_or_default(),
+        _ => contents.clone(),
+    };
+    let metadata = |contents: &Table, section: &str| match contents
+        .get(section)
+        .and_then(|section| section.get("metadata"))
+        .and_then(|metadata| metadata.get("hax"))
+    {
+        Some(Value::Table(hax)) => Some(hax.clone()),
+        _ => None,
+    };
+    let mut config = Table::new();
+    let mut configs = vec![
+        metadata(&root_contents, "workspace"),
+        metadata(&contents, "package"),
+    ];
+    let dir_of = |manifest: &Path| manifest.parent().unwrap_or(Path::new("")).join(CONFIG_FILE);
+    if let Some(root) = root.as_ref().filter(|root| **root != manifest) {
+        configs.push(read(&dir_of(root))?);
+    }
+    configs.push(read(&dir_of(&manifest))?);
+    for hax in configs.into_iter().flatten() {
+        merge(&mut config, hax);
+    }
+    Ok(config)
+}
+
+/// Merges `overrides` into `base`, recursively.
+fn merge(base: &mut Table, overrides: Table) {
+    for (key, value) in overrides {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(base)), Value::Table(overrides)) => merge(base, overrides),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Installs the values of `config` as default values for the options
+/// of `command`.
+pub fn apply(mut command: Command, config: &Table) -> Result<Command, clap::Error> {
+    for (key, value) in config {
+        if let (Value::Table(table), Some(subcommand)) = (value, command.find_subcommand(key)) {
+            let subcommand = apply(subcommand.clone(), table)?;
+            command = command.mut_subcommand(key, |_| subcommand);
+            continue;
+        }
+        let Some(arg) = command
+            .get_arguments()
+            .find(|arg| arg.get_long() == Some(key) || arg.get_id() == &key.replace('-', "_"))
+        else {
+            return Err(clap::Error::raw(
+                ErrorKind::UnknownArgument,
+                format!(
+                    "unknown option `{key}` for `{}` in hax configuration\n",
+                    command.get_name()
+                ),
+            ));
+        };
+        let id = arg.get_id().clone();
+        let defaults = default_values(key, arg, value)?;
+        check_default_values(command.get_name(), key, arg, &defaults)?;
+        command = command.mut_arg(id, |arg| arg.default_values(defaults));
+    }
+    Ok(command)
+}
+
+/// Converts a TOML value into default values for the option `arg`.
+fn default_values(key: &str, arg: &Arg, value: &Value) -> Result<Vec<String>, clap::Error> {
+    let invalid = |expected: &str| {
+        clap::Error::raw(
+            ErrorKind::InvalidValue,
+            format!("invalid value for option `{key}` in hax configuration: expected {expected}\n"),
+        )
+    };
+    let multiple = matches!(arg.get_action(), ArgAction::Append)
+        || arg.get_num_args().is_some_and(|n| n.max_values() > 1);
+    Ok(match (arg.get_action(), value) {
+        // A boolean flag given in a configuration means the flag is set
+        (ArgAction::SetTrue, Value::Boolean(b)) => vec![b.to_string()],
+        (ArgAction::SetFalse, Value::Boolean(b)) => vec![(!b).to_string()],
+        (ArgAction::SetTrue | ArgAction::SetFalse, _) => return Err(invalid("a boolean")),
+        (_, Value::String(s)) => vec![s.clone()],
+        (_, Value::Integer(n)) => vec![n.to_string()],
+        (_, Value::Float(n)) => vec![n.to_string()],
+        (_, Value::Boolean(b)) => vec![b.to_string()],
+        (_, Value::Array(values)) if multiple => values
+            .iter()
+            .map(|value| match value {
+                Value::Array(_) | Value::Table(_) => Err(invalid("an array of values")),
+                value => default_values(key, arg, value),
+            })
+            .collect::<Result<Vec<_>, _>>()?
+            .concat(),
+        (_, Value::Array(_)) => return Err(invalid("a single value")),
+        _ => return Err(invalid("a value")),
+    })
+}
+
+/// Checks that `defaults` are valid values for the option `arg` of
+/// the command `command`, by parsing them with the parser of `arg`.
+fn check_default_values(
+    command: &str,
+    key: &str,
+    arg: &Arg,
+    defaults: &[String],
+) -> Result<(), clap::Error> {
+    let mut check = Arg::new(arg.get_id().clone())
+        .long(key.to_string())
+        .action(arg.get_action().clone())
+        .value_parser(arg.get_value_parser().clone())
+        .default_values(defaults);
+    if let Some(num_args) = arg.get_num_args() {
+        check = check.num_args(num_args);
+    }
+    Command::new(command.to_string())
+        .arg(check)
+        .try_get_matches_from([command])
+        .map(|_| ())
+        .map_err(|err| {
+            clap::Error::raw(
+                ErrorKind::InvalidValue,
+                format!(
+                    "invalid value for option `{key}` in hax configuration: {}\n",
+                    err.kind()
+                ),
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(contents: &str) -> Table {
+        contents.parse().unwrap()
+    }
+
+    fn write(dir: &Path, file: &str, contents: &str) {
+        let path = dir.join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    fn command() -> Command {
+        Command::new("hax")
+            .arg(Arg::new("cargo_args").long("cargo-args").num_args(1..))
+            .arg(
+                Arg::new("verbose")
+                    .long("verbose")
+                    .action(ArgAction::SetTrue),
+            )
+            .subcommand(
+                Command::new("into").subcommand(
+                    Command::new("fstar").arg(
+                        Arg::new("z3rlimit")
+                            .long("z3rlimit")
+                            .value_parser(clap::value_parser!(u32))
+                            .default_value("15"),
+                    ),
+                ),
+            )
+    }
+
+    #[test]
+    fn precedence() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(
+            root,
+            MANIFEST,
+            "[workspace]\nmembers = [\"krate\"]\n[workspace.metadata.hax]\na = 1\nb = 1\nc = 1\nd = 1\n",
+        );
+        write(root, CONFIG_FILE, "c = 3\nd = 3\n");
+        write(
+            root,
+            "krate/Cargo.toml",
+            "[package]\nname = \"krate\"\n[package.metadata.hax]\nb = 2\nc = 2\nd = 2\n",
+        );
+        write(root, "krate/hax.toml", "d = 4\n");
+        write(root, "krate/src/lib.rs", "");
+        let expected = table("a = 1\nb = 2\nc = 3\nd = 4\n");
+        // From the package, from one of its subdirectories, and with
+        // `--manifest-path` from the workspace root
+        assert_eq!(load(&root.join("krate"), &[]).unwrap(), expected);
+        assert_eq!(load(&root.join("krate/src"), &[]).unwrap(), expected);
+        let args = ["-C", "--manifest-path", "krate/Cargo.toml", ";"].map(String::from);
+        assert_eq!(load(root, &args).unwrap(), expected);
+        let args = ["-C", "--manifest-path=krate/Cargo.toml", ";"].map(String::from);
+        assert_eq!(load(root, &args).unwrap(), expected);
+        // From the workspace root
+        assert_eq!(
+            load(root, &[]).unwrap(),
+            table("a = 1\nb = 1\nc = 3\nd = 3\n")
+        );
+    }
+
+    #[test]
+    fn command_line_takes_precedence() {
+        let config = table("verbose = true\n[into.fstar]\nz3rlimit = 100\n");
+        let command = apply(command(), &config).unwrap();
+        let matches = command.clone().get_matches_from(["hax", "into", "fstar"]);
+        assert!(matches.get_flag("verbose"));
+        let (_, into) = matches.subcommand().unwrap();
+        let (_, fstar) = into.subcommand().unwrap();
+        assert_eq!(fstar.get_one::<u32>("z3rlimit"), Some(&100));
+        let matches = command.get_matches_from(["hax", "into", "fstar", "--z3rlimit", "30"]);
+        let (_, into) = matches.subcommand().unwrap();
+        let (_, fstar) = into.subcommand().unwrap();
+        assert_eq!(fstar.get_one::<u32>("z3rlimit"), Some(&30));
+    }
+
+    #[test]
+    fn options_of_cargo_hax() {
+        use clap::{CommandFactory, FromArgMatches};
+        use hax_types::cli_options::{Backend, Command, Options};
+        let config = table("cargo-args = [\"-p\", \"krate\"]\n[into.fstar]\nz3rlimit = 100\n");
+        let command = apply(Options::command(), &config).unwrap();
+        let matches = command.get_matches_from(["cargo-hax", "into", "fstar"]);
+        let options = Options::from_arg_matches(&matches).unwrap();
+        assert_eq!(options.cargo_flags, ["-p", "krate"]);
+        let Command::Backend(backend) = options.command else {
+            panic!("expected `into`")
+        };
+        let Backend::Fstar(fstar) = backend.backend else {
+            panic!("expected `fstar`")
+        };
+        assert_eq!(fstar.z3rlimit, 100);
+    }
+
+    #[test]
+    fn nested_tables_merge() {
+        let mut config = table("[into]\na = 1\n[into.fstar]\nz3rlimit = 10\nb = 1\n");
+        merge(&mut config, table("[into.fstar]\nz3rlimit = 20\n"));
+        assert_eq!(
+            config,
+            table("[into]\na = 1\n[into.fstar]\nz3rlimit = 20\nb = 1\n")
+        );
+    }
+
+    #[test]
+    fn defaults_of_values() {
+        let value = |value: &str| table(&format!("x = {value}"))["x"].clone();
+        let flag = Arg::new("x").action(ArgAction::SetTrue);
+        let negated_flag = Arg::new("x").action(ArgAction::SetFalse);
+        let scalar = Arg::new("x").action(ArgAction::Set);
+        let list = Arg::new("x").action(ArgAction::Append);
+        assert_eq!(
+            default_values("x", &flag, &value("true")).unwrap(),
+            ["true"]
+        );
+        assert_eq!(
+            default_values("x", &negated_flag, &value("true")).unwrap(),
+            ["false"]
+        );
+        assert_eq!(default_values("x", &scalar, &value("42")).unwrap(), ["42"]);
+        assert_eq!(
+            default_values("x", &scalar, &value("\"a\"")).unwrap(),
+            ["a"]
+        );
+        assert_eq!(
+            default_values("x", &list, &value("[\"-p\", \"krate\"]")).unwrap(),
+            ["-p", "krate"]
+        );
+        assert!(default_values("x", &flag, &value("1")).is_err());
+        assert!(default_values("x", &scalar, &value("[1, 2]")).is_err());
+        assert!(default_values("x", &list, &value("[[1]]")).is_err());
+        assert!(default_values("x", &scalar, &value("{ y = 1 }")).is_err());
+    }
+
+    #[test]
+    fn invalid_configurations_are_rejected() {
+        let error = apply(command(), &table("unknown = 1\n")).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::UnknownArgument);
+        let error = apply(command(), &table("[into.fstar]\nunknown = 1\n")).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::UnknownArgument);
+        for config in [
+            "verbose = { a = 1 }\n",
+            "verbose = \"yes\"\n",
+            "[into.fstar]\nz3rlimit = \"many\"\n",
+            "[into.fstar]\nz3rlimit = [1, 2]\n",
+        ] {
+            let error = apply(command(), &table(config)).unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InvalidValue, "{config}");
+        }
+    }
+}
@@ -0,0 +1,215 @@
+//! Implementation of `cargo hax locate`.
+//!
+//! Translated files come with source maps (`<FILE>.map`, see
+//! [`SourceMap`]) that map positions in the generated code to the Rust
+//! spans they were translated from. A position in a translated file is
+//! mapped to the closest preceding segment of its source map. A
+//! position in a Rust file is mapped to every generated position whose
+//! segment is the closest preceding one in that Rust file, across all
+//! source maps found under a given directory.
+
+use hax_types::cli_options::FilePosition;
+use hax_types::engine_api::{SourceMap, SourceMapSegment};
+use std::path::{Path, PathBuf};
+
+/// Path of the source map of a translated file.
+fn source_map_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".map");
+    path.with_file_name(file_name)
+}
+
+fn read_source_map(path: &Path) -> Option<SourceMap> {
+    serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()
+}
+
+/// Lists the source maps under `dir`, skipping hidden directories and
+/// cargo `target` directories.
+fn find_source_maps(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return vec![];
+    };
+    let mut source_maps = vec![];
+    for path in entries.flatten().map(|entry| entry.path()) {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if path.is_dir() {
+            if !name.starts_with('.') && name != "target" {
+                source_maps.extend(find_source_maps(&path));
+            }
+        } else if name.ends_with(".map") {
+            source_maps.push(path);
+        }
+    }
+    source_maps.sort();
+    source_maps
+}
+
+/// Tells whether `source`, a path found in a source map, designates
+/// `path`. Relative sources are relative to the directory of the
+/// translated crate, which is unknown here: we compare them with the
+/// trailing components of `path`.
+fn same_file(source: &Path, path: &Path) -> bool {
+    if source.is_absolute() {
+        matches!((source.canonicalize(), path.canonicalize()), (Ok(source), Ok(path)) if source == path)
+    } else {
+        path.ends_with(source)
+    }
+}
+
+/// `(line, column)` of a position, zero-based. A missing column means
+/// the end of the line.
+fn zero_based(position: &FilePosition) -> (usize, usize) {
+    (
+        position.line - 1,
+        position.column.map_or(usize::MAX, |column| column - 1),
+    )
+}
+
+fn one_based(path: PathBuf, line: usize, column: usize) -> FilePosition {
+    FilePosition {
+        path,
+        line: line + 1,
+        column: Some(column + 1),
+    }
+}
+
+/// Maps a position in a translated file back to Rust.
+fn locate_generated(position: &FilePosition, source_map: &SourceMap) -> Vec<FilePosition> {
+    let target = zero_based(position);
+    source_map
+        .segments()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|segment| (segment.gen_line, segment.gen_column) <= target)
+        .max_by_key(|segment| (segment.gen_line, segment.gen_column))
+        .and_then(|segment| {
+            let source = source_map.sources.get(segment.source)?;
+            Some(one_based(
+                PathBuf::from(source),
+                segment.src_line,
+                segment.src_column,
+            ))
+        })
+        .into_iter()
+        .collect()
+}
+
+/// Maps a position in a Rust file to the translated files found under
+/// `dir`.
+fn locate_rust(position: &FilePosition, dir: &Path) -> Vec<FilePosition> {
+    let target = zero_based(position);
+    let segments: Vec<(PathBuf, SourceMapSegment)> = find_source_maps(dir)
+        .into_iter()
+        .filter_map(|path| Some((read_source_map(&path)?, path)))
+        .flat_map(|(source_map, path)| {
+            let generated = path.with_extension("");
+            source_map
+                .segments()
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|segment| {
+                    source_map
+                        .sources
+                        .get(segment.source)
+                        .is_some_and(|source| same_file(Path::new(source), &position.path))
+                })
+                .map(move |segment| (generated.clone(), segment))
+                .collect::<Vec<_>>()
+        })
+        .filter(|(_, segment)| (segment.src_line, segment.src_column) <= target)
+        .collect();
+    let Some(closest) = segments
+        .iter()
+        .map(|(_, segment)| (segment.src_line, segment.src_column))
+        .max()
+    else {
+        return vec![];
+    };
+    let mut positions: Vec<_> = segments
+        .into_iter()
+        .filter(|(_, segment)| (segment.src_line, segment.src_column) == closest)
+        .map(|(path, segment)| one_based(path, segment.gen_line, segment.gen_column))
+        .collect();
+    positions.sort_by_key(|position| (position.path.clone(), position.line, position.column));
+    positions.dedup();
+    positions
+}
+
+/// Looks up `position`, which is either in a translated file (if it
+/// has a source map) or in a Rust file.
+pub fn locate(position: &FilePosition, dir: &Path) -> Vec<FilePosition> {
+    match read_source_map(&source_map_path(&position.path)) {
+        Some(source_map) => locate_generated(position, &source_map),
+        None => locate_rust(position, dir),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A crate `krate` with a Rust file `src/lib.rs`, translated to
+    /// `proofs/Krate.fst` and `proofs/Krate.fsti`.
+    fn setup(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("hax-locate-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("src")).unwrap();
+        std::fs::create_dir_all(dir.join("proofs")).unwrap();
+        // Maps the generated positions 1:1 and 2:3 to the Rust
+        // positions 1:1 and 3:5 in the `.fst`, and 1:1 to 3:5 in the
+        // `.fsti`.
+        for (file, mappings) in [("Krate.fst", "AAAA;EAEI"), ("Krate.fsti", "AAEI")] {
+            let source_map = SourceMap {
+                mappings: mappings.to_string(),
+                sourceRoot: String::new(),
+                sources: vec!["src/lib.rs".to_string()],
+                sourcesContent: vec![],
+                names: vec![],
+                version: 3,
+                file: file.to_string(),
+            };
+            let path = dir.join("proofs").join(format!("{file}.map"));
+            std::fs::write(path, serde_json::to_string(&source_map).unwrap()).unwrap();
+        }
+        dir
+    }
+
+    fn position(path: PathBuf, line: usize, column: Option<usize>) -> FilePosition {
+        FilePosition { path, line, column }
+    }
+
+    #[test]
+    fn locate_generated_position() {
+        let dir = setup("generated");
+        let fst = dir.join("proofs/Krate.fst");
+        let found = |line, column| locate(&position(fst.clone(), line, column), &dir);
+        let rust = |line, column| position("src/lib.rs".into(), line, Some(column));
+        assert_eq!(found(1, Some(1)), [rust(1, 1)]);
+        // The closest preceding segment is used.
+        assert_eq!(found(2, Some(2)), [rust(1, 1)]);
+        assert_eq!(found(2, Some(9)), [rust(3, 5)]);
+        assert_eq!(found(7, None), [rust(3, 5)]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn locate_rust_position() {
+        let dir = setup("rust");
+        let lib = dir.join("src/lib.rs");
+        let found = |line, column| locate(&position(lib.clone(), line, column), &dir);
+        assert_eq!(
+            found(3, Some(8)),
+            [
+                position(dir.join("proofs/Krate.fst"), 2, Some(3)),
+                position(dir.join("proofs/Krate.fsti"), 1, Some(1)),
+            ]
+        );
+        assert_eq!(
+            found(2, None),
+            [position(dir.join("proofs/Krate.fst"), 1, Some(1))]
+        );
+        // Other Rust files have no translation.
+        assert!(locate(&position(dir.join("src/other.rs"), 3, None), &dir).is_empty());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
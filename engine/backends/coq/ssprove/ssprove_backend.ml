@@ -1811,11 +1811,6 @@ let print_item m (analysis_data : StaticAnalysis.analysis_data)
   in
   Print.pitem item
 
-let cleanup_item_strings =
-  List.map ~f:String.strip
-  >> List.filter ~f:(String.is_empty >> not)
-  >> String.concat ~sep:"\n\n"
-
 (* module ConCert = struct *)
 (*   let translate_concert_annotations *)
 (*       (analysis_data : StaticAnalysis.analysis_data) (e : item) : *)
@@ -2191,22 +2186,14 @@ let cleanup_item_strings =
 (*     ] *)
 (* end *)
 
-let process_annotation (x : 'a list) (f2 : ('b * ('a -> 'b)) list) : 'b list =
-  List.concat_map
-    ~f:(fun (d, f) ->
-      let temp = List.map ~f x in
-      if List.is_empty (List.concat temp) then [] else d :: temp)
-    f2
-
-let string_of_items m (x, y) =
-  cleanup_item_strings
-    (List.map ~f:decls_to_string
-       (process_annotation x
-          [
-            ([], print_item m y);
-            (* ConCert.(concert_header, translate_concert_annotations y); *)
-          ]
-          (* @ ConCert.concert_contract_type_decls x *)))
+let string_of_items m (x, y) : Generic_printer.AnnotatedString.t =
+  List.map x ~f:(fun (item : AST.item) ->
+      (item.span, print_item m y item |> decls_to_string |> String.strip))
+  (* ConCert.(concert_header, translate_concert_annotations y) *)
+  (* @ ConCert.concert_contract_type_decls x *)
+  |> List.filter ~f:(snd >> String.is_empty >> not)
+  |> List.map ~f:(uncurry Generic_printer.AnnotatedString.of_span)
+  |> Generic_printer.AnnotatedString.concat_list ~sep:"\n\n"
 
 (* TODO move into string_of_items, as SSP.AST decl *)
 let hardcoded_coq_headers =
@@ -2247,13 +2234,20 @@ let translate m (_bo : BackendOptions.t) ~(bundles : AST.item list list)
            String.concat ~sep:"_"
              (List.map ~f:(map_first_letter String.uppercase) ns)
          in
-         let file_content =
-           hardcoded_coq_headers ^ "\n"
-           ^ string_of_items m (items, analysis_data)
-           ^ "\n"
+         let annotated =
+           let open Generic_printer.AnnotatedString in
+           concat_list
+             [
+               pure (hardcoded_coq_headers ^ "\n");
+               string_of_items m (items, analysis_data);
+               pure "\n";
+             ]
+         in
+         let contents, sourcemap =
+           Generic_printer.AnnotatedString.
+             (to_string annotated, Some (to_sourcemap annotated))
          in
-         Types.
-           { path = mod_name ^ ".v"; contents = file_content; sourcemap = None })
+         Types.{ path = mod_name ^ ".v"; contents; sourcemap })
 
 let apply_phases (_bo : BackendOptions.t) (i : Ast.Rust.item list) :
     AST.item list =
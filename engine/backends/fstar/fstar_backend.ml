@@ -873,7 +873,7 @@ struct
 
   let add_clauses_effect_type ~self ~no_tot_abbrev (attrs : attrs) typ :
       F.AST.typ =
-    let attr_term ?keep_last_args ?map_expr kind f =
+    let attr_term ?keep_last_args ?map_expr ?(pexpr = pexpr) kind f =
       (* A clause on a method with a `self` produces a function whose first argument is `self_`.
          `subst_self` will substitute that first argument `self_` into the provided local identifier `self`.
       *)
@@ -913,8 +913,16 @@ struct
       visitor#visit_expr ()
     in
     let decreases =
-      attr_term Decreases ~map_expr:extract_any_to_unit_payload (fun t ->
-          F.AST.Decreases (t, None))
+      (* A tuple measure is lexicographic: it is extracted as a [%[...]]. *)
+      let pmeasure (e : expr) =
+        match e.e with
+        | Construct { constructor = `TupleCons n; fields; base = None }
+          when n > 1 ->
+            F.term @@ F.AST.LexList (List.map ~f:(snd >> pexpr) fields)
+        | _ -> pexpr e
+      in
+      attr_term Decreases ~map_expr:extract_any_to_unit_payload ~pexpr:pmeasure
+        (fun t -> F.AST.Decreases (t, None))
     in
     let smtpat =
       let smt_pat = F.term_of_lid [ "SMTPat" ] in
@@ -1031,6 +1039,63 @@ struct
         @@ F.AST.TopLevelLet (NoLetQualifier, [ (F.pat @@ pat, term) ]);
       ]
     in
+    (* A type with an invariant (see [hax_lib::invariant]) is extracted
+       as a raw type, followed by the refinement of the raw type by the
+       invariant. *)
+    let invariant = Attrs.associated_fn Refine e.attrs in
+    let type_name name =
+      let name = (RenderId.render name).name in
+      if Option.is_some invariant then name ^ "_raw" else name
+    in
+    let invariant_decls name generics =
+      match invariant with
+      | Some (_, [ { pat; _ } ], body) ->
+          let binder, _ = U.Expect.pbinding_simple pat |> Option.value_exn in
+          (* The binder has the raw type inside of the refinement: its
+             ascriptions with the refined type are dropped. *)
+          let body =
+            let visitor =
+              object
+                inherit [_] U.Visitors.map as super
+
+                method! visit_expr () e =
+                  match e.e with
+                  | Ascription { e = { e = LocalVar var; _ } as e; _ }
+                    when [%eq: local_ident] binder var ->
+                      e
+                  | _ -> super#visit_expr () e
+              end
+            in
+            visitor#visit_expr () body
+          in
+          let raw =
+            F.mk_e_app
+              (F.term_of_lid [ type_name name ])
+              (List.map
+                 ~f:FStarBinder.(of_generic_param e.span >> to_ident)
+                 generics.params
+              |> List.map ~f:(fun id -> F.term @@ F.AST.Name (F.lid_of_id id))
+              )
+          in
+          let pat =
+            F.pat
+            @@ F.AST.PatVar (F.id @@ (RenderId.render name).name, None, [])
+          in
+          F.decls
+          @@ F.AST.TopLevelLet
+               ( NoLetQualifier,
+                 [
+                   ( F.pat
+                     @@ F.AST.PatApp
+                          ( pat,
+                            FStarBinder.(
+                              of_generics e.span generics
+                              |> List.map ~f:to_pattern) ),
+                     F.mk_refined (plocal_ident_str binder) raw (fun ~x ->
+                         pexpr body) );
+                 ] )
+      | _ -> []
+    in
     match e.v with
     | Alias { name; item } ->
         (* These should come from bundled items (in the case of cyclic module dependencies).
@@ -1061,6 +1126,10 @@ struct
           @@ F.AST.TopLevelLet (qualifier, [ (pat, pexpr body) ])
         in
         let is_const = List.is_empty params in
+        (* The definitions of pure functions (see [hax_lib::pure]) are
+           part of the interfaces, so that specifications can unfold
+           them. *)
+        let is_transparent = is_const || Attrs.pure e.attrs in
         let ty =
           add_clauses_effect_type
             ~self:
@@ -1068,7 +1137,7 @@ struct
                let* { var; _ } = Destruct.pat_PBinding hd.pat in
                let*? () = String.equal var.name "self" in
                Some var)
-            ~no_tot_abbrev:(ctx.interface_mode && not is_const)
+            ~no_tot_abbrev:(ctx.interface_mode && not is_transparent)
             e.attrs (pty body.span body.typ)
         in
         let arrow_typ =
@@ -1105,7 +1174,8 @@ struct
         let impl, full =
           if is_erased then (erased, erased) else ([ impl ], [ full ])
         in
-        if ctx.interface_mode && ((not is_const) || is_erased) then intf :: impl
+        if ctx.interface_mode && ((not is_transparent) || is_erased) then
+          intf :: impl
         else full
     | TyAlias { name; generics; ty } ->
         let pat =
@@ -1159,12 +1229,12 @@ struct
           is_struct = true;
         } ->
         F.decls
-        @@ F.AST.Tycon
+          (F.AST.Tycon
              ( false,
                false,
                [
                  F.AST.TyconRecord
-                   ( F.id @@ (RenderId.render name).name,
+                   ( F.id @@ type_name name,
                      FStarBinder.of_generics e.span generics
                      |> List.map ~f:FStarBinder.implicit_to_explicit
                      |> List.map ~f:FStarBinder.to_binder,
@@ -1182,11 +1252,12 @@ struct
                            [],
                            pmaybe_refined_ty e.span fvars attrs fname ty ))
                        (inits arguments) );
-               ] )
+               ] ))
+        @ invariant_decls name generics
     | Type { name; generics; variants; _ } ->
         let self =
           F.mk_e_app
-            (F.term_of_lid [ (RenderId.render name).name ])
+            (F.term_of_lid [ type_name name ])
             (List.map
                ~f:FStarBinder.(of_generic_param e.span >> to_ident)
                generics.params
@@ -1224,18 +1295,19 @@ struct
             variants
         in
         F.decls
-        @@ F.AST.Tycon
+          (F.AST.Tycon
              ( false,
                false,
                [
                  F.AST.TyconVariant
-                   ( F.id @@ (RenderId.render name).name,
+                   ( F.id @@ type_name name,
                      FStarBinder.of_generics e.span generics
                      |> List.map ~f:FStarBinder.implicit_to_explicit
                      |> List.map ~f:FStarBinder.to_binder,
                      None,
                      constructors );
-               ] )
+               ] ))
+        @ invariant_decls name generics
     | IMacroInvokation { macro; argument; span } -> (
         let open Hacspeclib_macro_parser in
         let unsupported_macro () =
@@ -1750,7 +1822,7 @@ let strings_of_item (bo : BackendOptions.t) m items (item : item) :
 type rec_prefix = NonRec | FirstMutRec | MutRec
 
 let string_of_items ~mod_name ~bundles (bo : BackendOptions.t) m items :
-    string * string =
+    Generic_printer.AnnotatedString.t * Generic_printer.AnnotatedString.t =
   let collect_trait_goal_idents =
     object
       inherit [_] Visitors.reduce as super
@@ -1827,34 +1899,43 @@ let string_of_items ~mod_name ~bundles (bo : BackendOptions.t) m items :
       ~f:(fun item ->
         let recursivity_prefix = get_recursivity_prefix item in
         let strs = strings_of_item bo m items item in
-        match (recursivity_prefix, item.v) with
+        (match (recursivity_prefix, item.v) with
         | FirstMutRec, Fn _ ->
             replace_in_strs ~pattern:"let" ~with_:"let rec" strs
         | MutRec, Fn _ -> replace_in_strs ~pattern:"let" ~with_:"and" strs
         | MutRec, Type _ -> replace_in_strs ~pattern:"type" ~with_:"and" strs
         | _ -> strs)
+        |> List.map ~f:(fun str -> (item.span, str)))
       its
-    |> List.map ~f:(map_string ~f:String.strip)
+    |> List.map ~f:(fun (span, str) -> (span, map_string ~f:String.strip str))
     |> List.filter
-         ~f:(fst >> (function `Impl s | `Intf s -> String.is_empty s) >> not)
+         ~f:
+           (snd >> fst
+           >> (function `Impl s | `Intf s -> String.is_empty s)
+           >> not)
   in
   let string_for filter =
     let l =
       List.filter_map
-        ~f:(fun (s, space) ->
+        ~f:(fun (span, (s, space)) ->
           let* s = filter s in
-          Some (s, space))
+          Some (span, s, space))
         (strings items)
     in
     let n = List.length l - 1 in
     let lines =
       List.mapi
-        ~f:(fun i (s, space) ->
+        ~f:(fun i (span, s, space) ->
           s
-          ^ if [%matches? `NoNewline] space || [%eq: int] i n then "" else "\n")
+          ^ (if [%matches? `NoNewline] space || [%eq: int] i n then ""
+             else "\n")
+          |> Generic_printer.AnnotatedString.of_span span)
         l
     in
-    match lines with [] -> "" | _ -> header ^ String.concat ~sep:"\n" lines
+    let open Generic_printer.AnnotatedString in
+    match lines with
+    | [] -> pure ""
+    | _ -> concat (pure header) (concat_list ~sep:"\n" lines)
   in
   let replace =
     String.substr_replace_all ~pattern:"_hax_panic_freedom_admit_"
@@ -1882,17 +1963,21 @@ let translate_as_fstar m (bo : BackendOptions.t) ~(bundles : AST.item list list)
          let mod_name = module_name ns in
          let impl, intf = string_of_items ~mod_name ~bundles bo m items in
          let make ~ext body =
-           if String.is_empty body then None
+           let open Generic_printer.AnnotatedString in
+           if String.is_empty (to_string body) then None
            else
-             Some
-               Types.
-                 {
-                   path = mod_name ^ "." ^ ext;
-                   contents =
-                     "module " ^ mod_name ^ "\n" ^ fstar_headers bo ^ "\n\n"
-                     ^ body ^ "\n";
-                   sourcemap = None;
-                 }
+             let annotated =
+               concat_list
+                 [
+                   pure ("module " ^ mod_name ^ "\n" ^ fstar_headers bo ^ "\n\n");
+                   body;
+                   pure "\n";
+                 ]
+             in
+             let contents, sourcemap =
+               (to_string annotated, Some (to_sourcemap annotated))
+             in
+             Some Types.{ path = mod_name ^ "." ^ ext; contents; sourcemap }
          in
          List.filter_map ~f:Fn.id
            [ make ~ext:"fst" impl; make ~ext:"fsti" intf ])
@@ -113,15 +113,15 @@ end
 
 module type MAKE = sig
   module Preamble : sig
-    val print : item list -> string
+    val print : item list -> Generic_printer.AnnotatedString.t
   end
 
   module DataTypes : sig
-    val print : item list -> string
+    val print : item list -> Generic_printer.AnnotatedString.t
   end
 
   module Letfuns : sig
-    val print : item list -> string
+    val print : item list -> Generic_printer.AnnotatedString.t
   end
 end
 
@@ -790,20 +790,37 @@ module Make (Options : OPTS) : MAKE = struct
     || is_process_init item.attrs
 
   module type Subprinter = sig
-    val print : AST.item list -> string
+    val print : AST.item list -> Generic_printer.AnnotatedString.t
   end
 
+  (** Converts the output of the deprecated generic printer to an
+      annotated string *)
+  let annotated_string_of_annot_str
+      ((s, annotations) : Deprecated_generic_printer_base.annot_str) :
+      Generic_printer.AnnotatedString.t =
+    ( s,
+      List.map annotations
+        ~f:(fun
+            (({ line; col } : Deprecated_generic_printer_base.Annotation.loc), span)
+          -> (Generic_printer.Annotation.{ line; col }, span)) )
+
   module MkSubprinter (Section : sig
     val banner : string
     val preamble : AST.item list -> string
-    val contents : AST.item list -> string
+    val contents : AST.item list -> Generic_printer.AnnotatedString.t
   end) =
   struct
     let hline = "(*****************************************)\n"
     let banner = hline ^ "(* " ^ Section.banner ^ " *)\n" ^ hline ^ "\n"
 
     let print items =
-      banner ^ Section.preamble items ^ Section.contents items ^ "\n\n"
+      let open Generic_printer.AnnotatedString in
+      concat_list
+        [
+          pure (banner ^ Section.preamble items);
+          Section.contents items;
+          pure "\n\n";
+        ]
   end
 
   module Preamble = MkSubprinter (struct
@@ -826,7 +843,7 @@ module Make (Options : OPTS) : MAKE = struct
        letfun nat_err() = let x = construct_fail() in nat_default().\n\n\
        letfun bool_default() = false.\n"
 
-    let contents items = ""
+    let contents items = Generic_printer.AnnotatedString.pure ""
   end)
 
   module DataTypes = MkSubprinter (struct
@@ -841,8 +858,8 @@ module Make (Options : OPTS) : MAKE = struct
         items
 
     let contents items =
-      let contents, _ = Print.items NoAuxInfo (filter_data_types items) in
-      contents
+      Print.items NoAuxInfo (filter_data_types items)
+      |> annotated_string_of_annot_str
   end)
 
   module Letfuns = MkSubprinter (struct
@@ -853,13 +870,15 @@ module Make (Options : OPTS) : MAKE = struct
       let process_letfuns, pure_letfuns =
         List.partition_tf ~f:is_process (filter_crate_functions items)
       in
-      let pure_letfuns_print, _ =
+      let pure_letfuns_print =
         Print.items (CrateFns (filter_crate_functions items)) pure_letfuns
       in
-      let process_letfuns_print, _ =
+      let process_letfuns_print =
         Print.items (CrateFns (filter_crate_functions items)) process_letfuns
       in
-      pure_letfuns_print ^ process_letfuns_print
+      Generic_printer.AnnotatedString.concat
+        (annotated_string_of_annot_str pure_letfuns_print)
+        (annotated_string_of_annot_str process_letfuns_print)
   end)
 end
 
@@ -871,11 +890,14 @@ let translate m (bo : BackendOptions.t) ~(bundles : AST.item list list)
     end))
   in
   let lib_contents =
-    M.Preamble.print items ^ M.DataTypes.print items ^ M.Letfuns.print items
+    Generic_printer.AnnotatedString.concat_list
+      [ M.Preamble.print items; M.DataTypes.print items; M.Letfuns.print items ]
   in
-  let lib_file =
-    Types.{ path = "lib.pvl"; contents = lib_contents; sourcemap = None }
+  let contents, sourcemap =
+    Generic_printer.AnnotatedString.
+      (to_string lib_contents, Some (to_sourcemap lib_contents))
   in
+  let lib_file = Types.{ path = "lib.pvl"; contents; sourcemap } in
   [ lib_file ]
 
 open Phase_utils
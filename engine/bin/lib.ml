@@ -21,10 +21,41 @@ end)
 
 module Attrs = Attr_payloads.MakeBase (Error)
 
-let import_thir_items (include_clauses : Types.inclusion_clause list)
+(** Computes how each Rust item was handled during the import: was it
+    dropped, is it translated with its body, as a signature or opaquely? *)
+let item_inclusions ~(kept : Ast.Rust.item list)
+    (imported : (Types.def_id * bool * Ast.Rust.item list) list) :
+    (Types.def_id * Types.item_inclusion) list =
+  let kept =
+    List.map ~f:(fun (i : Ast.Rust.item) -> i.ident) kept
+    |> Set.of_list (module Concrete_ident)
+  in
+  List.filter_map
+    ~f:(fun (owner_id, type_only, (items : Ast.Rust.item list)) ->
+      let* _ = List.hd items in
+      let inclusion : Types.item_inclusion =
+        if List.exists ~f:(fun i -> Set.mem kept i.ident) items |> not then
+          if
+            List.exists
+              ~f:(fun i ->
+                [%matches? (Types.Excluded _ : Types.ha_item_status)]
+                  (Attrs.status i.attrs))
+              items
+          then ExcludedByAttribute
+          else ExcludedByClause
+        else if type_only then IncludedSignatureOnly
+        else if List.exists ~f:(fun i -> Attrs.is_erased i.attrs) items then
+          IncludedOpaque
+        else IncludedWithBody
+      in
+      Some (owner_id, inclusion))
+    imported
+
+let import_thir_items ~(report_inclusions : bool)
+    (include_clauses : Types.inclusion_clause list)
     (items : Types.item_for__decorated_for__expr_kind list) : Ast.Rust.item list
     =
-  let imported_items =
+  let type_only_items =
     List.map
       ~f:(fun item ->
         let ident = Concrete_ident.(of_def_id ~value:true item.owner_id) in
@@ -43,9 +74,14 @@ let import_thir_items (include_clauses : Types.inclusion_clause list)
             most_precise_clause
           |> Option.value ~default:false
         in
-        Import_thir.import_item ~type_only item)
+        (item, type_only))
       items
-    |> List.map ~f:snd
+  in
+  let imported_items =
+    List.map
+      ~f:(fun (item, type_only) ->
+        Import_thir.import_item ~type_only item |> snd)
+      type_only_items
   in
   Logs.info (fun m -> m "Items translated");
   let items = List.concat_map ~f:fst imported_items in
@@ -61,6 +97,7 @@ let import_thir_items (include_clauses : Types.inclusion_clause list)
       imported_items
     |> Map.of_alist_exn (module Concrete_ident)
   in
+  let items = Deps.rewrite_modeled_by items in
   let items = Deps.filter_by_inclusion_clauses include_clauses items in
   let items =
     items
@@ -75,6 +112,14 @@ let import_thir_items (include_clauses : Types.inclusion_clause list)
   in
   Hax_io.write
     (ItemProcessed (List.filter_map ~f:(fun i -> Span.owner_hint i.span) items));
+  if report_inclusions then
+    Hax_io.write
+      (ItemInclusions
+         (List.map2_exn
+            ~f:(fun ((item : Types.item_for__decorated_for__expr_kind), type_only)
+                    (imported, _) -> (item.owner_id, type_only, imported))
+            type_only_items imported_items
+         |> item_inclusions ~kept:items));
   (* Extract error reports for the items we actually extract *)
   let reports =
     List.concat_map
@@ -102,7 +147,9 @@ let run (options : Types.engine_options) : Types.output =
     in
     let items =
       Profiling.profile ThirImport (List.length options.input) (fun _ ->
-          import_thir_items include_clauses options.input)
+          import_thir_items
+            ~report_inclusions:(Option.is_some options.backend.report)
+            include_clauses options.input)
     in
     let items =
       if options.backend.extract_type_aliases then items
@@ -70,6 +70,7 @@ module AssocRole = struct
       | ProcessInit
       | ProtocolMessages
       | ItemQuote
+      | ModeledBy
     [@@deriving show, yojson, compare, sexp, eq]
   end
 
@@ -92,6 +93,7 @@ module AssocRole = struct
     | ProcessWrite -> ProcessWrite
     | ProcessInit -> ProcessInit
     | ProtocolMessages -> ProtocolMessages
+    | ModeledBy -> ModeledBy
 end
 
 module MakeBase (Error : Phase_utils.ERROR) = struct
@@ -138,6 +140,9 @@ module MakeBase (Error : Phase_utils.ERROR) = struct
   let lemma : attrs -> bool =
     payloads >> List.exists ~f:(fst >> [%matches? Types.Lemma])
 
+  let pure : attrs -> bool =
+    payloads >> List.exists ~f:(fst >> [%matches? Types.Pure])
+
   (* User code can be *decorated* (e.g. attributes `ensures` or
      `refine`). A decoration is attached to a user code via an
      `AssociatedItem` attribute, that specifies an unique identifier
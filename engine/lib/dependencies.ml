@@ -59,6 +59,59 @@ module Make (F : Features.T) = struct
     end) in
     raw_associated_item >> List.filter_map ~f:(snd >> try_item_of_uid)
 
+  (** Rewrites the call sites of the items marked with
+      `#[hax_lib::modeled_by(model)]` into calls to `model`. The model
+      is found in the body of the `ModeledBy` associated item of the
+      marked item, which is a function calling `model`. *)
+  let rewrite_modeled_by (items : item list) : item list =
+    let open Attrs.WithItems (struct
+      let items = items
+    end) in
+    let rec model_of_body (body : expr) : concrete_ident option =
+      match body.e with
+      | Block { e; _ } | Ascription { e; _ } -> model_of_body e
+      | e -> U.Expect.concrete_app' e
+    in
+    (* Both items and the items of impls can be modeled. *)
+    let modelable_items =
+      List.concat_map
+        ~f:(fun item ->
+          let impl_items =
+            match item.v with
+            | Impl { items; _ } ->
+                List.map
+                  ~f:(fun ii -> (ii.ii_ident, ii.ii_attrs, ii.ii_span))
+                  items
+            | _ -> []
+          in
+          (item.ident, item.attrs, item.span) :: impl_items)
+        items
+    in
+    let models =
+      List.filter_map
+        ~f:(fun (ident, attrs, span) ->
+          match status attrs with
+          | Types.Excluded { modeled_by = Some model } -> (
+              let* _, _, body = associated_fn ModeledBy attrs in
+              match model_of_body body with
+              | Some model_ident -> Some (ident, model_ident)
+              | None ->
+                  Error.assertion_failure span
+                    ("Could not find the model `" ^ model
+                   ^ "` of this item: expected a call to a function"))
+          | _ -> None)
+        modelable_items
+      |> Map.of_alist_exn (module Concrete_ident)
+    in
+    if Map.is_empty models then items
+    else
+      let rename _ : global_ident -> global_ident = function
+        | `Concrete ident ->
+            `Concrete (Map.find models ident |> Option.value ~default:ident)
+        | ident -> ident
+      in
+      List.map ~f:(U.Mappers.rename_global_idents_item rename) items
+
   module ItemGraph = struct
     module G = Graph.Persistent.Digraph.Concrete (Concrete_ident)
 
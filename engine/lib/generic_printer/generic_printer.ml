@@ -77,25 +77,40 @@ module AnnotatedString = struct
   (** Lifts a string to an annotated list *)
   let pure (s : string) : t = (s, [])
 
-  (** Concatenate two annotated strings *)
-  let concat (x : t) (y : t) : t =
-    let (xs, xl), (ys, yl) = (x, y) in
-    let last_x =
-      let lines = String.split ~on:'\n' xs in
-      let last_line = List.last lines |> Option.value ~default:"" in
-      let col, line = (String.length last_line, List.length lines) in
-      Annotation.{ col; line }
-    in
-    let yl =
-      let f ({ line; col } : Annotation.loc) : Annotation.loc =
+  (** Computes the location right after [s], when [s] is printed at
+      location [loc] *)
+  let end_loc (loc : Annotation.loc) (s : string) : Annotation.loc =
+    match String.rindex s '\n' with
+    | Some i ->
         {
-          line = line + last_x.line;
-          col = (match col with 0 -> col + last_x.col | _ -> col);
+          line = loc.line + String.count s ~f:(Char.equal '\n');
+          col = String.length s - i - 1;
         }
-      in
-      List.map ~f:(f *** Fn.id) yl
+    | None -> { loc with col = loc.col + String.length s }
+
+  (** Concatenate a list of annotated strings, separated by [sep] *)
+  let concat_list ?(sep = "") (l : t list) : t =
+    let shift (at : Annotation.loc) ({ line; col } : Annotation.loc) :
+        Annotation.loc =
+      if Int.equal line 0 then { line = at.line; col = at.col + col }
+      else { line = at.line + line; col }
+    in
+    let _, strings, annots =
+      List.intersperse ~sep:(pure sep) l
+      |> List.fold ~init:(Annotation.{ line = 0; col = 0 }, [], [])
+           ~f:(fun (at, strings, annots) (s, l) ->
+             let l = List.map ~f:(shift at *** Fn.id) l in
+             (end_loc at s, s :: strings, List.rev_append l annots))
     in
-    (xs ^ ys, xl @ yl)
+    (String.concat (List.rev strings), List.rev annots)
+
+  (** Concatenate two annotated strings *)
+  let concat (x : t) (y : t) : t = concat_list [ x; y ]
+
+  (** Lifts a string to an annotated string, annotating its beginning
+      with [span] *)
+  let of_span (span : span) (s : string) : t =
+    (s, [ (Annotation.{ line = 0; col = 0 }, span) ])
 
   let to_sourcemap : t -> Types.source_map =
     snd >> List.filter_map ~f:Annotation.to_mapping >> Sourcemaps.Source_maps.mk
@@ -41,6 +41,11 @@ struct
 
     type loop_annotation_kind =
       | LoopInvariant of { index_pat : B.pat option; invariant : B.expr }
+      | LoopItemsInvariant of {
+          index_pat : B.pat;
+          prefix_pat : B.pat;
+          invariant : B.expr;
+        }
       | LoopVariant of B.expr
 
     type loop_annotation = {
@@ -68,6 +73,24 @@ struct
             annotation =
               Some (LoopInvariant { index_pat = Some pat; invariant });
           }
+      | Some
+          ( f,
+            [
+              {
+                e =
+                  Closure
+                    { params = [ index_pat; prefix_pat ]; body = invariant; _ };
+                _;
+              };
+            ],
+            body )
+        when Global_ident.eq_name Hax_lib___internal_iterator_loop_invariant f
+        ->
+          {
+            body;
+            annotation =
+              Some (LoopItemsInvariant { index_pat; prefix_pat; invariant });
+          }
       | Some (f, [ invariant ], body)
         when Global_ident.eq_name Hax_lib___internal_while_loop_invariant f ->
           {
@@ -90,8 +113,12 @@ struct
       | Range of { start : B.expr; end_ : B.expr }
       | Slice of B.expr
       | ChunksExact of { size : B.expr; slice : B.expr }
+      | Chunks of { size : B.expr; slice : B.expr }
+      | Windows of { size : B.expr; slice : B.expr }
       | Enumerate of iterator
       | StepBy of { n : B.expr; it : iterator }
+      | Rev of iterator
+      | Zip of iterator * iterator
     [@@deriving show]
 
     let rec as_iterator (e : B.expr) : iterator option =
@@ -139,8 +166,75 @@ struct
       else if f_eq Core__slice__Impl__chunks_exact then
         let* slice, size = two_args () in
         Some (ChunksExact { size; slice })
+      else if f_eq Core__slice__Impl__chunks then
+        let* slice, size = two_args () in
+        Some (Chunks { size; slice })
+      else if f_eq Core__slice__Impl__windows then
+        let* slice, size = two_args () in
+        Some (Windows { size; slice })
+      else if f_eq Core__iter__traits__iterator__Iterator__rev then
+        let* it = one_arg () in
+        let* it = as_iterator it in
+        Some (Rev it)
+      else if f_eq Core__iter__traits__iterator__Iterator__zip then
+        let* it, other = two_args () in
+        let* it = as_iterator it in
+        let* other =
+          match other.typ with
+          | TSlice _ | TArray _ -> Some (Slice other)
+          | _ -> as_iterator other
+        in
+        Some (Zip (it, other))
       else None
 
+    (** The type `Seq<typ>` of specification sequences. *)
+    let seq_typ (typ : B.ty) : B.ty =
+      TApp
+        {
+          ident =
+            `Concrete (Concrete_ident.of_name ~value:false Hax_lib__seq__Seq);
+          args = [ GType typ ];
+        }
+
+    (** The sequence of the items of an iterator, with the type of the
+        items. *)
+    let rec items_of_iterator span (it : iterator) : (B.expr * B.ty) option =
+      let open Concrete_ident_generated in
+      let usize = B.TInt { size = SSize; signedness = Unsigned } in
+      let items f args typ = Some (UB.call f args span (seq_typ typ), typ) in
+      match it with
+      | Range { start; end_ } ->
+          items Rust_primitives__hax__folds__items_range [ start; end_ ]
+            start.typ
+      | Slice slice -> (
+          match slice.typ with
+          | TSlice { ty; _ } | TArray { typ = ty; _ } -> Some (slice, ty)
+          | _ -> None)
+      | ChunksExact { size; slice } ->
+          items Rust_primitives__hax__folds__items_chunks_exact [ size; slice ]
+            slice.typ
+      | Chunks { size; slice } ->
+          items Rust_primitives__hax__folds__items_chunks [ size; slice ]
+            slice.typ
+      | Windows { size; slice } ->
+          items Rust_primitives__hax__folds__items_windows [ size; slice ]
+            slice.typ
+      | Enumerate it ->
+          let* it, typ = items_of_iterator span it in
+          items Rust_primitives__hax__folds__items_enumerate [ it ]
+            (UB.make_tuple_typ [ usize; typ ])
+      | StepBy { n; it } ->
+          let* it, typ = items_of_iterator span it in
+          items Rust_primitives__hax__folds__items_step_by [ n; it ] typ
+      | Rev it ->
+          let* it, typ = items_of_iterator span it in
+          items Rust_primitives__hax__folds__items_rev [ it ] typ
+      | Zip (it, other) ->
+          let* it, typ = items_of_iterator span it in
+          let* other, other_typ = items_of_iterator span other in
+          items Rust_primitives__hax__folds__items_zip [ it; other ]
+            (UB.make_tuple_typ [ typ; other_typ ])
+
     let fn_args_of_iterator (cf : A.cf_kind option) (it : iterator) :
         (Concrete_ident.name * B.expr list * B.ty) option =
       let open Concrete_ident_generated in
@@ -231,11 +325,51 @@ struct
           let { body; annotation } = extract_loop_annotation body in
           let it = dexpr it in
           let pat = dpat pat in
-          let fn : B.expr = UB.make_closure [ bpat; pat ] body body.span in
           let cf = Option.map ~f:fst control_flow in
+          let iterator = as_iterator it in
+          (* The index of the loops folded over their items is their
+             number of iterations. *)
+          let fold_items (items, item_typ) index_pat prefix_pat invariant =
+            let usize = B.TInt { size = SSize; signedness = Unsigned } in
+            let fn : B.expr =
+              UB.make_closure
+                [ bpat; UB.make_tuple_pat [ MS.pat_PWild ~typ:usize; pat ] ]
+                body body.span
+            in
+            let index_pat =
+              Option.value ~default:(MS.pat_PWild ~typ:usize) index_pat
+            in
+            let prefix_pat =
+              Option.value
+                ~default:(MS.pat_PWild ~typ:(seq_typ item_typ))
+                prefix_pat
+            in
+            let invariant : B.expr =
+              UB.make_closure
+                [ bpat; index_pat; prefix_pat ]
+                invariant invariant.span
+            in
+            let fold_op : Concrete_ident.name =
+              match cf with
+              | Some BreakOrReturn ->
+                  Rust_primitives__hax__folds__fold_items_return
+              | Some BreakOnly -> Rust_primitives__hax__folds__fold_items_cf
+              | None -> Rust_primitives__hax__folds__fold_items
+            in
+            (fold_op, [ items; invariant; init; fn ])
+          in
           let f, args =
-            match as_iterator it |> Option.bind ~f:(fn_args_of_iterator cf) with
-            | Some (f, args, typ) ->
+            match
+              ( annotation,
+                Option.bind ~f:(fn_args_of_iterator cf) iterator,
+                Option.bind ~f:(items_of_iterator span) iterator )
+            with
+            | ( (None | Some (LoopInvariant _ | LoopVariant _)),
+                Some (f, args, typ),
+                _ ) ->
+                let fn : B.expr =
+                  UB.make_closure [ bpat; pat ] body body.span
+                in
                 (* TODO what happens if there is control flow? *)
                 let invariant : B.expr =
                   let default =
@@ -252,7 +386,26 @@ struct
                   UB.make_closure [ bpat; pat ] invariant invariant.span
                 in
                 (f, args @ [ invariant; init; fn ])
-            | None ->
+            | ( Some (LoopItemsInvariant { index_pat; prefix_pat; invariant }),
+                _,
+                Some items ) ->
+                fold_items items (Some index_pat) (Some prefix_pat) invariant
+            | Some (LoopInvariant { index_pat; invariant }), _, Some items ->
+                fold_items items index_pat None invariant
+            | Some (LoopInvariant _ | LoopItemsInvariant _), _, None ->
+                Error.unimplemented ~issue_id:858
+                  ~details:
+                    "Loop invariants are only supported on `for` loops over \
+                     ranges and slices, possibly adapted with `iter`, \
+                     `enumerate`, `step_by`, `zip`, `rev`, `windows`, \
+                     `chunks` and `chunks_exact`, and on `while let \
+                     Some(x) = it.next()` loops over such an iterator `it` \
+                     that the body of the loop does not mention."
+                  span
+            | _ ->
+                let fn : B.expr =
+                  UB.make_closure [ bpat; pat ] body body.span
+                in
                 let fold : Concrete_ident.name =
                   match cf with
                   | Some BreakOrReturn ->
@@ -27,7 +27,8 @@ module Make (F : Features.T) =
               when Ast.Global_ident.eq_name Hax_lib__Refinement__new f
                    || Ast.Global_ident.eq_name Hax_lib__RefineAs__into_checked f
                    || Ast.Global_ident.eq_name Hax_lib__Refinement__get_mut f
-                   || Ast.Global_ident.eq_name Hax_lib__Refinement__get f ->
+                   || Ast.Global_ident.eq_name Hax_lib__Refinement__get f
+                   || Ast.Global_ident.eq_name Hax_lib__Invariant__checked f ->
                 { e with e = Ascription { typ = e.typ; e = inner } }
             | _ -> e
 
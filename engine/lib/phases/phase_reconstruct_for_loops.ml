@@ -232,13 +232,161 @@ struct
             Some { it; pat; body; state; label; witness }
         | _ -> None
                [@ocamlformat "disable"]
+
+      let rec strip_blocks_and_never_to_any (e : expr) : expr =
+        match e.e with
+        | Block { e; safety_mode = Safe; _ } -> strip_blocks_and_never_to_any e
+        | App { f = { e = GlobalVar f }; args = [ e ]; _ }
+          when Global_ident.eq_name Rust_primitives__hax__never_to_any f ->
+            strip_blocks_and_never_to_any e
+        | _ -> e
+
+      (** Matches `while let Some(pat) = it.next() { body }` loops, when
+          `it` is a local variable that `body` does not mention. Returns
+          the loop, the variable `it` and the span of the loop. *)
+      let match_while_let (e : expr) : (t * local_ident * span) option =
+        let e = UA.Mappers.normalize_borrow_mut#visit_expr () e in
+        let* label, state, witness, body =
+          match e.e with
+          | Loop { label; kind = UnconditionalLoop; state; witness; body; _ }
+            ->
+              Some (label, state, witness, body)
+          | _ -> None
+        in
+        let* scrutinee, arms =
+          match (strip_blocks_and_never_to_any body).e with
+          | Match { scrutinee; arms }
+          | Let
+              {
+                monadic = None;
+                lhs = { p = PWild };
+                rhs = { e = Match { scrutinee; arms } };
+                body = { e = GlobalVar (`TupleCons 0) };
+              } ->
+              Some (scrutinee, arms)
+          | _ -> None
+        in
+        let* it =
+          match scrutinee.e with
+          | App
+              {
+                f = { e = GlobalVar (`Concrete next_meth) };
+                args = [ { e = Borrow { kind = Mut _; e = it } } ];
+                _;
+              }
+            when Concrete_ident.eq_name
+                   Core__iter__traits__iterator__Iterator__next next_meth ->
+              Some it
+          | _ -> None
+        in
+        let* it_variable =
+          match it.e with LocalVar var -> Some var | _ -> None
+        in
+        match arms with
+        | [
+         {
+           arm =
+             {
+               arm_pat =
+                 {
+                   p =
+                     PConstruct
+                       { constructor = `Concrete some_ctor; fields = [ { pat } ]; _ };
+                 };
+               body;
+               guard = None;
+             };
+         };
+         { arm = { arm_pat = { p = PWild }; body = else_; guard = None } };
+        ]
+          when Concrete_ident.eq_name Core__option__Option__Some some_ctor
+               && [%matches? Break { e = { e = GlobalVar (`TupleCons 0) } }]
+                    (strip_blocks_and_never_to_any else_).e
+               && not
+                    (Set.mem
+                       (UA.Reducers.collect_local_idents#visit_expr () body)
+                       it_variable) ->
+            Some ({ it; pat; body; state; label; witness }, it_variable, e.span)
+        | _ -> None
+
+      (** The spans of the `while let` loops whose iterator is dead after
+          the loop, as found by `record_consuming_loops`. *)
+      let consuming_loops : span list ref = ref []
+
+      (** Records the `while let` loops over `var` among the statements
+          of `scope`, the scope of a `let` binding `var`, which are not
+          followed by a use of `var`. *)
+      let rec record_consuming_loops (var : local_ident) (scope : expr) : unit
+          =
+        let record (loop : expr) (rest : expr option) =
+          match match_while_let (strip_blocks_and_never_to_any loop) with
+          | Some (_, it_variable, span)
+            when [%eq: local_ident] var it_variable
+                 && not
+                      (Option.exists rest ~f:(fun rest ->
+                           Set.mem
+                             (UA.Reducers.collect_local_idents#visit_expr ()
+                                rest)
+                             var)) ->
+              consuming_loops := span :: !consuming_loops
+          | _ -> ()
+        in
+        match scope.e with
+        | Let { monadic = None; rhs; body; _ } ->
+            record rhs (Some body);
+            record_consuming_loops var body
+        | _ -> record scope None
+
+      (** Tells whether the body of a loop starts with a loop invariant
+          closure, which needs the loop to be a `for` loop. *)
+      let starts_with_invariant_closure (body : expr) : bool =
+        match (strip_blocks_and_never_to_any body).e with
+        | Let
+            {
+              monadic = None;
+              lhs = { p = PWild };
+              rhs =
+                {
+                  e =
+                    App
+                      { f = { e = GlobalVar f }; args = [ { e = Closure _ } ]; _ };
+                };
+              _;
+            } ->
+            Global_ident.eq_name Hax_lib___internal_loop_invariant f
+            || Global_ident.eq_name Hax_lib___internal_iterator_loop_invariant
+                 f
+        | _ -> false
+
+      (** Reconstructs `while let Some(pat) = it.next() { body }` loops
+          as `for pat in it { body }` loops, when `it` is a local
+          variable that `body` does not mention. The `for` loop consumes
+          `it`: the loop is only reconstructed when `it` is dead after
+          it, or when its invariant is a closure, which requires a `for`
+          loop. *)
+      let extract_while_let (e : expr) : t option =
+        let* loop, _, span = match_while_let e in
+        let*? () =
+          List.mem !consuming_loops span ~equal:Span.equal
+          || starts_with_invariant_closure loop.body
+        in
+        Some loop
     end
 
     [%%inline_defs dmutability + dsafety_kind]
 
     let rec dexpr_unwrapped (expr : A.expr) : B.expr =
       let h = [%inline_body dexpr_unwrapped] in
-      match For.extract expr with
+      (match expr.e with
+      | Let { lhs = { p = PBinding { var; _ } }; body; _ } ->
+          For.record_consuming_loops var body
+      | _ -> ());
+      let loop =
+        match For.extract expr with
+        | None -> For.extract_while_let expr
+        | loop -> loop
+      in
+      match loop with
       | Some { it; pat; body; label; state; witness } ->
           {
             e =
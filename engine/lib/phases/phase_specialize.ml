@@ -116,6 +116,14 @@ module Make (F : Features.T) =
           let is_int : (ty, unit) predicate =
             tapp0 >>& eq_global_ident Hax_lib__int__Int >>& erase
 
+          let is_mod_int : (ty, unit) predicate = function
+            | TApp { ident; _ } | TRef { typ = TApp { ident; _ }; _ } ->
+                (eq_global_ident Hax_lib__modular__ModInt >>& erase) ident
+            | _ -> None
+
+          let not_ (p : ('a, unit) predicate) : ('a, unit) predicate =
+           fun x -> match p x with Some () -> None | None -> Some ()
+
           let is_machine_int : (ty, unit) predicate =
            fun t ->
             match t with
@@ -133,6 +141,7 @@ module Make (F : Features.T) =
           let int_any = mk [ etyp >> is_int ] any
           let int_int_any = mk [ etyp >> is_int; etyp >> is_int ] any
           let any_int = mk [ any ] is_int
+          let not_mod_int_int = mk [ etyp >> not_ is_mod_int ] is_int
           let rint_any = mk [ etyp >> (tref >>& is_int) ] any
 
           let rint_rint_any =
@@ -144,6 +153,7 @@ module Make (F : Features.T) =
             mk [ etyp >> is_machine_int; etyp >> is_machine_int ] any
 
           let mint_any = mk [ etyp >> is_machine_int ] any
+          let int_mint_any = mk [ etyp >> is_int; etyp >> is_machine_int ] any
           let bool_prop = mk [ etyp >> is_bool ] is_prop
           let prop_bool = mk [ etyp >> is_prop ] is_bool
 
@@ -151,6 +161,35 @@ module Make (F : Features.T) =
             | TArrow (ts, t) -> Some (ts @ [ t ])
             | _ -> None
 
+          (** Comparisons between an `Int` and a machine integer: the
+              machine integer is lifted to an `Int`. *)
+          let mixed_int_comparison (fn : t) (int_fn : t) : pattern list =
+            let int_typ =
+              let ident =
+                Ast.Global_ident.of_name ~value:false Hax_lib__int__Int
+              in
+              TApp { ident; args = [] }
+            in
+            let lift_machine_int _ (e : expr) =
+              match e.typ with
+              | TInt _ | TRef { typ = TInt _; _ } ->
+                  U.call Rust_primitives__hax__int__from_machine [ e ] e.span
+                    int_typ
+              | _ -> e
+            in
+            let fn_replace =
+              FnReplace.map_args lift_machine_int
+                (FnReplace.name int_fn |> FnReplace.monorphic)
+            in
+            [
+              mk'
+                [ etyp >> (tref >>& is_int); etyp >> is_machine_int ]
+                any fn fn_replace;
+              mk'
+                [ etyp >> is_machine_int; etyp >> (tref >>& is_int) ]
+                any fn fn_replace;
+            ]
+
           let a_to_b a b : _ predicate =
             arrow >> fun x ->
             let* t, u =
@@ -208,6 +247,14 @@ module Make (F : Features.T) =
             int_int_any Core__ops__arith__Rem__rem
               Rust_primitives__hax__int__rem;
             int_any Core__ops__arith__Neg__neg Rust_primitives__hax__int__neg;
+            int_int_any Core__ops__bit__BitAnd__bitand
+              Rust_primitives__hax__int__bitand;
+            int_int_any Core__ops__bit__BitOr__bitor
+              Rust_primitives__hax__int__bitor;
+            int_int_any Core__ops__bit__BitXor__bitxor
+              Rust_primitives__hax__int__bitxor;
+            int_mint_any Core__ops__bit__Shl__shl Rust_primitives__hax__int__shl;
+            int_mint_any Core__ops__bit__Shr__shr Rust_primitives__hax__int__shr;
             rint_rint_any Core__cmp__PartialOrd__gt
               Rust_primitives__hax__int__gt;
             rint_rint_any Core__cmp__PartialOrd__ge
@@ -218,13 +265,24 @@ module Make (F : Features.T) =
               Rust_primitives__hax__int__le;
             rint_rint_any Core__cmp__PartialEq__ne Rust_primitives__hax__int__ne;
             rint_rint_any Core__cmp__PartialEq__eq Rust_primitives__hax__int__eq;
-            any_int Hax_lib__abstraction__Abstraction__lift
+            (* `ModInt` is lifted by its own `Abstraction` instance *)
+            not_mod_int_int Hax_lib__abstraction__Abstraction__lift
               Rust_primitives__hax__int__from_machine;
             any_int Hax_lib__int__ToInt__to_int
               Rust_primitives__hax__int__from_machine;
             int_any Hax_lib__abstraction__Concretization__concretize
               Rust_primitives__hax__int__into_machine;
           ]
+          @ List.concat_map
+              ~f:(fun (fn, int_fn) -> mixed_int_comparison fn int_fn)
+              [
+                (Core__cmp__PartialOrd__gt, Rust_primitives__hax__int__gt);
+                (Core__cmp__PartialOrd__ge, Rust_primitives__hax__int__ge);
+                (Core__cmp__PartialOrd__lt, Rust_primitives__hax__int__lt);
+                (Core__cmp__PartialOrd__le, Rust_primitives__hax__int__le);
+                (Core__cmp__PartialEq__ne, Rust_primitives__hax__int__ne);
+                (Core__cmp__PartialEq__eq, Rust_primitives__hax__int__eq);
+              ]
 
         let prop_replacements =
           let name_from_bool = Hax_lib__prop__constructors__from_bool in
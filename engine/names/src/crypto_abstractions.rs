@@ -1,22 +1,33 @@
 use hax_lib_protocol::crypto::*;
 
-fn crypto_abstractions() {
+fn crypto_abstractions() -> hax_lib_protocol::ProtocolResult<()> {
     let bytes = vec![0u8; 32];
-    let iv = AEADIV::from_bytes(&bytes);
-    let key = AEADKey::from_bytes(AEADAlgorithm::Chacha20Poly1305, &bytes);
+    let iv = AEADIV::from_bytes(&bytes)?;
+    let key = AEADKey::from_bytes(AEADAlgorithm::Chacha20Poly1305, &bytes)?;
 
-    let (cipher_text, _tag) = aead_encrypt(key, iv, &bytes, &bytes);
-    let iv = AEADIV::from_bytes(&bytes);
-    let key = AEADKey::from_bytes(AEADAlgorithm::Chacha20Poly1305, &bytes);
-    let _ = aead_decrypt(key, iv, &bytes, &cipher_text, AEADTag::from_bytes(&bytes));
+    let (cipher_text, _tag) = aead_encrypt(key, iv, &bytes, &bytes)?;
+    let iv = AEADIV::from_bytes(&bytes)?;
+    let key = AEADKey::from_bytes(AEADAlgorithm::Chacha20Poly1305, &bytes)?;
+    let _ = aead_decrypt(key, iv, &bytes, &cipher_text, AEADTag::from_bytes(&bytes)?);
 
     let p = DHElement::from_bytes(&bytes);
     let s = DHScalar::from_bytes(&bytes);
-    dh_scalar_multiply(DHGroup::X25519, s.clone(), p);
-    dh_scalar_multiply_base(DHGroup::X25519, s);
+    dh_scalar_multiply(DHGroup::X25519, s.clone(), p)?;
+    dh_scalar_multiply_base(DHGroup::X25519, s)?;
 
     let _ = hmac(HMACAlgorithm::Sha256, &bytes, &bytes);
 
+    let prk = hkdf_extract(HKDFAlgorithm::Sha256, &bytes, &bytes)?;
+    let _ = hkdf_expand(HKDFAlgorithm::Sha256, &prk, &bytes, 32)?;
+
+    let (signing_key, verification_key) = signature_key_gen(SignatureAlgorithm::Ed25519, &bytes)?;
+    let signature = sign(&signing_key, &bytes, &bytes)?;
+    verify(&verification_key, &bytes, &signature)?;
+
+    let (private_key, public_key) = kem_key_gen(KEMAlgorithm::X25519, &bytes)?;
+    let (_shared_secret, ciphertext) = kem_encapsulate(&public_key, &bytes)?;
+    let _ = kem_decapsulate(&private_key, &ciphertext)?;
+
     let _ = 1u64.to_le_bytes();
     let slice = &bytes[0..1];
     let _ = slice.len();
@@ -28,4 +39,5 @@ fn crypto_abstractions() {
 
     let _ = hash(HashAlgorithm::Sha256, &bytes);
     let _ = cipher_text.clone();
+    Ok(())
 }
@@ -30,6 +30,7 @@ fn dummy_hax_concrete_ident_wrapper<I: core::iter::Iterator<Item = u8>>(x: I, mu
     assert_eq!(1, 1);
     hax_lib::assert!(true);
     hax_lib::_internal_loop_invariant(|_: usize| true);
+    hax_lib::_internal_iterator_loop_invariant(|_: usize, _: hax_lib::Seq<()>| true);
     hax_lib::_internal_while_loop_invariant(hax_lib::Prop::from(true));
     hax_lib::_internal_loop_decreases(hax_lib::Int::_unsafe_from_str("0"));
 
@@ -77,6 +78,10 @@ fn dummy_hax_concrete_ident_wrapper<I: core::iter::Iterator<Item = u8>>(x: I, mu
         let _ = it.clone().step_by(2);
         let _ = it.clone().enumerate();
         let _ = [()].chunks_exact(2);
+        let _ = [()].chunks(2);
+        let _ = [()].windows(2);
+        let _ = it.clone().zip(it.clone());
+        let _ = [()].iter().rev();
         let _ = [()].iter();
         let _ = (&[()] as &[()]).iter();
     }
@@ -88,6 +93,12 @@ fn dummy_hax_concrete_ident_wrapper<I: core::iter::Iterator<Item = u8>>(x: I, mu
         let _ = a.clone().pow2();
         let _ = Int::_unsafe_from_str("1");
         let _: u32 = a.concretize();
+        let b = a.pow(2);
+        let _ = (a.abs(), a.signum(), a.min(b), a.max(b), a.gcd(b));
+        let _ = (a % b, a << 2u32, a >> 2u32, a & b, a | b, a ^ b);
+        let _ = (a < 3u8, 3u8 < a, a == 3u8, 3u8 == a);
+        let m: ModInt<7> = 3u8.lift_mod();
+        let _: Int = m.lift();
     }
 
     fn question_mark_result<A, B: From<A>>(x: A) -> Result<(), B> {
@@ -98,7 +109,7 @@ fn dummy_hax_concrete_ident_wrapper<I: core::iter::Iterator<Item = u8>>(x: I, mu
     let _ = hax_lib::inline("");
     let _: () = hax_lib::inline_unsafe("");
     let _: () = hax_lib::any_to_unit(());
-    use hax_lib::{RefineAs, Refinement};
+    use hax_lib::{Invariant, RefineAs, Refinement};
 
     fn refinements<T: Refinement + Clone, U: RefineAs<T>>(x: T, y: U) -> T {
         let _ = x.clone().get_mut();
@@ -106,6 +117,10 @@ fn dummy_hax_concrete_ident_wrapper<I: core::iter::Iterator<Item = u8>>(x: I, mu
         y.into_checked()
     }
 
+    fn invariants<T: Invariant>(x: T) -> T {
+        x.checked()
+    }
+
     const _: () = {
         use core::{cmp::*, ops::*};
         fn arith<
@@ -227,6 +242,17 @@ mod hax {
         fn fold_chunked_slice() {}
         fn fold_chunked_slice_cf() {}
         fn fold_chunked_slice_return() {}
+        fn fold_items() {}
+        fn fold_items_cf() {}
+        fn fold_items_return() {}
+        fn items_range() {}
+        fn items_step_by() {}
+        fn items_enumerate() {}
+        fn items_zip() {}
+        fn items_rev() {}
+        fn items_windows() {}
+        fn items_chunks() {}
+        fn items_chunks_exact() {}
         fn fold_cf() {}
         fn fold_return() {}
     }
@@ -243,6 +269,12 @@ mod hax {
         fn rem() {}
         fn neg() {}
 
+        fn shl() {}
+        fn shr() {}
+        fn bitand() {}
+        fn bitor() {}
+        fn bitxor() {}
+
         fn le() {}
         fn lt() {}
         fn ge() {}
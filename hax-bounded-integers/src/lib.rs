@@ -4,6 +4,41 @@ pub mod num_traits;
 pub mod _macro_utils {
     pub use duplicate;
     pub use paste;
+
+    /// An integer of any primitive integer type, so that bounds of
+    /// different types can be compared in constant contexts.
+    #[derive(Clone, Copy)]
+    pub struct AnyInt {
+        negative: bool,
+        magnitude: u128,
+    }
+
+    impl AnyInt {
+        /// `as_i128` and `as_u128` are casts of the same integer, the
+        /// first one is used for signed types.
+        pub const fn new(signed: bool, as_i128: i128, as_u128: u128) -> Self {
+            if signed {
+                Self {
+                    negative: as_i128 < 0,
+                    magnitude: as_i128.unsigned_abs(),
+                }
+            } else {
+                Self {
+                    negative: false,
+                    magnitude: as_u128,
+                }
+            }
+        }
+
+        pub const fn le(self, other: Self) -> bool {
+            match (self.negative, other.negative) {
+                (true, false) => true,
+                (false, true) => false,
+                (false, false) => self.magnitude <= other.magnitude,
+                (true, true) => self.magnitude >= other.magnitude,
+            }
+        }
+    }
 }
 
 #[doc(hidden)]
@@ -293,11 +328,161 @@ macro_rules! derivate_operations_for_bounded {
     }
 }
 
+#[doc(hidden)]
+#[macro_export]
+macro_rules! derivate_interval_ops_for_bounded {
+    ($bounded_t:ident($t:ident)) => {
+        #[hax_lib::attributes]
+        impl<const MIN: $t, const MAX: $t> $bounded_t<MIN, MAX> {
+            /// Checks at run time that `self` lies in `MIN2..=MAX2`.
+            #[inline(always)]
+            pub fn narrow<const MIN2: $t, const MAX2: $t>(self) -> Option<$bounded_t<MIN2, MAX2>> {
+                let x = ::hax_lib::Refinement::get(self);
+                if x >= MIN2 && x <= MAX2 {
+                    Some(::hax_lib::Refinement::new(x))
+                } else {
+                    None
+                }
+            }
+
+            /// Adds two bounded integers. The bounds `MIN_R..=MAX_R`
+            /// of the result, usually given by the expected type, must
+            /// contain `MIN + MIN2..=MAX + MAX2`: this is checked at
+            /// compile time (where an overflowing bound is an error) and
+            /// is a precondition in extracted code.
+            #[hax_lib::requires(
+                ::hax_lib::Abstraction::lift(MIN_R) <= ::hax_lib::Abstraction::lift(MIN) + ::hax_lib::Abstraction::lift(MIN2)
+                && ::hax_lib::Abstraction::lift(MAX) + ::hax_lib::Abstraction::lift(MAX2) <= ::hax_lib::Abstraction::lift(MAX_R)
+            )]
+            #[inline(always)]
+            pub fn add_bounded<const MIN2: $t, const MAX2: $t, const MIN_R: $t, const MAX_R: $t>(
+                self,
+                rhs: $bounded_t<MIN2, MAX2>,
+            ) -> $bounded_t<MIN_R, MAX_R> {
+                #[cfg(not(hax))]
+                let () = const {
+                    assert!(
+                        MIN_R <= MIN + MIN2 && MAX + MAX2 <= MAX_R,
+                        "the bounds of the result do not contain the bounds of the sum"
+                    )
+                };
+                ::hax_lib::Refinement::new(
+                    ::hax_lib::Refinement::get(self) + ::hax_lib::Refinement::get(rhs),
+                )
+            }
+
+            /// Subtracts two bounded integers. The bounds `MIN_R..=MAX_R`
+            /// of the result must contain `MIN - MAX2..=MAX - MIN2`, see
+            /// [`Self::add_bounded`].
+            #[hax_lib::requires(
+                ::hax_lib::Abstraction::lift(MIN_R) <= ::hax_lib::Abstraction::lift(MIN) - ::hax_lib::Abstraction::lift(MAX2)
+                && ::hax_lib::Abstraction::lift(MAX) - ::hax_lib::Abstraction::lift(MIN2) <= ::hax_lib::Abstraction::lift(MAX_R)
+            )]
+            #[inline(always)]
+            pub fn sub_bounded<const MIN2: $t, const MAX2: $t, const MIN_R: $t, const MAX_R: $t>(
+                self,
+                rhs: $bounded_t<MIN2, MAX2>,
+            ) -> $bounded_t<MIN_R, MAX_R> {
+                #[cfg(not(hax))]
+                let () = const {
+                    assert!(
+                        MIN_R <= MIN - MAX2 && MAX - MIN2 <= MAX_R,
+                        "the bounds of the result do not contain the bounds of the difference"
+                    )
+                };
+                ::hax_lib::Refinement::new(
+                    ::hax_lib::Refinement::get(self) - ::hax_lib::Refinement::get(rhs),
+                )
+            }
+
+            /// Multiplies two bounded integers. The bounds
+            /// `MIN_R..=MAX_R` of the result must contain the products
+            /// of the bounds of the operands, see [`Self::add_bounded`].
+            #[hax_lib::requires({
+                let (min, max) = (::hax_lib::Abstraction::lift(MIN), ::hax_lib::Abstraction::lift(MAX));
+                let (min2, max2) = (::hax_lib::Abstraction::lift(MIN2), ::hax_lib::Abstraction::lift(MAX2));
+                let (min_r, max_r) = (::hax_lib::Abstraction::lift(MIN_R), ::hax_lib::Abstraction::lift(MAX_R));
+                min_r <= min * min2 && min * min2 <= max_r
+                    && min_r <= min * max2 && min * max2 <= max_r
+                    && min_r <= max * min2 && max * min2 <= max_r
+                    && min_r <= max * max2 && max * max2 <= max_r
+            })]
+            #[inline(always)]
+            pub fn mul_bounded<const MIN2: $t, const MAX2: $t, const MIN_R: $t, const MAX_R: $t>(
+                self,
+                rhs: $bounded_t<MIN2, MAX2>,
+            ) -> $bounded_t<MIN_R, MAX_R> {
+                #[cfg(not(hax))]
+                let () = const {
+                    let products = [MIN * MIN2, MIN * MAX2, MAX * MIN2, MAX * MAX2];
+                    let mut i = 0;
+                    while i < products.len() {
+                        assert!(
+                            MIN_R <= products[i] && products[i] <= MAX_R,
+                            "the bounds of the result do not contain the bounds of the product"
+                        );
+                        i += 1;
+                    }
+                };
+                ::hax_lib::Refinement::new(
+                    ::hax_lib::Refinement::get(self) * ::hax_lib::Refinement::get(rhs),
+                )
+            }
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! derivate_conversions_for_bounded {
+    (@each [$($list:tt)*]) => {};
+    (@each [$($list:tt)*] $bounded_t:ident($t:ident), $($rest:tt)*) => {
+        $crate::derivate_conversions_for_bounded!(@from $bounded_t($t); $($list)*);
+        $crate::derivate_conversions_for_bounded!(@each [$($list)*] $($rest)*);
+    };
+    (@any_int $t:ident, $x:expr) => {
+        $crate::_macro_utils::AnyInt::new(<$t>::MIN != 0, $x as i128, $x as u128)
+    };
+    (@from $bounded_t:ident($t:ident); $($target_t:ident($u:ident)),* $(,)?) => {
+        $crate::_macro_utils::paste::paste! {
+            #[hax_lib::attributes]
+            impl<const MIN: $t, const MAX: $t> $bounded_t<MIN, MAX> {
+                $(
+                    #[doc = concat!("Converts to a [`", stringify!($target_t), "`] whose bounds `MIN2..=MAX2` contain `MIN..=MAX`.")]
+                    #[doc = "This is checked at compile time and is a precondition in extracted code."]
+                    #[doc = "To convert to tighter bounds, use [`Self::narrow`] first."]
+                    #[hax_lib::requires(
+                        ::hax_lib::Abstraction::lift(MIN2) <= ::hax_lib::Abstraction::lift(MIN)
+                        && ::hax_lib::Abstraction::lift(MAX) <= ::hax_lib::Abstraction::lift(MAX2)
+                    )]
+                    #[inline(always)]
+                    pub fn [< to_ $target_t:snake >]<const MIN2: $u, const MAX2: $u>(self) -> $target_t<MIN2, MAX2> {
+                        #[cfg(not(hax))]
+                        let () = const {
+                            assert!(
+                                $crate::derivate_conversions_for_bounded!(@any_int $u, MIN2)
+                                    .le($crate::derivate_conversions_for_bounded!(@any_int $t, MIN))
+                                && $crate::derivate_conversions_for_bounded!(@any_int $t, MAX)
+                                    .le($crate::derivate_conversions_for_bounded!(@any_int $u, MAX2)),
+                                "the bounds of the target do not contain the bounds of the source"
+                            )
+                        };
+                        ::hax_lib::Refinement::new(::hax_lib::Refinement::get(self) as $u)
+                    }
+                )*
+            }
+        }
+    };
+    ($($list:tt)*) => {
+        $crate::derivate_conversions_for_bounded!(@each [$($list)*] $($list)*);
+    };
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! mk_bounded {
     ($(#$attr:tt)* $bounded_t:ident<$(const $cst_name:ident : $cst_ty:ty),*>($t: ident $($bytes:expr)?, |$x:ident| $body:expr)$(,)?) => {
-        #[hax_lib::refinement_type(|$x| $body)]
+        #[hax_lib::refinement_type(debug, display, |$x| $body)]
         #[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
         $(#$attr)*
         pub struct $bounded_t<$(const $cst_name : $cst_ty),*>($t);
@@ -308,6 +493,7 @@ macro_rules! mk_bounded {
             #[doc = concat!("Bounded ", stringify!($t)," integers. This struct enforces the invariant that values are greater or equal to `MIN` and less or equal to `MAX`.")]
             $bounded_t<const MIN: $t, const MAX: $t>($t $($bytes)?, |x| x >= MIN && x <= MAX)
         );
+        $crate::derivate_interval_ops_for_bounded!($bounded_t($t));
     };
     ($bounded_t:ident($t: ident $($bytes:expr)?), $($tt:tt)+) => {
         $crate::mk_bounded!($bounded_t($t $($bytes)?));
@@ -330,6 +516,21 @@ mk_bounded!(
     BoundedUsize(usize),
 );
 
+derivate_conversions_for_bounded!(
+    BoundedI8(i8),
+    BoundedI16(i16),
+    BoundedI32(i32),
+    BoundedI64(i64),
+    BoundedI128(i128),
+    BoundedIsize(isize),
+    BoundedU8(u8),
+    BoundedU16(u16),
+    BoundedU32(u32),
+    BoundedU64(u64),
+    BoundedU128(u128),
+    BoundedUsize(usize),
+);
+
 /// Makes a refined new type in a very similar way to
 /// `hax_lib::refinement_tyoe`, but derives the various traits an
 /// integer type is expected to implement.
@@ -377,7 +578,7 @@ fn tests() {
     zzz += 32;
 
     let x: BoundedU8<0, 5> = 2.into_checked();
-    let y: BoundedU8<5, 10> = (x + x).into_checked();
+    let y: BoundedU8<5, 10> = (x + x + 1).into_checked();
 
     let _ = x >> 3;
     let _ = x >> BoundedU8::<0, 5>::new(3);
@@ -394,5 +595,20 @@ fn tests() {
     let _ = 4 / y;
     let _ = 4 * y;
     let _ = 4 + y;
-    let _ = 4 - y;
+    let _ = 10 - y;
+
+    let z: BoundedU8<5, 15> = x.add_bounded(y);
+    let _: BoundedU8<0, 10> = y.sub_bounded(x);
+    let _: BoundedU8<0, 50> = x.mul_bounded(y);
+    assert_eq!(z.narrow::<0, 7>().map(|z| z.get()), Some(7));
+    assert_eq!(z.narrow::<8, 15>(), None);
+
+    let w: BoundedI32<-3, 3> = (-2).into_checked();
+    let _: BoundedI32<-9, 9> = w.mul_bounded(w);
+    let _: BoundedI32<-6, 6> = w.sub_bounded(w);
+    let w: BoundedI64<-3, 3> = w.to_bounded_i64();
+    assert_eq!(w.get(), -2);
+    let z: BoundedUsize<0, 15> = z.to_bounded_usize();
+    let z: BoundedI16<-1, 255> = z.to_bounded_i16();
+    assert_eq!(z.get(), 7);
 }
@@ -1,6 +1,8 @@
 use quote::quote;
 use syn::{parse, parse_macro_input};
 
+mod protocol;
+
 /// This macro takes an `fn` as the basis of an `InitialState` implementation
 /// for the state type that is returned by the `fn` (on success).
 ///
@@ -307,3 +309,300 @@ pub fn read(
 
     output.into()
 }
+
+/// Converts a `CamelCase` type name to `snake_case`.
+fn snake_case(name: &str) -> String {
+    let mut result = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                result.push('_');
+            }
+            result.extend(c.to_lowercase());
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// Derives a binary wire format for a message struct or enum, i.e. a
+/// `hax_lib_protocol::wire::Message` implementation, in which every
+/// field is length-prefixed and enum variants are tagged with their
+/// index. The types of the fields must implement `Message`
+/// themselves.
+///
+/// The macro also generates the functions `encode_<name>` and
+/// `decode_<name>`, where `<name>` is the name of the message type in
+/// snake case. The encoder is extracted as a ProVerif constructor and
+/// the decoder as its destructor, so that the symbolic model of the
+/// wire format is always in sync with its implementation.
+///
+/// # Panics
+///
+/// Encoding is total, as its ProVerif constructor is: the generated
+/// `encode_to` and `encode_<name>` panic if a field is encoded on 4GiB
+/// or more, since its length does not fit in its 4-byte prefix.
+///
+/// Example:
+/// ```ignore
+/// #[derive(hax_lib_protocol_macros::Message)]
+/// pub enum Message {
+///     Ping(u8),
+///     Pong(u8),
+/// }
+///
+/// // The following is generated by the macro:
+/// #[hax_lib::exclude]
+/// impl ::hax_lib_protocol::wire::Message for Message {
+///     fn encode_to(&self, out: &mut Vec<u8>) {
+///         match self {
+///             Message::Ping(field_0) => {
+///                 out.push(0u8);
+///                 ::hax_lib_protocol::wire::encode_field(field_0, out);
+///             }
+///             Message::Pong(field_0) => {
+///                 out.push(1u8);
+///                 ::hax_lib_protocol::wire::encode_field(field_0, out);
+///             }
+///         }
+///     }
+///
+///     fn decode(bytes: &[u8]) -> ::hax_lib_protocol::ProtocolResult<Self> {
+///         let mut reader = ::hax_lib_protocol::wire::Reader::new(bytes);
+///         let value = match reader.tag()? {
+///             0u8 => Message::Ping(reader.field()?),
+///             1u8 => Message::Pong(reader.field()?),
+///             _ => return Err(::hax_lib_protocol::ProtocolError::InvalidMessage),
+///         };
+///         reader.finish()?;
+///         Ok(value)
+///     }
+/// }
+///
+/// #[hax_lib::pv_constructor]
+/// pub fn encode_message(value: &Message) -> Vec<u8> {
+///     ::hax_lib_protocol::wire::Message::encode(value)
+/// }
+///
+/// #[hax_lib::proverif::replace(
+///     "reduc forall x: $:{Message}; ${decode_message}(${encode_message}(x)) = x."
+/// )]
+/// pub fn decode_message(bytes: &[u8]) -> ::hax_lib_protocol::ProtocolResult<Message> {
+///     <Message as ::hax_lib_protocol::wire::Message>::decode(bytes)
+/// }
+/// ```
+#[proc_macro_error2::proc_macro_error]
+#[proc_macro_derive(Message)]
+pub fn message(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    use proc_macro_error2::abort;
+    use syn::spanned::Spanned;
+
+    let input: syn::DeriveInput = parse_macro_input!(item);
+    if !input.generics.params.is_empty() {
+        abort!(
+            input.generics.span(),
+            "Messages with generic parameters are not supported"
+        );
+    }
+    let name = &input.ident;
+    let vis = &input.vis;
+
+    /// The pattern binding the fields of a struct or variant, and the
+    /// expression decoding them.
+    fn fields(
+        path: proc_macro2::TokenStream,
+        fields: &syn::Fields,
+    ) -> (
+        proc_macro2::TokenStream,
+        Vec<syn::Ident>,
+        proc_macro2::TokenStream,
+    ) {
+        let bindings: Vec<syn::Ident> = fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                field
+                    .ident
+                    .clone()
+                    .unwrap_or_else(|| quote::format_ident!("field_{}", i))
+            })
+            .collect();
+        match fields {
+            syn::Fields::Named(_) => (
+                quote!(#path { #(#bindings),* }),
+                bindings.clone(),
+                quote!(#path { #(#bindings: reader.field()?),* }),
+            ),
+            syn::Fields::Unnamed(_) => {
+                let reads = bindings.iter().map(|_| quote!(reader.field()?));
+                (
+                    quote!(#path ( #(#bindings),* )),
+                    bindings.clone(),
+                    quote!(#path ( #(#reads),* )),
+                )
+            }
+            syn::Fields::Unit => (quote!(#path), bindings, quote!(#path)),
+        }
+    }
+
+    let (encode_body, decode_body) = match &input.data {
+        syn::Data::Struct(data) => {
+            let (pattern, bindings, decode) = fields(quote!(#name), &data.fields);
+            (
+                quote!(
+                    let #pattern = self;
+                    #(::hax_lib_protocol::wire::encode_field(#bindings, out);)*
+                ),
+                quote!(#decode),
+            )
+        }
+        syn::Data::Enum(data) => {
+            if data.variants.len() > 256 {
+                abort!(
+                    data.variants.span(),
+                    "Messages with more than 256 variants are not supported"
+                );
+            }
+            let (encode_arms, decode_arms): (Vec<_>, Vec<_>) = data
+                .variants
+                .iter()
+                .enumerate()
+                .map(|(tag, variant)| {
+                    let tag = tag as u8;
+                    let variant_name = &variant.ident;
+                    let (pattern, bindings, decode) =
+                        fields(quote!(#name::#variant_name), &variant.fields);
+                    (
+                        quote!(#pattern => {
+                            out.push(#tag);
+                            #(::hax_lib_protocol::wire::encode_field(#bindings, out);)*
+                        }),
+                        quote!(#tag => #decode,),
+                    )
+                })
+                .unzip();
+            (
+                quote!(match self { #(#encode_arms)* }),
+                quote!(match reader.tag()? {
+                    #(#decode_arms)*
+                    _ => return Err(::hax_lib_protocol::ProtocolError::InvalidMessage),
+                }),
+            )
+        }
+        syn::Data::Union(data) => {
+            abort!(
+                data.union_token.span(),
+                "Unions cannot be derived as messages"
+            );
+        }
+    };
+
+    let snake_name = snake_case(&name.to_string());
+    let encoder = quote::format_ident!("encode_{}", snake_name);
+    let decoder = quote::format_ident!("decode_{}", snake_name);
+    let equation = format!("reduc forall x: $:{{{name}}}; ${{{decoder}}}(${{{encoder}}}(x)) = x.");
+
+    quote!(
+        #[hax_lib::exclude]
+        impl ::hax_lib_protocol::wire::Message for #name {
+            #[allow(unused_variables)]
+            fn encode_to(&self, out: &mut Vec<u8>) {
+                #encode_body
+            }
+
+            fn decode(bytes: &[u8]) -> ::hax_lib_protocol::ProtocolResult<Self> {
+                let mut reader = ::hax_lib_protocol::wire::Reader::new(bytes);
+                let value = #decode_body;
+                reader.finish()?;
+                Ok(value)
+            }
+        }
+
+        #[hax_lib::pv_constructor]
+        #vis fn #encoder(value: &#name) -> Vec<u8> {
+            ::hax_lib_protocol::wire::Message::encode(value)
+        }
+
+        #[hax_lib::proverif::replace(#equation)]
+        #vis fn #decoder(bytes: &[u8]) -> ::hax_lib_protocol::ProtocolResult<#name> {
+            <#name as ::hax_lib_protocol::wire::Message>::decode(bytes)
+        }
+    )
+    .into()
+}
+
+/// Declares a two-party protocol: its messages, and for each of its
+/// two roles, the state types and the transitions between them.
+///
+/// A transition is one of
+/// - `init S with f;`, for `f: fn(Vec<u8>) -> ProtocolResult<S>`,
+/// - `init_empty S with f;`, for `f: fn() -> ProtocolResult<S>`,
+/// - `write S -> T (V) with f;`, for `f: fn(S) -> ProtocolResult<(T, M)>`,
+///   returning a message of variant `V`,
+/// - `read S -> T (V) with f;`, for `f: fn(S, M) -> ProtocolResult<T>`,
+///   accepting a message of variant `V`,
+///
+/// where `M` is the messages enum. For every transition, the macro
+/// generates a function marked as a ProVerif process, named after the
+/// kind and the states of the transition (`process_init_s`,
+/// `process_write_s_t` or `process_read_s_t`), and the corresponding
+/// `InitialState`, `WriteState` or `ReadState` implementation. The
+/// messages enum is marked as `hax_lib::protocol_messages`.
+///
+/// The macro fails if the protocol is not coherent, e.g. if a message
+/// is written by a role but never read by the other one, or if a role
+/// is never initialized.
+///
+/// Example:
+/// ```ignore
+/// hax_lib_protocol_macros::protocol! {
+///     pub enum Message {
+///         Ping(u8),
+///         Pong(u8),
+///     }
+///
+///     role Initiator {
+///         pub struct A0 { data: u8 }
+///         pub struct A1 {}
+///         pub struct A2 { received: u8 }
+///
+///         init A0 with init_a;
+///         write A0 -> A1 (Ping) with write_ping;
+///         read A1 -> A2 (Pong) with read_pong;
+///     }
+///
+///     role Responder {
+///         pub struct B0 {}
+///         pub struct B1 { received: u8 }
+///         pub struct B2 {}
+///
+///         init_empty B0 with init_b;
+///         read B0 -> B1 (Ping) with read_ping;
+///         write B1 -> B2 (Pong) with write_pong;
+///     }
+/// }
+///
+/// // Among others, the following is generated by the macro:
+/// #[hax_lib::process_write]
+/// fn process_write_a0_a1(state: A0) -> ::hax_lib_protocol::ProtocolResult<(A1, Message)> {
+///     write_ping(state)
+/// }
+///
+/// #[hax_lib::exclude]
+/// impl ::hax_lib_protocol::state_machine::WriteState for A0 {
+///     type NextState = A1;
+///     type Message = Message;
+///
+///     fn write(self) -> ::hax_lib_protocol::ProtocolResult<(A1, Message)> {
+///         let (next, msg) = process_write_a0_a1(self)?;
+///         debug_assert!(matches!(msg, Message::Ping { .. }), "`A0` must write a `Ping` message");
+///         Ok((next, msg))
+///     }
+/// }
+/// ```
+#[proc_macro]
+pub fn protocol(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let protocol: protocol::Protocol = parse_macro_input!(input);
+    protocol.expand().into()
+}
@@ -0,0 +1,418 @@
+//! Parsing, checking and expansion of the declarative protocol
+//! descriptions of the `protocol!` macro.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
+use syn::{braced, parenthesized, Ident, ItemEnum, ItemStruct, Path, Token};
+
+/// A protocol: its messages and its two roles.
+pub struct Protocol {
+    messages: ItemEnum,
+    roles: Vec<Role>,
+}
+
+/// A role of the protocol, i.e. the state machine of one party.
+struct Role {
+    name: Ident,
+    states: Vec<ItemStruct>,
+    transitions: Vec<Transition>,
+}
+
+/// A transition of a role, implemented by the function `with`.
+struct Transition {
+    kind: TransitionKind,
+    with: Path,
+}
+
+enum TransitionKind {
+    /// Initialization of the role from a prologue.
+    Init { state: Ident },
+    /// Initialization of the role without prologue.
+    InitEmpty { state: Ident },
+    /// Transition from `from` to `to` by writing the message variant
+    /// `message`.
+    Write {
+        from: Ident,
+        to: Ident,
+        message: Ident,
+    },
+    /// Transition from `from` to `to` by reading the message variant
+    /// `message`.
+    Read {
+        from: Ident,
+        to: Ident,
+        message: Ident,
+    },
+}
+
+impl Parse for Protocol {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let messages = input.parse()?;
+        let mut roles = Vec::new();
+        while !input.is_empty() {
+            roles.push(input.parse()?);
+        }
+        Ok(Self { messages, roles })
+    }
+}
+
+impl Parse for Role {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let keyword: Ident = input.parse()?;
+        if keyword != "role" {
+            return Err(syn::Error::new(keyword.span(), "Expected `role`"));
+        }
+        let name = input.parse()?;
+        let content;
+        braced!(content in input);
+        let mut states = Vec::new();
+        let mut transitions = Vec::new();
+        while !content.is_empty() {
+            if content.peek(Token![#]) || content.peek(Token![pub]) || content.peek(Token![struct])
+            {
+                states.push(content.parse()?);
+            } else {
+                transitions.push(content.parse()?);
+            }
+        }
+        Ok(Self {
+            name,
+            states,
+            transitions,
+        })
+    }
+}
+
+impl Parse for Transition {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let keyword: Ident = input.parse()?;
+        let kind = match keyword.to_string().as_str() {
+            "init" => TransitionKind::Init {
+                state: input.parse()?,
+            },
+            "init_empty" => TransitionKind::InitEmpty {
+                state: input.parse()?,
+            },
+            "write" | "read" => {
+                let from = input.parse()?;
+                input.parse::<Token![->]>()?;
+                let to = input.parse()?;
+                let message;
+                parenthesized!(message in input);
+                let message = message.parse()?;
+                if keyword == "write" {
+                    TransitionKind::Write { from, to, message }
+                } else {
+                    TransitionKind::Read { from, to, message }
+                }
+            }
+            _ => {
+                return Err(syn::Error::new(
+                    keyword.span(),
+                    "Expected a state or one of `init`, `init_empty`, `write` and `read`",
+                ))
+            }
+        };
+        let with: Ident = input.parse()?;
+        if with != "with" {
+            return Err(syn::Error::new(with.span(), "Expected `with`"));
+        }
+        let with = input.parse()?;
+        input.parse::<Token![;]>()?;
+        Ok(Self { kind, with })
+    }
+}
+
+impl TransitionKind {
+    /// The name of the `hax_lib::process_*` function of the
+    /// transition, made of its kind and of its states, which identify
+    /// it in a coherent protocol.
+    fn process_name(&self) -> Ident {
+        let kind = match self {
+            Self::Init { .. } | Self::InitEmpty { .. } => "init",
+            Self::Write { .. } => "write",
+            Self::Read { .. } => "read",
+        };
+        let states: Vec<String> = self
+            .states()
+            .iter()
+            .map(|state| crate::snake_case(&state.to_string()))
+            .collect();
+        format_ident!("process_{}_{}", kind, states.join("_"))
+    }
+
+    /// The states the transition goes through.
+    fn states(&self) -> Vec<&Ident> {
+        match self {
+            Self::Init { state } | Self::InitEmpty { state } => vec![state],
+            Self::Write { from, to, .. } | Self::Read { from, to, .. } => vec![from, to],
+        }
+    }
+}
+
+/// Accumulates errors, to report all of them at once.
+#[derive(Default)]
+struct Errors(Option<syn::Error>);
+
+impl Errors {
+    fn push(&mut self, span: proc_macro2::Span, message: String) {
+        let error = syn::Error::new(span, message);
+        match &mut self.0 {
+            Some(errors) => errors.combine(error),
+            None => self.0 = Some(error),
+        }
+    }
+}
+
+impl Protocol {
+    /// Checks that the protocol is a coherent two-party protocol:
+    /// transitions are between states of their role, every role is
+    /// initialized once, and every message written by a role is read
+    /// by the other role and conversely.
+    fn check(&self) -> syn::Result<()> {
+        let mut errors = Errors::default();
+        if self.roles.len() != 2 {
+            errors.push(
+                self.messages.ident.span(),
+                format!(
+                    "A protocol has exactly two roles, found {}",
+                    self.roles.len()
+                ),
+            );
+        }
+        let variants: Vec<&Ident> = self.messages.variants.iter().map(|v| &v.ident).collect();
+        let mut all_states: Vec<&Ident> = Vec::new();
+        for role in &self.roles {
+            for state in &role.states {
+                if all_states.contains(&&state.ident) {
+                    errors.push(
+                        state.ident.span(),
+                        format!("State `{}` is declared twice", state.ident),
+                    );
+                }
+                all_states.push(&state.ident);
+            }
+        }
+
+        let mut process_names: Vec<Ident> = Vec::new();
+        for transition in self.roles.iter().flat_map(|role| &role.transitions) {
+            let name = transition.kind.process_name();
+            if process_names.contains(&name) {
+                errors.push(
+                    transition.with.span(),
+                    format!(
+                        "Two transitions would generate a process named `{name}`, rename one of their states"
+                    ),
+                );
+            }
+            process_names.push(name);
+        }
+
+        for (i, role) in self.roles.iter().enumerate() {
+            let peer = (self.roles.len() == 2).then(|| &self.roles[1 - i]);
+            let is_state = |ident: &Ident| role.states.iter().any(|s| s.ident == *ident);
+            let mut inits = 0;
+            let mut writing_states: Vec<&Ident> = Vec::new();
+            let mut reads: Vec<(&Ident, &Ident)> = Vec::new();
+            for transition in &role.transitions {
+                for state in transition.kind.states() {
+                    if !is_state(state) {
+                        errors.push(
+                            state.span(),
+                            format!("`{state}` is not a state of role `{}`", role.name),
+                        );
+                    }
+                }
+                match &transition.kind {
+                    TransitionKind::Init { state } | TransitionKind::InitEmpty { state } => {
+                        inits += 1;
+                        if inits > 1 {
+                            errors.push(
+                                state.span(),
+                                format!("Role `{}` is initialized more than once", role.name),
+                            );
+                        }
+                    }
+                    TransitionKind::Write { from, message, .. } => {
+                        if writing_states.contains(&from) {
+                            errors.push(
+                                from.span(),
+                                format!("State `{from}` writes more than one message"),
+                            );
+                        }
+                        writing_states.push(from);
+                        let is_variant = check_message(&mut errors, &variants, message);
+                        match peer {
+                            Some(peer) if is_variant && !peer.reads(message) => errors.push(
+                                message.span(),
+                                format!(
+                                    "Message `{message}` is written by role `{}` but never read by role `{}`",
+                                    role.name, peer.name
+                                ),
+                            ),
+                            _ => (),
+                        }
+                    }
+                    TransitionKind::Read { from, to, message } => {
+                        if reads.contains(&(from, to)) {
+                            errors.push(
+                                to.span(),
+                                format!("State `{from}` reads into `{to}` more than once"),
+                            );
+                        }
+                        reads.push((from, to));
+                        let is_variant = check_message(&mut errors, &variants, message);
+                        match peer {
+                            Some(peer) if is_variant && !peer.writes(message) => errors.push(
+                                message.span(),
+                                format!(
+                                    "Message `{message}` is read by role `{}` but never written by role `{}`",
+                                    role.name, peer.name
+                                ),
+                            ),
+                            _ => (),
+                        }
+                    }
+                }
+            }
+            if inits == 0 {
+                errors.push(
+                    role.name.span(),
+                    format!(
+                        "Role `{}` has no `init` or `init_empty` transition",
+                        role.name
+                    ),
+                );
+            }
+        }
+        errors.0.map_or(Ok(()), Err)
+    }
+
+    /// The state types, the annotated messages enum, and for every
+    /// transition a `hax_lib::process_*` function calling the
+    /// transition function together with the corresponding
+    /// `state_machine` trait implementation. If the protocol is not
+    /// coherent, only the types are generated, along with the errors.
+    pub fn expand(self) -> TokenStream {
+        let messages = &self.messages;
+        let message_type = &messages.ident;
+        let mut output = quote!(
+            #[hax_lib::protocol_messages]
+            #messages
+        );
+        for state in self.roles.iter().flat_map(|role| &role.states) {
+            output.extend(quote!(#state));
+        }
+        if let Err(errors) = self.check() {
+            output.extend(errors.into_compile_error());
+            return output;
+        }
+        for role in &self.roles {
+            for Transition { kind, with } in &role.transitions {
+                let process = kind.process_name();
+                output.extend(match kind {
+                    TransitionKind::Init { state } => quote!(
+                        #[hax_lib::process_init]
+                        fn #process(prologue: Vec<u8>) -> ::hax_lib_protocol::ProtocolResult<#state> {
+                            #with(prologue)
+                        }
+
+                        #[hax_lib::exclude]
+                        impl ::hax_lib_protocol::state_machine::InitialState for #state {
+                            fn init(prologue: Option<Vec<u8>>) -> ::hax_lib_protocol::ProtocolResult<Self> {
+                                match prologue {
+                                    Some(prologue) => #process(prologue),
+                                    None => Err(::hax_lib_protocol::ProtocolError::InvalidPrologue),
+                                }
+                            }
+                        }
+                    ),
+                    TransitionKind::InitEmpty { state } => quote!(
+                        #[hax_lib::process_init]
+                        fn #process() -> ::hax_lib_protocol::ProtocolResult<#state> {
+                            #with()
+                        }
+
+                        #[hax_lib::exclude]
+                        impl ::hax_lib_protocol::state_machine::InitialState for #state {
+                            fn init(prologue: Option<Vec<u8>>) -> ::hax_lib_protocol::ProtocolResult<Self> {
+                                match prologue {
+                                    Some(_) => Err(::hax_lib_protocol::ProtocolError::InvalidPrologue),
+                                    None => #process(),
+                                }
+                            }
+                        }
+                    ),
+                    TransitionKind::Write { from, to, message } => quote!(
+                        #[hax_lib::process_write]
+                        fn #process(state: #from) -> ::hax_lib_protocol::ProtocolResult<(#to, #message_type)> {
+                            #with(state)
+                        }
+
+                        #[hax_lib::exclude]
+                        impl ::hax_lib_protocol::state_machine::WriteState for #from {
+                            type NextState = #to;
+                            type Message = #message_type;
+
+                            fn write(self) -> ::hax_lib_protocol::ProtocolResult<(#to, #message_type)> {
+                                let (next, msg) = #process(self)?;
+                                debug_assert!(
+                                    matches!(msg, #message_type::#message { .. }),
+                                    concat!("`", stringify!(#from), "` must write a `", stringify!(#message), "` message"),
+                                );
+                                Ok((next, msg))
+                            }
+                        }
+                    ),
+                    TransitionKind::Read { from, to, message } => quote!(
+                        #[hax_lib::process_read]
+                        fn #process(state: #from, msg: #message_type) -> ::hax_lib_protocol::ProtocolResult<#to> {
+                            #with(state, msg)
+                        }
+
+                        #[hax_lib::exclude]
+                        impl ::hax_lib_protocol::state_machine::ReadState<#to> for #from {
+                            type Message = #message_type;
+
+                            fn read(self, msg: #message_type) -> ::hax_lib_protocol::ProtocolResult<#to> {
+                                match msg {
+                                    #message_type::#message { .. } => #process(self, msg),
+                                    _ => Err(::hax_lib_protocol::ProtocolError::InvalidMessage),
+                                }
+                            }
+                        }
+                    ),
+                });
+            }
+        }
+        output
+    }
+}
+
+impl Role {
+    fn writes(&self, message: &Ident) -> bool {
+        self.transitions
+            .iter()
+            .any(|t| matches!(&t.kind, TransitionKind::Write { message: m, .. } if m == message))
+    }
+
+    fn reads(&self, message: &Ident) -> bool {
+        self.transitions
+            .iter()
+            .any(|t| matches!(&t.kind, TransitionKind::Read { message: m, .. } if m == message))
+    }
+}
+
+/// Checks that `message` is a variant of the messages enum.
+fn check_message(errors: &mut Errors, variants: &[&Ident], message: &Ident) -> bool {
+    let is_variant = variants.contains(&message);
+    if !is_variant {
+        errors.push(
+            message.span(),
+            format!("`{message}` is not a variant of the messages enum"),
+        );
+    }
+    is_variant
+}
@@ -0,0 +1,444 @@
+//! The default crypto provider, backed by `libcrux`.
+
+use super::*;
+
+/// The crypto provider backed by `libcrux`, computing on bytes.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Libcrux;
+
+impl From<DHGroup> for libcrux::ecdh::Algorithm {
+    /// Converter to `libcrux` type.
+    fn from(value: DHGroup) -> Self {
+        match value {
+            DHGroup::X25519 => libcrux::ecdh::Algorithm::X25519,
+            DHGroup::X448 => libcrux::ecdh::Algorithm::X448,
+            DHGroup::P256 => libcrux::ecdh::Algorithm::P256,
+            DHGroup::P384 => libcrux::ecdh::Algorithm::P384,
+            DHGroup::P521 => libcrux::ecdh::Algorithm::P521,
+        }
+    }
+}
+
+impl From<AEADAlgorithm> for libcrux::aead::Algorithm {
+    /// Converter to `libcrux` type.
+    fn from(value: AEADAlgorithm) -> Self {
+        match value {
+            AEADAlgorithm::Aes128Gcm => libcrux::aead::Algorithm::Aes128Gcm,
+            AEADAlgorithm::Aes256Gcm => libcrux::aead::Algorithm::Aes256Gcm,
+            AEADAlgorithm::Chacha20Poly1305 => libcrux::aead::Algorithm::Chacha20Poly1305,
+        }
+    }
+}
+
+impl From<HashAlgorithm> for libcrux::digest::Algorithm {
+    /// Converter to `libcrux` type.
+    fn from(value: HashAlgorithm) -> Self {
+        match value {
+            HashAlgorithm::Sha1 => libcrux::digest::Algorithm::Sha1,
+            HashAlgorithm::Sha224 => libcrux::digest::Algorithm::Sha224,
+            HashAlgorithm::Sha256 => libcrux::digest::Algorithm::Sha256,
+            HashAlgorithm::Sha384 => libcrux::digest::Algorithm::Sha384,
+            HashAlgorithm::Sha512 => libcrux::digest::Algorithm::Sha512,
+            HashAlgorithm::Blake2s => libcrux::digest::Algorithm::Blake2s,
+            HashAlgorithm::Blake2b => libcrux::digest::Algorithm::Blake2b,
+            HashAlgorithm::Sha3_224 => libcrux::digest::Algorithm::Sha3_224,
+            HashAlgorithm::Sha3_256 => libcrux::digest::Algorithm::Sha3_256,
+            HashAlgorithm::Sha3_384 => libcrux::digest::Algorithm::Sha3_384,
+            HashAlgorithm::Sha3_512 => libcrux::digest::Algorithm::Sha3_512,
+        }
+    }
+}
+
+impl From<HMACAlgorithm> for libcrux::hmac::Algorithm {
+    /// Converter to `libcrux` type.
+    fn from(value: HMACAlgorithm) -> Self {
+        match value {
+            HMACAlgorithm::Sha1 => libcrux::hmac::Algorithm::Sha1,
+            HMACAlgorithm::Sha256 => libcrux::hmac::Algorithm::Sha256,
+            HMACAlgorithm::Sha384 => libcrux::hmac::Algorithm::Sha384,
+            HMACAlgorithm::Sha512 => libcrux::hmac::Algorithm::Sha512,
+        }
+    }
+}
+
+impl From<HKDFAlgorithm> for libcrux::hkdf::Algorithm {
+    /// Converter to `libcrux` type.
+    fn from(value: HKDFAlgorithm) -> Self {
+        match value {
+            HKDFAlgorithm::Sha256 => libcrux::hkdf::Algorithm::Sha256,
+            HKDFAlgorithm::Sha384 => libcrux::hkdf::Algorithm::Sha384,
+            HKDFAlgorithm::Sha512 => libcrux::hkdf::Algorithm::Sha512,
+        }
+    }
+}
+
+impl From<SignatureAlgorithm> for libcrux::signature::Algorithm {
+    /// Converter to `libcrux` type.
+    fn from(value: SignatureAlgorithm) -> Self {
+        match value {
+            SignatureAlgorithm::Ed25519 => libcrux::signature::Algorithm::Ed25519,
+            SignatureAlgorithm::EcDsaP256 => libcrux::signature::Algorithm::EcDsaP256(
+                libcrux::signature::DigestAlgorithm::Sha256,
+            ),
+        }
+    }
+}
+
+impl From<KEMAlgorithm> for libcrux::kem::Algorithm {
+    /// Converter to `libcrux` type.
+    fn from(value: KEMAlgorithm) -> Self {
+        match value {
+            KEMAlgorithm::X25519 => libcrux::kem::Algorithm::X25519,
+            KEMAlgorithm::MlKem768 => libcrux::kem::Algorithm::Kyber768,
+        }
+    }
+}
+
+/// A random number generator that yields the bytes it is given, and
+/// then fails: this makes the randomized algorithms of `libcrux`
+/// deterministic.
+struct FixedRandomness<'a>(&'a [u8]);
+
+impl rand::RngCore for FixedRandomness<'_> {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        // Leaves `dest` untouched when the randomness is exhausted:
+        // `libcrux` only uses `try_fill_bytes`.
+        let _ = self.try_fill_bytes(dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        if dest.len() > self.0.len() {
+            const EXHAUSTED: core::num::NonZeroU32 =
+                match core::num::NonZeroU32::new(rand::Error::CUSTOM_START) {
+                    Some(code) => code,
+                    None => unreachable!(),
+                };
+            return Err(EXHAUSTED.into());
+        }
+        let (bytes, rest) = self.0.split_at(dest.len());
+        dest.copy_from_slice(bytes);
+        self.0 = rest;
+        Ok(())
+    }
+}
+
+impl rand::CryptoRng for FixedRandomness<'_> {}
+
+impl Libcrux {
+    fn aead_inputs(
+        key: &AEADKey<Self>,
+        iv: &AEADIV<Self>,
+    ) -> ProtocolResult<(libcrux::aead::Key, libcrux::aead::Iv)> {
+        let key = libcrux::aead::Key::from_bytes(key.algorithm().into(), key.value().clone())
+            .map_err(|_| ProtocolError::CryptoError)?;
+        let iv = libcrux::aead::Iv::new(iv.value()).map_err(|_| ProtocolError::CryptoError)?;
+        Ok((key, iv))
+    }
+}
+
+impl CryptoProvider for Libcrux {
+    type Value = Vec<u8>;
+
+    fn value_from_bytes(bytes: &[u8]) -> Vec<u8> {
+        bytes.to_vec()
+    }
+
+    fn dh_scalar_multiply(
+        &self,
+        group: DHGroup,
+        scalar: &DHScalar<Self>,
+        element: &DHElement<Self>,
+    ) -> ProtocolResult<Vec<u8>> {
+        libcrux::ecdh::derive(group.into(), element.value(), scalar.value())
+            .map_err(|_| ProtocolError::CryptoError)
+    }
+
+    fn dh_scalar_multiply_base(
+        &self,
+        group: DHGroup,
+        scalar: &DHScalar<Self>,
+    ) -> ProtocolResult<Vec<u8>> {
+        libcrux::ecdh::secret_to_public(group.into(), scalar.value())
+            .map_err(|_| ProtocolError::CryptoError)
+    }
+
+    fn aead_encrypt(
+        &self,
+        key: &AEADKey<Self>,
+        iv: &AEADIV<Self>,
+        aad: &Vec<u8>,
+        plain: &Vec<u8>,
+    ) -> ProtocolResult<(Vec<u8>, Vec<u8>)> {
+        let (key, iv) = Self::aead_inputs(key, iv)?;
+        let (tag, cip) = libcrux::aead::encrypt_detached(&key, plain, iv, aad)
+            .map_err(|_| ProtocolError::CryptoError)?;
+        Ok((cip, tag.as_ref().to_vec()))
+    }
+
+    fn aead_decrypt(
+        &self,
+        key: &AEADKey<Self>,
+        iv: &AEADIV<Self>,
+        aad: &Vec<u8>,
+        cip: &Vec<u8>,
+        tag: &AEADTag<Self>,
+    ) -> ProtocolResult<Vec<u8>> {
+        let (key, iv) = Self::aead_inputs(key, iv)?;
+        let tag: [u8; 16] = tag
+            .value()
+            .as_slice()
+            .try_into()
+            .map_err(|_| ProtocolError::CryptoError)?;
+        libcrux::aead::decrypt_detached(&key, cip, iv, aad, &tag.into())
+            .map_err(|_| ProtocolError::CryptoError)
+    }
+
+    fn hash(&self, algorithm: HashAlgorithm, input: &Vec<u8>) -> ProtocolResult<Vec<u8>> {
+        Ok(libcrux::digest::hash(algorithm.into(), input))
+    }
+
+    fn hmac(
+        &self,
+        algorithm: HMACAlgorithm,
+        key: &Vec<u8>,
+        input: &Vec<u8>,
+    ) -> ProtocolResult<Vec<u8>> {
+        Ok(libcrux::hmac::hmac(algorithm.into(), key, input, None))
+    }
+
+    fn hkdf_extract(
+        &self,
+        algorithm: HKDFAlgorithm,
+        salt: &Vec<u8>,
+        ikm: &Vec<u8>,
+    ) -> ProtocolResult<Vec<u8>> {
+        Ok(libcrux::hkdf::extract(algorithm.into(), salt, ikm))
+    }
+
+    fn hkdf_expand(
+        &self,
+        algorithm: HKDFAlgorithm,
+        prk: &Vec<u8>,
+        info: &Vec<u8>,
+        length: usize,
+    ) -> ProtocolResult<Vec<u8>> {
+        libcrux::hkdf::expand(algorithm.into(), prk, info, length)
+            .map_err(|_| ProtocolError::CryptoError)
+    }
+
+    fn signature_key_gen(
+        &self,
+        algorithm: SignatureAlgorithm,
+        randomness: &Vec<u8>,
+    ) -> ProtocolResult<(SigningKey<Self>, VerificationKey<Self>)> {
+        let (signing_key, verification_key) =
+            libcrux::signature::key_gen(algorithm.into(), &mut FixedRandomness(randomness))
+                .map_err(|_| ProtocolError::CryptoError)?;
+        Ok((
+            SigningKey::from_value(algorithm, signing_key),
+            VerificationKey::from_value(algorithm, verification_key),
+        ))
+    }
+
+    fn verification_key(&self, key: &SigningKey<Self>) -> ProtocolResult<VerificationKey<Self>> {
+        // `libcrux` only derives verification keys during key
+        // generation, which accepts a signing key as randomness if it
+        // is well-formed, and normalizes it otherwise.
+        let (signing_key, verification_key) =
+            self.signature_key_gen(key.algorithm(), key.value())?;
+        if signing_key.value() != key.value() {
+            return Err(ProtocolError::CryptoError);
+        }
+        Ok(verification_key)
+    }
+
+    fn sign(
+        &self,
+        key: &SigningKey<Self>,
+        message: &Vec<u8>,
+        randomness: &Vec<u8>,
+    ) -> ProtocolResult<Vec<u8>> {
+        libcrux::signature::sign(
+            key.algorithm().into(),
+            message,
+            key.value(),
+            &mut FixedRandomness(randomness),
+        )
+        .map(libcrux::signature::Signature::into_vec)
+        .map_err(|_| ProtocolError::CryptoError)
+    }
+
+    fn verify(
+        &self,
+        key: &VerificationKey<Self>,
+        message: &Vec<u8>,
+        signature: &Vec<u8>,
+    ) -> ProtocolResult<()> {
+        let bytes: [u8; 64] = signature
+            .as_slice()
+            .try_into()
+            .map_err(|_| ProtocolError::CryptoError)?;
+        let signature = match key.algorithm() {
+            SignatureAlgorithm::Ed25519 => libcrux::signature::Signature::Ed25519(
+                libcrux::signature::Ed25519Signature::from_bytes(bytes),
+            ),
+            SignatureAlgorithm::EcDsaP256 => libcrux::signature::Signature::EcDsaP256(
+                libcrux::signature::EcDsaP256Signature::from_bytes(bytes, key.algorithm().into()),
+            ),
+        };
+        libcrux::signature::verify(message, &signature, key.value())
+            .map_err(|_| ProtocolError::CryptoError)
+    }
+
+    fn kem_key_gen(
+        &self,
+        algorithm: KEMAlgorithm,
+        randomness: &Vec<u8>,
+    ) -> ProtocolResult<(KEMPrivateKey<Self>, KEMPublicKey<Self>)> {
+        let (private_key, public_key) =
+            libcrux::kem::key_gen(algorithm.into(), &mut FixedRandomness(randomness))
+                .map_err(|_| ProtocolError::CryptoError)?;
+        Ok((
+            KEMPrivateKey::from_value(algorithm, private_key.encode()),
+            KEMPublicKey::from_value(algorithm, public_key.encode()),
+        ))
+    }
+
+    fn kem_public_key(&self, key: &KEMPrivateKey<Self>) -> ProtocolResult<KEMPublicKey<Self>> {
+        let public_key = libcrux::kem::secret_to_public(key.algorithm().into(), key.value())
+            .map_err(|_| ProtocolError::CryptoError)?;
+        Ok(KEMPublicKey::from_value(key.algorithm(), public_key))
+    }
+
+    fn kem_encapsulate(
+        &self,
+        key: &KEMPublicKey<Self>,
+        randomness: &Vec<u8>,
+    ) -> ProtocolResult<(Vec<u8>, Vec<u8>)> {
+        let public_key = libcrux::kem::PublicKey::decode(key.algorithm().into(), key.value())
+            .map_err(|_| ProtocolError::CryptoError)?;
+        let (shared_secret, ciphertext) =
+            libcrux::kem::encapsulate(&public_key, &mut FixedRandomness(randomness))
+                .map_err(|_| ProtocolError::CryptoError)?;
+        Ok((shared_secret.encode(), ciphertext.encode()))
+    }
+
+    fn kem_decapsulate(
+        &self,
+        key: &KEMPrivateKey<Self>,
+        ciphertext: &Vec<u8>,
+    ) -> ProtocolResult<Vec<u8>> {
+        let algorithm = key.algorithm().into();
+        let private_key = libcrux::kem::PrivateKey::decode(algorithm, key.value())
+            .map_err(|_| ProtocolError::CryptoError)?;
+        let ciphertext = libcrux::kem::Ct::decode(algorithm, ciphertext)
+            .map_err(|_| ProtocolError::CryptoError)?;
+        libcrux::kem::decapsulate(&ciphertext, &private_key)
+            .map(|shared_secret| shared_secret.encode())
+            .map_err(|_| ProtocolError::CryptoError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::crypto::*;
+
+    #[test]
+    fn dh_commutes() {
+        let group = DHGroup::X25519;
+        let (a, b) = ([1; 32], [2; 32]);
+        let a_pub = dh_scalar_multiply_base(group, DHScalar::from_bytes(&a)).unwrap();
+        let b_pub = dh_scalar_multiply_base(group, DHScalar::from_bytes(&b)).unwrap();
+        let ab = dh_scalar_multiply(
+            group,
+            DHScalar::from_bytes(&a),
+            DHElement::from_bytes(&b_pub),
+        )
+        .unwrap();
+        let ba = dh_scalar_multiply(
+            group,
+            DHScalar::from_bytes(&b),
+            DHElement::from_bytes(&a_pub),
+        )
+        .unwrap();
+        assert_eq!(ab, ba);
+    }
+
+    #[test]
+    fn aead_round_trip() {
+        let algorithm = AEADAlgorithm::Chacha20Poly1305;
+        let key = || AEADKey::from_bytes(algorithm, &[0; 32]).unwrap();
+        let iv = || AEADIV::from_bytes(&[0; 12]).unwrap();
+        let (cip, tag) = aead_encrypt(key(), iv(), b"aad", b"plain").unwrap();
+        let decrypt = |aad: &[u8], cip: &[u8], tag: &[u8]| {
+            aead_decrypt(key(), iv(), aad, cip, AEADTag::from_bytes(tag).unwrap())
+        };
+        assert_eq!(decrypt(b"aad", &cip, &tag).unwrap(), b"plain");
+        assert!(decrypt(b"other", &cip, &tag).is_err());
+        let mut tampered = cip.clone();
+        tampered[0] ^= 1;
+        assert!(decrypt(b"aad", &tampered, &tag).is_err());
+    }
+
+    #[test]
+    fn hkdf_expand_length() {
+        let algorithm = HKDFAlgorithm::Sha256;
+        let prk = hkdf_extract(algorithm, b"salt", b"ikm").unwrap();
+        assert_eq!(hkdf_expand(algorithm, &prk, b"info", 42).unwrap().len(), 42);
+        assert!(hkdf_expand(algorithm, &prk, b"info", 255 * 32 + 1).is_err());
+    }
+
+    #[test]
+    fn signature_round_trip() {
+        for algorithm in [SignatureAlgorithm::Ed25519, SignatureAlgorithm::EcDsaP256] {
+            let (key, verification) = signature_key_gen(algorithm, &[7; 32]).unwrap();
+            assert_eq!(
+                verification_key(&key).unwrap().value(),
+                verification.value()
+            );
+            let signature = sign(&key, b"message", &[8; 32]).unwrap();
+            assert!(verify(&verification, b"message", &signature).is_ok());
+            assert!(verify(&verification, b"other", &signature).is_err());
+            let (_, other) = signature_key_gen(algorithm, &[9; 32]).unwrap();
+            assert!(verify(&other, b"message", &signature).is_err());
+        }
+    }
+
+    #[test]
+    fn kem_round_trip() {
+        let (key, public_key) = kem_key_gen(KEMAlgorithm::X25519, &[3; 32]).unwrap();
+        assert_eq!(kem_public_key(&key).unwrap().value(), public_key.value());
+        let (shared_secret, ciphertext) = kem_encapsulate(&public_key, &[4; 32]).unwrap();
+        assert_eq!(kem_decapsulate(&key, &ciphertext).unwrap(), shared_secret);
+        let (other, _) = kem_key_gen(KEMAlgorithm::X25519, &[5; 32]).unwrap();
+        assert_ne!(
+            kem_decapsulate(&other, &ciphertext).ok(),
+            Some(shared_secret)
+        );
+    }
+
+    #[test]
+    fn ml_kem_is_not_supported() {
+        assert!(matches!(
+            kem_key_gen(KEMAlgorithm::MlKem768, &[3; 64]),
+            Err(ProtocolError::CryptoError)
+        ));
+        let key = KEMPrivateKey::from_bytes(KEMAlgorithm::MlKem768, &[3; 2400]);
+        assert!(matches!(
+            kem_public_key(&key),
+            Err(ProtocolError::CryptoError)
+        ));
+    }
+}
@@ -0,0 +1,707 @@
+//! This module defines a cryptographic abstraction layer for use in
+//! hax protocol specifications.
+//!
+//! Cryptographic operations are performed by a [`CryptoProvider`].
+//! The free functions of this module use the default provider,
+//! [`Libcrux`]. Protocol specifications that are generic over the
+//! provider can also be executed with the [`Symbolic`] provider,
+//! which computes on terms instead of bytes: this is meant for tests,
+//! e.g. to cross-check a specification against its ProVerif model.
+//!
+//! The free functions are extracted to ProVerif constructors and
+//! destructors, with the same equations as the [`Symbolic`] provider.
+//! Group elements and AEAD tags received as bytes are converted with
+//! ProVerif type converters.
+
+use crate::{ProtocolError, ProtocolResult};
+
+mod libcrux_provider;
+mod symbolic;
+
+pub use libcrux_provider::Libcrux;
+pub use symbolic::{AEADInputs, Symbolic, Term};
+
+/// A provider of cryptographic operations.
+///
+/// A provider computes on values of type [`CryptoProvider::Value`]:
+/// bytes for a concrete provider, terms for a symbolic one. Operations
+/// never panic, failures are reported as
+/// [`ProtocolError::CryptoError`].
+pub trait CryptoProvider: Sized {
+    /// The values this provider computes on.
+    type Value: Clone + PartialEq + core::fmt::Debug;
+
+    /// Embeds public `bytes` into a value.
+    fn value_from_bytes(bytes: &[u8]) -> Self::Value;
+
+    /// Scalar multiplication of `scalar` and `element`.
+    fn dh_scalar_multiply(
+        &self,
+        group: DHGroup,
+        scalar: &DHScalar<Self>,
+        element: &DHElement<Self>,
+    ) -> ProtocolResult<Self::Value>;
+
+    /// Scalar multiplication of a fixed generator and `scalar`.
+    fn dh_scalar_multiply_base(
+        &self,
+        group: DHGroup,
+        scalar: &DHScalar<Self>,
+    ) -> ProtocolResult<Self::Value>;
+
+    /// AEAD encryption of `plain`, returns `(ciphertext, tag)`.
+    fn aead_encrypt(
+        &self,
+        key: &AEADKey<Self>,
+        iv: &AEADIV<Self>,
+        aad: &Self::Value,
+        plain: &Self::Value,
+    ) -> ProtocolResult<(Self::Value, Self::Value)>;
+
+    /// AEAD decryption of `cip`, returns the plaintext.
+    fn aead_decrypt(
+        &self,
+        key: &AEADKey<Self>,
+        iv: &AEADIV<Self>,
+        aad: &Self::Value,
+        cip: &Self::Value,
+        tag: &AEADTag<Self>,
+    ) -> ProtocolResult<Self::Value>;
+
+    /// Hashing of `input` using `algorithm`.
+    fn hash(&self, algorithm: HashAlgorithm, input: &Self::Value) -> ProtocolResult<Self::Value>;
+
+    /// HMAC of `input` using `algorithm` as the hash function.
+    fn hmac(
+        &self,
+        algorithm: HMACAlgorithm,
+        key: &Self::Value,
+        input: &Self::Value,
+    ) -> ProtocolResult<Self::Value>;
+
+    /// HKDF extraction of a pseudo-random key from `salt` and the
+    /// input key material `ikm`.
+    fn hkdf_extract(
+        &self,
+        algorithm: HKDFAlgorithm,
+        salt: &Self::Value,
+        ikm: &Self::Value,
+    ) -> ProtocolResult<Self::Value>;
+
+    /// HKDF expansion of the pseudo-random key `prk` into `length`
+    /// bytes of key material, bound to `info`.
+    fn hkdf_expand(
+        &self,
+        algorithm: HKDFAlgorithm,
+        prk: &Self::Value,
+        info: &Self::Value,
+        length: usize,
+    ) -> ProtocolResult<Self::Value>;
+
+    /// Generation of a signature key pair from `randomness`.
+    fn signature_key_gen(
+        &self,
+        algorithm: SignatureAlgorithm,
+        randomness: &Self::Value,
+    ) -> ProtocolResult<(SigningKey<Self>, VerificationKey<Self>)>;
+
+    /// The verification key of the signing key `key`.
+    fn verification_key(&self, key: &SigningKey<Self>) -> ProtocolResult<VerificationKey<Self>>;
+
+    /// Signature of `message` with `key`, using `randomness` if the
+    /// algorithm is randomized.
+    fn sign(
+        &self,
+        key: &SigningKey<Self>,
+        message: &Self::Value,
+        randomness: &Self::Value,
+    ) -> ProtocolResult<Self::Value>;
+
+    /// Verification of the signature `signature` of `message`.
+    fn verify(
+        &self,
+        key: &VerificationKey<Self>,
+        message: &Self::Value,
+        signature: &Self::Value,
+    ) -> ProtocolResult<()>;
+
+    /// Generation of a KEM key pair from `randomness`.
+    fn kem_key_gen(
+        &self,
+        algorithm: KEMAlgorithm,
+        randomness: &Self::Value,
+    ) -> ProtocolResult<(KEMPrivateKey<Self>, KEMPublicKey<Self>)>;
+
+    /// The public key of the KEM private key `key`.
+    fn kem_public_key(&self, key: &KEMPrivateKey<Self>) -> ProtocolResult<KEMPublicKey<Self>>;
+
+    /// Encapsulation of a fresh shared secret to `key`, returns
+    /// `(shared_secret, ciphertext)`.
+    fn kem_encapsulate(
+        &self,
+        key: &KEMPublicKey<Self>,
+        randomness: &Self::Value,
+    ) -> ProtocolResult<(Self::Value, Self::Value)>;
+
+    /// Decapsulation of the shared secret in `ciphertext`.
+    fn kem_decapsulate(
+        &self,
+        key: &KEMPrivateKey<Self>,
+        ciphertext: &Self::Value,
+    ) -> ProtocolResult<Self::Value>;
+}
+
+/// Checks that `bytes` has length `expected`.
+fn check_length(bytes: &[u8], expected: usize) -> ProtocolResult<()> {
+    if bytes.len() == expected {
+        Ok(())
+    } else {
+        Err(ProtocolError::CryptoError)
+    }
+}
+
+/// An abstract Diffie-Hellman scalar.
+pub struct DHScalar<P: CryptoProvider = Libcrux>(P::Value);
+
+impl<P: CryptoProvider> DHScalar<P> {
+    /// Wrap bytes into a Diffie-Hellman scalar. Does *not* perform
+    /// input validation.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        DHScalar(P::value_from_bytes(bytes))
+    }
+
+    /// Wrap a value of the provider into a Diffie-Hellman scalar.
+    pub fn from_value(value: P::Value) -> Self {
+        DHScalar(value)
+    }
+
+    /// The value of the provider this scalar wraps.
+    pub fn value(&self) -> &P::Value {
+        &self.0
+    }
+}
+
+impl<P: CryptoProvider> Clone for DHScalar<P> {
+    fn clone(&self) -> Self {
+        DHScalar(self.0.clone())
+    }
+}
+
+/// An abstract Diffie-Hellman group element.
+pub struct DHElement<P: CryptoProvider = Libcrux>(P::Value);
+
+impl<P: CryptoProvider> DHElement<P> {
+    /// Wrap bytes into a Diffie-Hellman group element. Does *not* perform
+    /// input validation.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        DHElement(P::value_from_bytes(bytes))
+    }
+
+    /// Wrap a value of the provider into a Diffie-Hellman group
+    /// element, e.g. the result of [`CryptoProvider::dh_scalar_multiply_base`].
+    pub fn from_value(value: P::Value) -> Self {
+        DHElement(value)
+    }
+
+    /// The value of the provider this element wraps.
+    pub fn value(&self) -> &P::Value {
+        &self.0
+    }
+}
+
+impl<P: CryptoProvider> Clone for DHElement<P> {
+    fn clone(&self) -> Self {
+        DHElement(self.0.clone())
+    }
+}
+
+/// Choice of Diffie-Hellman groups.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum DHGroup {
+    X25519,
+    X448,
+    P256,
+    P384,
+    P521,
+}
+
+/// An abstract AEAD key.
+pub struct AEADKey<P: CryptoProvider = Libcrux> {
+    algorithm: AEADAlgorithm,
+    value: P::Value,
+}
+
+/// Choice of AEAD algorithms.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum AEADAlgorithm {
+    Aes128Gcm,
+    Aes256Gcm,
+    Chacha20Poly1305,
+}
+
+impl AEADAlgorithm {
+    /// The length of the keys of this algorithm, in bytes.
+    pub const fn key_length(self) -> usize {
+        match self {
+            AEADAlgorithm::Aes128Gcm => 16,
+            AEADAlgorithm::Aes256Gcm | AEADAlgorithm::Chacha20Poly1305 => 32,
+        }
+    }
+}
+
+impl<P: CryptoProvider> AEADKey<P> {
+    /// Attempt deserialization of `bytes` into an AEAD key for
+    /// `algorithm`. Fails if `bytes` does not have the key length of
+    /// `algorithm`.
+    pub fn from_bytes(algorithm: AEADAlgorithm, bytes: &[u8]) -> ProtocolResult<Self> {
+        check_length(bytes, algorithm.key_length())?;
+        Ok(Self::from_value(algorithm, P::value_from_bytes(bytes)))
+    }
+
+    /// Wrap a value of the provider into an AEAD key for `algorithm`,
+    /// e.g. the output of a key derivation. Does *not* perform input
+    /// validation.
+    pub fn from_value(algorithm: AEADAlgorithm, value: P::Value) -> Self {
+        AEADKey { algorithm, value }
+    }
+
+    /// The algorithm this key is for.
+    pub fn algorithm(&self) -> AEADAlgorithm {
+        self.algorithm
+    }
+
+    /// The value of the provider this key wraps.
+    pub fn value(&self) -> &P::Value {
+        &self.value
+    }
+}
+
+/// An abstract AEAD initialization vector.
+pub struct AEADIV<P: CryptoProvider = Libcrux>(P::Value);
+
+impl<P: CryptoProvider> AEADIV<P> {
+    /// The length of AEAD initialization vectors, in bytes.
+    pub const LENGTH: usize = 12;
+
+    /// Attempt construction of an AEAD IV from `bytes`. Fails if the
+    /// number of `bytes` is not [`AEADIV::LENGTH`].
+    pub fn from_bytes(bytes: &[u8]) -> ProtocolResult<Self> {
+        check_length(bytes, Self::LENGTH)?;
+        Ok(AEADIV(P::value_from_bytes(bytes)))
+    }
+
+    /// Wrap a value of the provider into an AEAD IV. Does *not*
+    /// perform input validation.
+    pub fn from_value(value: P::Value) -> Self {
+        AEADIV(value)
+    }
+
+    /// The value of the provider this IV wraps.
+    pub fn value(&self) -> &P::Value {
+        &self.0
+    }
+}
+
+/// An abstract AEAD authentication tag.
+pub struct AEADTag<P: CryptoProvider = Libcrux>(P::Value);
+
+impl<P: CryptoProvider> AEADTag<P> {
+    /// The length of AEAD authentication tags, in bytes.
+    pub const LENGTH: usize = 16;
+
+    /// Attempt deserialization of an AEAD tag from `bytes`. Fails if
+    /// the number of `bytes` is not [`AEADTag::LENGTH`].
+    pub fn from_bytes(bytes: &[u8]) -> ProtocolResult<Self> {
+        check_length(bytes, Self::LENGTH)?;
+        Ok(AEADTag(P::value_from_bytes(bytes)))
+    }
+
+    /// Wrap a value of the provider into an AEAD tag, e.g. the tag
+    /// returned by [`CryptoProvider::aead_encrypt`]. Does *not*
+    /// perform input validation.
+    pub fn from_value(value: P::Value) -> Self {
+        AEADTag(value)
+    }
+
+    /// The value of the provider this tag wraps.
+    pub fn value(&self) -> &P::Value {
+        &self.0
+    }
+}
+
+/// Choice of hashing algorithms.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum HashAlgorithm {
+    Sha1,
+    Sha224,
+    Sha256,
+    Sha384,
+    Sha512,
+    Blake2s,
+    Blake2b,
+    Sha3_224,
+    Sha3_256,
+    Sha3_384,
+    Sha3_512,
+}
+
+/// Choice of algorithms for instantiation of HMAC.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum HMACAlgorithm {
+    Sha1,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+/// Choice of hash functions for HKDF.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum HKDFAlgorithm {
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+/// Choice of signature algorithms.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum SignatureAlgorithm {
+    Ed25519,
+    /// ECDSA on P-256 with SHA-256.
+    EcDsaP256,
+}
+
+/// An abstract signing key.
+pub struct SigningKey<P: CryptoProvider = Libcrux> {
+    algorithm: SignatureAlgorithm,
+    value: P::Value,
+}
+
+impl<P: CryptoProvider> SigningKey<P> {
+    /// Wrap bytes into a signing key for `algorithm`. Does *not*
+    /// perform input validation.
+    pub fn from_bytes(algorithm: SignatureAlgorithm, bytes: &[u8]) -> Self {
+        Self::from_value(algorithm, P::value_from_bytes(bytes))
+    }
+
+    /// Wrap a value of the provider into a signing key for
+    /// `algorithm`. Does *not* perform input validation.
+    pub fn from_value(algorithm: SignatureAlgorithm, value: P::Value) -> Self {
+        SigningKey { algorithm, value }
+    }
+
+    /// The algorithm this key is for.
+    pub fn algorithm(&self) -> SignatureAlgorithm {
+        self.algorithm
+    }
+
+    /// The value of the provider this key wraps.
+    pub fn value(&self) -> &P::Value {
+        &self.value
+    }
+}
+
+/// An abstract signature verification key.
+pub struct VerificationKey<P: CryptoProvider = Libcrux> {
+    algorithm: SignatureAlgorithm,
+    value: P::Value,
+}
+
+impl<P: CryptoProvider> VerificationKey<P> {
+    /// Wrap bytes into a verification key for `algorithm`. Does *not*
+    /// perform input validation.
+    pub fn from_bytes(algorithm: SignatureAlgorithm, bytes: &[u8]) -> Self {
+        Self::from_value(algorithm, P::value_from_bytes(bytes))
+    }
+
+    /// Wrap a value of the provider into a verification key for
+    /// `algorithm`. Does *not* perform input validation.
+    pub fn from_value(algorithm: SignatureAlgorithm, value: P::Value) -> Self {
+        VerificationKey { algorithm, value }
+    }
+
+    /// The algorithm this key is for.
+    pub fn algorithm(&self) -> SignatureAlgorithm {
+        self.algorithm
+    }
+
+    /// The value of the provider this key wraps.
+    pub fn value(&self) -> &P::Value {
+        &self.value
+    }
+}
+
+impl<P: CryptoProvider> Clone for VerificationKey<P> {
+    fn clone(&self) -> Self {
+        Self::from_value(self.algorithm, self.value.clone())
+    }
+}
+
+/// Choice of key encapsulation mechanisms.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+pub enum KEMAlgorithm {
+    /// DH-based KEM on X25519.
+    X25519,
+    /// ML-KEM-768 (Kyber768). The version of `libcrux` used by the
+    /// [`Libcrux`] provider does not implement it yet: its operations
+    /// fail with [`ProtocolError::CryptoError`] there.
+    MlKem768,
+}
+
+/// An abstract KEM private key.
+pub struct KEMPrivateKey<P: CryptoProvider = Libcrux> {
+    algorithm: KEMAlgorithm,
+    value: P::Value,
+}
+
+impl<P: CryptoProvider> KEMPrivateKey<P> {
+    /// Wrap bytes into a KEM private key for `algorithm`. Does *not*
+    /// perform input validation.
+    pub fn from_bytes(algorithm: KEMAlgorithm, bytes: &[u8]) -> Self {
+        Self::from_value(algorithm, P::value_from_bytes(bytes))
+    }
+
+    /// Wrap a value of the provider into a KEM private key for
+    /// `algorithm`. Does *not* perform input validation.
+    pub fn from_value(algorithm: KEMAlgorithm, value: P::Value) -> Self {
+        KEMPrivateKey { algorithm, value }
+    }
+
+    /// The algorithm this key is for.
+    pub fn algorithm(&self) -> KEMAlgorithm {
+        self.algorithm
+    }
+
+    /// The value of the provider this key wraps.
+    pub fn value(&self) -> &P::Value {
+        &self.value
+    }
+}
+
+/// An abstract KEM public key.
+pub struct KEMPublicKey<P: CryptoProvider = Libcrux> {
+    algorithm: KEMAlgorithm,
+    value: P::Value,
+}
+
+impl<P: CryptoProvider> KEMPublicKey<P> {
+    /// Wrap bytes into a KEM public key for `algorithm`. Does *not*
+    /// perform input validation.
+    pub fn from_bytes(algorithm: KEMAlgorithm, bytes: &[u8]) -> Self {
+        Self::from_value(algorithm, P::value_from_bytes(bytes))
+    }
+
+    /// Wrap a value of the provider into a KEM public key for
+    /// `algorithm`. Does *not* perform input validation.
+    pub fn from_value(algorithm: KEMAlgorithm, value: P::Value) -> Self {
+        KEMPublicKey { algorithm, value }
+    }
+
+    /// The algorithm this key is for.
+    pub fn algorithm(&self) -> KEMAlgorithm {
+        self.algorithm
+    }
+
+    /// The value of the provider this key wraps.
+    pub fn value(&self) -> &P::Value {
+        &self.value
+    }
+}
+
+impl<P: CryptoProvider> Clone for KEMPublicKey<P> {
+    fn clone(&self) -> Self {
+        Self::from_value(self.algorithm, self.value.clone())
+    }
+}
+
+/// Scalar multiplication of `scalar` and `element`, using [`Libcrux`].
+#[hax_lib::proverif::replace(
+    "fun hax_dh_element(bitstring): $:{DHElement} [typeConverter].
+fun ${dh_scalar_multiply}($:{DHGroup}, $:{DHScalar}, $:{DHElement}): bitstring.
+equation forall group: $:{DHGroup}, a: $:{DHScalar}, b: $:{DHScalar};
+    ${dh_scalar_multiply}(group, a, hax_dh_element(${dh_scalar_multiply_base}(group, b)))
+        = ${dh_scalar_multiply}(group, b, hax_dh_element(${dh_scalar_multiply_base}(group, a)))."
+)]
+pub fn dh_scalar_multiply(
+    group: DHGroup,
+    scalar: DHScalar,
+    element: DHElement,
+) -> ProtocolResult<Vec<u8>> {
+    Libcrux.dh_scalar_multiply(group, &scalar, &element)
+}
+
+/// Scalar multiplication of a fixed generator and `scalar`, using
+/// [`Libcrux`].
+#[hax_lib::proverif::replace(
+    "fun ${dh_scalar_multiply_base}($:{DHGroup}, $:{DHScalar}): bitstring."
+)]
+pub fn dh_scalar_multiply_base(group: DHGroup, scalar: DHScalar) -> ProtocolResult<Vec<u8>> {
+    Libcrux.dh_scalar_multiply_base(group, &scalar)
+}
+
+/// AEAD encryption using [`Libcrux`] and the algorithm of `key`.
+/// Returns a pair of byte vectors `(ciphertext, tag)`.
+#[hax_lib::proverif::replace(
+    "fun hax_aead_ciphertext($:{AEADKey}, $:{AEADIV}, bitstring, bitstring): bitstring.
+fun hax_aead_tag($:{AEADKey}, $:{AEADIV}, bitstring, bitstring): bitstring.
+letfun ${aead_encrypt}(key: $:{AEADKey}, iv: $:{AEADIV}, aad: bitstring, plain: bitstring) =
+    (hax_aead_ciphertext(key, iv, aad, plain), hax_aead_tag(key, iv, aad, plain))."
+)]
+pub fn aead_encrypt(
+    key: AEADKey,
+    iv: AEADIV,
+    aad: &[u8],
+    plain: &[u8],
+) -> ProtocolResult<(Vec<u8>, Vec<u8>)> {
+    Libcrux.aead_encrypt(&key, &iv, &aad.to_vec(), &plain.to_vec())
+}
+
+/// AEAD decryption using [`Libcrux`] and the algorithm of `key`. On
+/// success returns the decrypted plaintext, otherwise a `CryptoError`.
+#[hax_lib::proverif::replace(
+    "fun hax_aead_tag_value(bitstring): $:{AEADTag} [typeConverter].
+reduc forall key: $:{AEADKey}, iv: $:{AEADIV}, aad: bitstring, plain: bitstring;
+    ${aead_decrypt}(
+        key,
+        iv,
+        aad,
+        hax_aead_ciphertext(key, iv, aad, plain),
+        hax_aead_tag_value(hax_aead_tag(key, iv, aad, plain))
+    ) = plain."
+)]
+pub fn aead_decrypt(
+    key: AEADKey,
+    iv: AEADIV,
+    aad: &[u8],
+    cip: &[u8],
+    tag: AEADTag,
+) -> ProtocolResult<Vec<u8>> {
+    Libcrux.aead_decrypt(&key, &iv, &aad.to_vec(), &cip.to_vec(), &tag)
+}
+
+/// Hashing using [`Libcrux`] and `algorithm`.
+#[hax_lib::proverif::replace("fun ${hash}($:{HashAlgorithm}, bitstring): bitstring.")]
+pub fn hash(algorithm: HashAlgorithm, input: &[u8]) -> ProtocolResult<Vec<u8>> {
+    Libcrux.hash(algorithm, &input.to_vec())
+}
+
+/// HMAC using [`Libcrux`] and `algorithm` as the hash function.
+#[hax_lib::proverif::replace("fun ${hmac}($:{HMACAlgorithm}, bitstring, bitstring): bitstring.")]
+pub fn hmac(algorithm: HMACAlgorithm, key: &[u8], input: &[u8]) -> ProtocolResult<Vec<u8>> {
+    Libcrux.hmac(algorithm, &key.to_vec(), &input.to_vec())
+}
+
+/// HKDF extraction using [`Libcrux`] and `algorithm`.
+#[hax_lib::proverif::replace(
+    "fun ${hkdf_extract}($:{HKDFAlgorithm}, bitstring, bitstring): bitstring."
+)]
+pub fn hkdf_extract(algorithm: HKDFAlgorithm, salt: &[u8], ikm: &[u8]) -> ProtocolResult<Vec<u8>> {
+    Libcrux.hkdf_extract(algorithm, &salt.to_vec(), &ikm.to_vec())
+}
+
+/// HKDF expansion using [`Libcrux`] and `algorithm`. Fails if
+/// `length` is too large for `algorithm`.
+#[hax_lib::proverif::replace(
+    "fun ${hkdf_expand}($:{HKDFAlgorithm}, bitstring, bitstring, $:{usize}): bitstring."
+)]
+pub fn hkdf_expand(
+    algorithm: HKDFAlgorithm,
+    prk: &[u8],
+    info: &[u8],
+    length: usize,
+) -> ProtocolResult<Vec<u8>> {
+    Libcrux.hkdf_expand(algorithm, &prk.to_vec(), &info.to_vec(), length)
+}
+
+/// The verification key of `key`, using [`Libcrux`].
+#[hax_lib::proverif::replace("fun ${verification_key}($:{SigningKey}): $:{VerificationKey}.")]
+pub fn verification_key(key: &SigningKey) -> ProtocolResult<VerificationKey> {
+    Libcrux.verification_key(key)
+}
+
+/// Generation of a signature key pair from `randomness`, using
+/// [`Libcrux`].
+#[hax_lib::proverif::replace(
+    "fun hax_signing_key($:{SignatureAlgorithm}, bitstring): $:{SigningKey}.
+letfun ${signature_key_gen}(algorithm: $:{SignatureAlgorithm}, randomness: bitstring) =
+    let key = hax_signing_key(algorithm, randomness) in
+    (key, ${verification_key}(key))."
+)]
+pub fn signature_key_gen(
+    algorithm: SignatureAlgorithm,
+    randomness: &[u8],
+) -> ProtocolResult<(SigningKey, VerificationKey)> {
+    Libcrux.signature_key_gen(algorithm, &randomness.to_vec())
+}
+
+/// Signature of `message` with `key` using [`Libcrux`]. `randomness`
+/// is used by ECDSA only.
+#[hax_lib::proverif::replace("fun ${sign}($:{SigningKey}, bitstring, bitstring): bitstring.")]
+pub fn sign(key: &SigningKey, message: &[u8], randomness: &[u8]) -> ProtocolResult<Vec<u8>> {
+    Libcrux.sign(key, &message.to_vec(), &randomness.to_vec())
+}
+
+/// Verification of the signature `signature` of `message` using
+/// [`Libcrux`]. Fails with a `CryptoError` if the signature is
+/// invalid.
+#[hax_lib::proverif::replace(
+    "reduc forall key: $:{SigningKey}, message: bitstring, randomness: bitstring;
+    ${verify}(${verification_key}(key), message, ${sign}(key, message, randomness)) = empty."
+)]
+pub fn verify(key: &VerificationKey, message: &[u8], signature: &[u8]) -> ProtocolResult<()> {
+    Libcrux.verify(key, &message.to_vec(), &signature.to_vec())
+}
+
+/// The public key of the KEM private key `key`, using [`Libcrux`].
+#[hax_lib::proverif::replace(
+    "fun ${kem_public_key}($:{KEMPrivateKey}): $:{KEMPublicKey}.
+fun hax_kem_shared_secret($:{KEMPublicKey}, bitstring): bitstring.
+fun hax_kem_ciphertext($:{KEMPublicKey}, bitstring): bitstring."
+)]
+pub fn kem_public_key(key: &KEMPrivateKey) -> ProtocolResult<KEMPublicKey> {
+    Libcrux.kem_public_key(key)
+}
+
+/// Generation of a KEM key pair from `randomness`, using [`Libcrux`].
+#[hax_lib::proverif::replace(
+    "fun hax_kem_private_key($:{KEMAlgorithm}, bitstring): $:{KEMPrivateKey}.
+letfun ${kem_key_gen}(algorithm: $:{KEMAlgorithm}, randomness: bitstring) =
+    let key = hax_kem_private_key(algorithm, randomness) in
+    (key, ${kem_public_key}(key))."
+)]
+pub fn kem_key_gen(
+    algorithm: KEMAlgorithm,
+    randomness: &[u8],
+) -> ProtocolResult<(KEMPrivateKey, KEMPublicKey)> {
+    Libcrux.kem_key_gen(algorithm, &randomness.to_vec())
+}
+
+/// Encapsulation of a fresh shared secret to `key` using [`Libcrux`].
+/// Returns a pair of byte vectors `(shared_secret, ciphertext)`.
+#[hax_lib::proverif::replace(
+    "letfun ${kem_encapsulate}(key: $:{KEMPublicKey}, randomness: bitstring) =
+    (hax_kem_shared_secret(key, randomness), hax_kem_ciphertext(key, randomness))."
+)]
+pub fn kem_encapsulate(
+    key: &KEMPublicKey,
+    randomness: &[u8],
+) -> ProtocolResult<(Vec<u8>, Vec<u8>)> {
+    Libcrux.kem_encapsulate(key, &randomness.to_vec())
+}
+
+/// Decapsulation of the shared secret in `ciphertext` using
+/// [`Libcrux`].
+#[hax_lib::proverif::replace(
+    "reduc forall key: $:{KEMPrivateKey}, randomness: bitstring;
+    ${kem_decapsulate}(key, hax_kem_ciphertext(${kem_public_key}(key), randomness))
+        = hax_kem_shared_secret(${kem_public_key}(key), randomness)."
+)]
+pub fn kem_decapsulate(key: &KEMPrivateKey, ciphertext: &[u8]) -> ProtocolResult<Vec<u8>> {
+    Libcrux.kem_decapsulate(key, &ciphertext.to_vec())
+}
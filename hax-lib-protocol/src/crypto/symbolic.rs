@@ -0,0 +1,422 @@
+//! A symbolic crypto provider, computing on terms instead of bytes.
+//!
+//! Cryptographic primitives are modeled as in the ProVerif backend:
+//! they are perfect, and the only equations between terms are the
+//! ones of Diffie-Hellman (`a * (b * G) = b * (a * G)`) and of AEAD
+//! decryption, signature verification and KEM decapsulation. Keys
+//! generated from some randomness are the randomness itself. The provider is deterministic and keeps no state.
+
+use super::*;
+
+/// The inputs of an AEAD encryption.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AEADInputs {
+    pub algorithm: AEADAlgorithm,
+    pub key: Term,
+    pub iv: Term,
+    pub aad: Term,
+    pub plain: Term,
+}
+
+/// Symbolic values.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Term {
+    /// Public bytes.
+    Bytes(Vec<u8>),
+    /// The public key `scalar * G`, for `G` the generator of the group.
+    DHPublic(DHGroup, Box<Term>),
+    /// The shared secret `a * (b * G)` of the scalars `a` and `b`,
+    /// stored in increasing order.
+    DHShared(DHGroup, Box<Term>, Box<Term>),
+    /// `scalar * element`, for an `element` that is not a public key.
+    DH(DHGroup, Box<Term>, Box<Term>),
+    /// The ciphertext of an AEAD encryption.
+    AEADCiphertext(Box<AEADInputs>),
+    /// The authentication tag of an AEAD encryption.
+    AEADTag(Box<AEADInputs>),
+    /// The hash of a term.
+    Hash(HashAlgorithm, Box<Term>),
+    /// The HMAC of a term, with a key.
+    HMAC(HMACAlgorithm, Box<Term>, Box<Term>),
+    /// The HKDF extraction of a salt and an input key material.
+    HKDFExtract(HKDFAlgorithm, Box<Term>, Box<Term>),
+    /// The HKDF expansion of a pseudo-random key and an info, with a
+    /// length.
+    HKDFExpand(HKDFAlgorithm, Box<Term>, Box<Term>, usize),
+    /// The verification key of a signing key.
+    VerificationKey(SignatureAlgorithm, Box<Term>),
+    /// The signature of a message with a signing key and randomness.
+    Signature(SignatureAlgorithm, Box<Term>, Box<Term>, Box<Term>),
+    /// The public key of a KEM private key.
+    KEMPublicKey(KEMAlgorithm, Box<Term>),
+    /// The shared secret encapsulated to a public key with randomness.
+    KEMSharedSecret(KEMAlgorithm, Box<Term>, Box<Term>),
+    /// The ciphertext encapsulating a shared secret to a public key
+    /// with randomness.
+    KEMCiphertext(KEMAlgorithm, Box<Term>, Box<Term>),
+}
+
+/// The symbolic crypto provider, computing on [`Term`]s.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Symbolic;
+
+impl Symbolic {
+    fn aead_inputs(
+        key: &AEADKey<Self>,
+        iv: &AEADIV<Self>,
+        aad: &Term,
+        plain: &Term,
+    ) -> Box<AEADInputs> {
+        Box::new(AEADInputs {
+            algorithm: key.algorithm(),
+            key: key.value().clone(),
+            iv: iv.value().clone(),
+            aad: aad.clone(),
+            plain: plain.clone(),
+        })
+    }
+}
+
+impl CryptoProvider for Symbolic {
+    type Value = Term;
+
+    fn value_from_bytes(bytes: &[u8]) -> Term {
+        Term::Bytes(bytes.to_vec())
+    }
+
+    fn dh_scalar_multiply(
+        &self,
+        group: DHGroup,
+        scalar: &DHScalar<Self>,
+        element: &DHElement<Self>,
+    ) -> ProtocolResult<Term> {
+        let a = Box::new(scalar.value().clone());
+        Ok(match element.value() {
+            Term::DHPublic(element_group, b) if *element_group == group => {
+                let (a, b) = if a <= *b {
+                    (a, b.clone())
+                } else {
+                    (b.clone(), a)
+                };
+                Term::DHShared(group, a, b)
+            }
+            element => Term::DH(group, a, Box::new(element.clone())),
+        })
+    }
+
+    fn dh_scalar_multiply_base(
+        &self,
+        group: DHGroup,
+        scalar: &DHScalar<Self>,
+    ) -> ProtocolResult<Term> {
+        Ok(Term::DHPublic(group, Box::new(scalar.value().clone())))
+    }
+
+    fn aead_encrypt(
+        &self,
+        key: &AEADKey<Self>,
+        iv: &AEADIV<Self>,
+        aad: &Term,
+        plain: &Term,
+    ) -> ProtocolResult<(Term, Term)> {
+        let inputs = Self::aead_inputs(key, iv, aad, plain);
+        Ok((Term::AEADCiphertext(inputs.clone()), Term::AEADTag(inputs)))
+    }
+
+    fn aead_decrypt(
+        &self,
+        key: &AEADKey<Self>,
+        iv: &AEADIV<Self>,
+        aad: &Term,
+        cip: &Term,
+        tag: &AEADTag<Self>,
+    ) -> ProtocolResult<Term> {
+        match cip {
+            Term::AEADCiphertext(inputs)
+                if *inputs == Self::aead_inputs(key, iv, aad, &inputs.plain)
+                    && *tag.value() == Term::AEADTag(inputs.clone()) =>
+            {
+                Ok(inputs.plain.clone())
+            }
+            _ => Err(ProtocolError::CryptoError),
+        }
+    }
+
+    fn hash(&self, algorithm: HashAlgorithm, input: &Term) -> ProtocolResult<Term> {
+        Ok(Term::Hash(algorithm, Box::new(input.clone())))
+    }
+
+    fn hmac(&self, algorithm: HMACAlgorithm, key: &Term, input: &Term) -> ProtocolResult<Term> {
+        Ok(Term::HMAC(
+            algorithm,
+            Box::new(key.clone()),
+            Box::new(input.clone()),
+        ))
+    }
+
+    fn hkdf_extract(
+        &self,
+        algorithm: HKDFAlgorithm,
+        salt: &Term,
+        ikm: &Term,
+    ) -> ProtocolResult<Term> {
+        Ok(Term::HKDFExtract(
+            algorithm,
+            Box::new(salt.clone()),
+            Box::new(ikm.clone()),
+        ))
+    }
+
+    fn hkdf_expand(
+        &self,
+        algorithm: HKDFAlgorithm,
+        prk: &Term,
+        info: &Term,
+        length: usize,
+    ) -> ProtocolResult<Term> {
+        Ok(Term::HKDFExpand(
+            algorithm,
+            Box::new(prk.clone()),
+            Box::new(info.clone()),
+            length,
+        ))
+    }
+
+    fn signature_key_gen(
+        &self,
+        algorithm: SignatureAlgorithm,
+        randomness: &Term,
+    ) -> ProtocolResult<(SigningKey<Self>, VerificationKey<Self>)> {
+        let key = SigningKey::from_value(algorithm, randomness.clone());
+        let verification_key = self.verification_key(&key)?;
+        Ok((key, verification_key))
+    }
+
+    fn verification_key(&self, key: &SigningKey<Self>) -> ProtocolResult<VerificationKey<Self>> {
+        Ok(VerificationKey::from_value(
+            key.algorithm(),
+            Term::VerificationKey(key.algorithm(), Box::new(key.value().clone())),
+        ))
+    }
+
+    fn sign(
+        &self,
+        key: &SigningKey<Self>,
+        message: &Term,
+        randomness: &Term,
+    ) -> ProtocolResult<Term> {
+        Ok(Term::Signature(
+            key.algorithm(),
+            Box::new(key.value().clone()),
+            Box::new(message.clone()),
+            Box::new(randomness.clone()),
+        ))
+    }
+
+    fn verify(
+        &self,
+        key: &VerificationKey<Self>,
+        message: &Term,
+        signature: &Term,
+    ) -> ProtocolResult<()> {
+        match signature {
+            Term::Signature(algorithm, signing_key, signed, _)
+                if *algorithm == key.algorithm()
+                    && **signed == *message
+                    && *key.value() == Term::VerificationKey(*algorithm, signing_key.clone()) =>
+            {
+                Ok(())
+            }
+            _ => Err(ProtocolError::CryptoError),
+        }
+    }
+
+    fn kem_key_gen(
+        &self,
+        algorithm: KEMAlgorithm,
+        randomness: &Term,
+    ) -> ProtocolResult<(KEMPrivateKey<Self>, KEMPublicKey<Self>)> {
+        let key = KEMPrivateKey::from_value(algorithm, randomness.clone());
+        let public_key = self.kem_public_key(&key)?;
+        Ok((key, public_key))
+    }
+
+    fn kem_public_key(&self, key: &KEMPrivateKey<Self>) -> ProtocolResult<KEMPublicKey<Self>> {
+        Ok(KEMPublicKey::from_value(
+            key.algorithm(),
+            Term::KEMPublicKey(key.algorithm(), Box::new(key.value().clone())),
+        ))
+    }
+
+    fn kem_encapsulate(
+        &self,
+        key: &KEMPublicKey<Self>,
+        randomness: &Term,
+    ) -> ProtocolResult<(Term, Term)> {
+        let public_key = Box::new(key.value().clone());
+        let randomness = Box::new(randomness.clone());
+        Ok((
+            Term::KEMSharedSecret(key.algorithm(), public_key.clone(), randomness.clone()),
+            Term::KEMCiphertext(key.algorithm(), public_key, randomness),
+        ))
+    }
+
+    /// Fails on ciphertexts that were not encapsulated to the public
+    /// key of `key`, where a real KEM may return an unrelated secret.
+    fn kem_decapsulate(
+        &self,
+        key: &KEMPrivateKey<Self>,
+        ciphertext: &Term,
+    ) -> ProtocolResult<Term> {
+        match ciphertext {
+            Term::KEMCiphertext(algorithm, public_key, randomness)
+                if *algorithm == key.algorithm()
+                    && *self.kem_public_key(key)?.value() == **public_key =>
+            {
+                Ok(Term::KEMSharedSecret(
+                    *algorithm,
+                    public_key.clone(),
+                    randomness.clone(),
+                ))
+            }
+            _ => Err(ProtocolError::CryptoError),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bytes(x: u8) -> Term {
+        Term::Bytes(vec![x])
+    }
+
+    #[test]
+    fn dh_commutes() {
+        let group = DHGroup::X25519;
+        let (a, b) = (DHScalar::from_bytes(&[1]), DHScalar::from_bytes(&[2]));
+        let a_pub = DHElement::from_value(Symbolic.dh_scalar_multiply_base(group, &a).unwrap());
+        let b_pub = DHElement::from_value(Symbolic.dh_scalar_multiply_base(group, &b).unwrap());
+        let ab = Symbolic.dh_scalar_multiply(group, &a, &b_pub).unwrap();
+        let ba = Symbolic.dh_scalar_multiply(group, &b, &a_pub).unwrap();
+        assert_eq!(ab, ba);
+        assert!(matches!(ab, Term::DHShared(..)));
+        // No equation across groups, or for elements that are not public keys.
+        let other = Symbolic
+            .dh_scalar_multiply(DHGroup::X448, &a, &b_pub)
+            .unwrap();
+        assert_ne!(other, ab);
+        let c = DHElement::<Symbolic>::from_bytes(&[3]);
+        assert_ne!(
+            Symbolic.dh_scalar_multiply(group, &a, &c).unwrap(),
+            Symbolic.dh_scalar_multiply(group, &b, &c).unwrap()
+        );
+    }
+
+    #[test]
+    fn aead_round_trip() {
+        let key =
+            AEADKey::<Symbolic>::from_bytes(AEADAlgorithm::Chacha20Poly1305, &[0; 32]).unwrap();
+        let iv = AEADIV::<Symbolic>::from_bytes(&[0; 12]).unwrap();
+        let (cip, tag) = Symbolic
+            .aead_encrypt(&key, &iv, &bytes(1), &bytes(2))
+            .unwrap();
+        let tag = AEADTag::from_value(tag);
+        assert_eq!(
+            Symbolic
+                .aead_decrypt(&key, &iv, &bytes(1), &cip, &tag)
+                .unwrap(),
+            bytes(2)
+        );
+    }
+
+    #[test]
+    fn aead_rejects_mismatches() {
+        let algorithm = AEADAlgorithm::Chacha20Poly1305;
+        let key = AEADKey::<Symbolic>::from_bytes(algorithm, &[0; 32]).unwrap();
+        let iv = AEADIV::<Symbolic>::from_bytes(&[0; 12]).unwrap();
+        let (cip, tag) = Symbolic
+            .aead_encrypt(&key, &iv, &bytes(1), &bytes(2))
+            .unwrap();
+        let tag = AEADTag::from_value(tag);
+        let other_key = AEADKey::<Symbolic>::from_bytes(algorithm, &[1; 32]).unwrap();
+        let other_iv = AEADIV::<Symbolic>::from_bytes(&[1; 12]).unwrap();
+        let other_tag = AEADTag::from_bytes(&[0; 16]).unwrap();
+        let rejected = |key: &AEADKey<Symbolic>,
+                        iv: &AEADIV<Symbolic>,
+                        aad: Term,
+                        cip: Term,
+                        tag: &AEADTag<Symbolic>| {
+            Symbolic.aead_decrypt(key, iv, &aad, &cip, tag).is_err()
+        };
+        assert!(rejected(&other_key, &iv, bytes(1), cip.clone(), &tag));
+        assert!(rejected(&key, &other_iv, bytes(1), cip.clone(), &tag));
+        assert!(rejected(&key, &iv, bytes(3), cip.clone(), &tag));
+        assert!(rejected(&key, &iv, bytes(1), bytes(2), &tag));
+        assert!(rejected(&key, &iv, bytes(1), cip, &other_tag));
+    }
+
+    #[test]
+    fn invalid_lengths() {
+        assert!(AEADKey::<Symbolic>::from_bytes(AEADAlgorithm::Aes128Gcm, &[0; 32]).is_err());
+        assert!(AEADIV::<Symbolic>::from_bytes(&[0; 11]).is_err());
+        assert!(AEADTag::<Symbolic>::from_bytes(&[0; 17]).is_err());
+    }
+
+    #[test]
+    fn hash_and_hmac() {
+        let hash = |input: Term| Symbolic.hash(HashAlgorithm::Sha256, &input).unwrap();
+        assert_eq!(hash(bytes(1)), hash(bytes(1)));
+        assert_ne!(hash(bytes(1)), hash(bytes(2)));
+        assert_ne!(
+            hash(bytes(1)),
+            Symbolic.hash(HashAlgorithm::Sha512, &bytes(1)).unwrap()
+        );
+        let hmac =
+            |key: Term, input: Term| Symbolic.hmac(HMACAlgorithm::Sha256, &key, &input).unwrap();
+        assert_eq!(hmac(bytes(0), bytes(1)), hmac(bytes(0), bytes(1)));
+        assert_ne!(hmac(bytes(0), bytes(1)), hmac(bytes(1), bytes(1)));
+        assert_ne!(hmac(bytes(0), bytes(1)), hash(bytes(1)));
+    }
+
+    #[test]
+    fn signature_round_trip() {
+        let algorithm = SignatureAlgorithm::Ed25519;
+        let (key, verification) = Symbolic.signature_key_gen(algorithm, &bytes(1)).unwrap();
+        let signature = Symbolic.sign(&key, &bytes(2), &bytes(3)).unwrap();
+        assert!(Symbolic
+            .verify(&verification, &bytes(2), &signature)
+            .is_ok());
+        assert!(Symbolic
+            .verify(&verification, &bytes(4), &signature)
+            .is_err());
+        let (_, other) = Symbolic.signature_key_gen(algorithm, &bytes(5)).unwrap();
+        assert!(Symbolic.verify(&other, &bytes(2), &signature).is_err());
+    }
+
+    #[test]
+    fn kem_round_trip() {
+        for algorithm in [KEMAlgorithm::X25519, KEMAlgorithm::MlKem768] {
+            let (key, public_key) = Symbolic.kem_key_gen(algorithm, &bytes(1)).unwrap();
+            let (shared_secret, ciphertext) =
+                Symbolic.kem_encapsulate(&public_key, &bytes(2)).unwrap();
+            assert_eq!(
+                Symbolic.kem_decapsulate(&key, &ciphertext).unwrap(),
+                shared_secret
+            );
+            let (other, _) = Symbolic.kem_key_gen(algorithm, &bytes(3)).unwrap();
+            assert!(Symbolic.kem_decapsulate(&other, &ciphertext).is_err());
+            assert!(Symbolic.kem_decapsulate(&key, &bytes(4)).is_err());
+        }
+        // The same randomness for another algorithm gives another key
+        let (x25519, _) = Symbolic
+            .kem_key_gen(KEMAlgorithm::X25519, &bytes(1))
+            .unwrap();
+        let (_, ml_kem) = Symbolic
+            .kem_key_gen(KEMAlgorithm::MlKem768, &bytes(1))
+            .unwrap();
+        let (_, ciphertext) = Symbolic.kem_encapsulate(&ml_kem, &bytes(2)).unwrap();
+        assert!(Symbolic.kem_decapsulate(&x25519, &ciphertext).is_err());
+    }
+}
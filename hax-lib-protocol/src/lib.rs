@@ -6,7 +6,9 @@
 //! use in protocol specifications.
 
 pub mod crypto;
+pub mod runner;
 pub mod state_machine;
+pub mod wire;
 
 /// A protocol error type.
 #[derive(Debug)]
@@ -0,0 +1,404 @@
+//! This module provides a driver to execute the state machines of two
+//! protocol parties against each other, for instance in integration
+//! tests.
+//!
+//! A [`Runner`] acts as an in-memory channel between an initiator and
+//! a responder: each [`Runner::exchange`] makes a state write a
+//! message and another state read it. The runner records the
+//! messages in a [`Transcript`], together with the names of the
+//! states involved, that can be compared against ProVerif traces.
+//! Messages can also be tampered with ([`Runner::exchange_tampered`])
+//! or injected ([`Runner::inject`]), e.g. to replay a message obtained
+//! with [`Runner::sent`].
+//!
+//! # Example:
+//!
+//! ```rust
+//! use hax_lib_protocol::runner::{Party, Runner};
+//! use hax_lib_protocol::state_machine::{ReadState, WriteState};
+//! use hax_lib_protocol::{ProtocolError, ProtocolResult};
+//!
+//! struct Ping(u8);
+//! struct Done;
+//! struct Waiting;
+//!
+//! impl WriteState for Ping {
+//!     type NextState = Done;
+//!     type Message = u8;
+//!     fn write(self) -> ProtocolResult<(Done, u8)> {
+//!         Ok((Done, self.0))
+//!     }
+//! }
+//!
+//! impl ReadState<Done> for Waiting {
+//!     type Message = u8;
+//!     fn read(self, msg: u8) -> ProtocolResult<Done> {
+//!         if msg == 42 { Ok(Done) } else { Err(ProtocolError::InvalidMessage) }
+//!     }
+//! }
+//!
+//! let mut runner = Runner::new();
+//! let (Done, Done) = runner.exchange(Party::Initiator, Ping(42), Waiting).unwrap();
+//! let tampered = runner.exchange_tampered(Party::Initiator, Ping(42), Waiting, |m| m + 1);
+//! assert!(tampered.is_err());
+//! let replayed = runner.sent::<u8>(0).unwrap();
+//! let Done = runner.inject(Party::Initiator, Waiting, replayed).unwrap();
+//! assert_eq!(runner.transcript().entries().len(), 3);
+//! ```
+
+use crate::state_machine::{ReadState, WriteState};
+use crate::ProtocolResult;
+use std::any::{type_name, Any};
+use std::fmt::{self, Debug, Display};
+
+/// The two parties of a protocol.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Party {
+    Initiator,
+    Responder,
+}
+
+impl Party {
+    /// The other party.
+    pub fn peer(self) -> Self {
+        match self {
+            Party::Initiator => Party::Responder,
+            Party::Responder => Party::Initiator,
+        }
+    }
+}
+
+/// How a message reached its reader.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Delivery {
+    /// As written by the sender.
+    Honest,
+    /// Modified after being written by the sender.
+    Tampered,
+    /// Not written by the sender at all.
+    Injected,
+}
+
+/// A transition of a party from one state type to another.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Transition {
+    pub from: &'static str,
+    pub to: &'static str,
+}
+
+impl Transition {
+    fn of<From, To>() -> Self {
+        Self {
+            from: type_name::<From>(),
+            to: type_name::<To>(),
+        }
+    }
+}
+
+/// A message of a [`Transcript`].
+#[derive(Clone, Debug)]
+pub struct TranscriptEntry {
+    /// The party the message is from.
+    pub sender: Party,
+    /// The transition of the sender when writing the message, `None`
+    /// for injected messages.
+    pub sender_transition: Option<Transition>,
+    /// The transition of the receiver when reading the message, `None`
+    /// if the receiver rejected the message.
+    pub receiver_transition: Option<Transition>,
+    /// The message, as delivered.
+    pub message: String,
+    pub delivery: Delivery,
+}
+
+impl Display for TranscriptEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let transition = |transition: Option<Transition>, default: &str| {
+            transition.map_or(default.to_string(), |Transition { from, to }| {
+                format!("{from} -> {to}")
+            })
+        };
+        write!(
+            f,
+            "{:?} [{}] --{:?} {}--> {:?} [{}]",
+            self.sender,
+            transition(self.sender_transition, "injected"),
+            self.delivery,
+            self.message,
+            self.sender.peer(),
+            transition(self.receiver_transition, "rejected"),
+        )
+    }
+}
+
+/// The messages exchanged during a run, in order.
+#[derive(Clone, Debug, Default)]
+pub struct Transcript {
+    entries: Vec<TranscriptEntry>,
+}
+
+impl Transcript {
+    pub fn entries(&self) -> &[TranscriptEntry] {
+        &self.entries
+    }
+}
+
+impl Display for Transcript {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            writeln!(f, "{entry}")?;
+        }
+        Ok(())
+    }
+}
+
+/// An in-memory channel between an initiator and a responder, which
+/// records a [`Transcript`].
+#[derive(Default)]
+pub struct Runner {
+    transcript: Transcript,
+    /// The messages of the transcript.
+    messages: Vec<Box<dyn Any>>,
+}
+
+impl Runner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn transcript(&self) -> &Transcript {
+        &self.transcript
+    }
+
+    /// The `index`-th message of the transcript, if it has type `M`.
+    pub fn sent<M: Clone + 'static>(&self, index: usize) -> Option<M> {
+        self.messages.get(index)?.downcast_ref::<M>().cloned()
+    }
+
+    /// Makes `writer`, a state of `sender`, write a message, and
+    /// `reader`, a state of its peer, read it. Returns the next states
+    /// of the writer and of the reader.
+    pub fn exchange<W, R, N>(
+        &mut self,
+        sender: Party,
+        writer: W,
+        reader: R,
+    ) -> ProtocolResult<(W::NextState, N)>
+    where
+        W: WriteState,
+        W::Message: Clone + Debug + 'static,
+        R: ReadState<N, Message = W::Message>,
+    {
+        let (next, message) = writer.write()?;
+        let transition = Transition::of::<W, W::NextState>();
+        let next_reader =
+            self.deliver(sender, Some(transition), Delivery::Honest, reader, message)?;
+        Ok((next, next_reader))
+    }
+
+    /// Like [`Runner::exchange`], but the message is modified by
+    /// `tamper` before being read.
+    pub fn exchange_tampered<W, R, N>(
+        &mut self,
+        sender: Party,
+        writer: W,
+        reader: R,
+        tamper: impl FnOnce(W::Message) -> W::Message,
+    ) -> ProtocolResult<(W::NextState, N)>
+    where
+        W: WriteState,
+        W::Message: Clone + Debug + 'static,
+        R: ReadState<N, Message = W::Message>,
+    {
+        let (next, message) = writer.write()?;
+        let transition = Transition::of::<W, W::NextState>();
+        let next_reader = self.deliver(
+            sender,
+            Some(transition),
+            Delivery::Tampered,
+            reader,
+            tamper(message),
+        )?;
+        Ok((next, next_reader))
+    }
+
+    /// Makes `reader` read `message`, as if sent by `sender`. Returns
+    /// the next state of the reader.
+    pub fn inject<R, N>(
+        &mut self,
+        sender: Party,
+        reader: R,
+        message: R::Message,
+    ) -> ProtocolResult<N>
+    where
+        R: ReadState<N>,
+        R::Message: Clone + Debug + 'static,
+    {
+        self.deliver(sender, None, Delivery::Injected, reader, message)
+    }
+
+    fn deliver<R, N>(
+        &mut self,
+        sender: Party,
+        sender_transition: Option<Transition>,
+        delivery: Delivery,
+        reader: R,
+        message: R::Message,
+    ) -> ProtocolResult<N>
+    where
+        R: ReadState<N>,
+        R::Message: Clone + Debug + 'static,
+    {
+        let rendered = format!("{message:?}");
+        self.messages.push(Box::new(message.clone()));
+        let result = reader.read(message);
+        self.transcript.entries.push(TranscriptEntry {
+            sender,
+            sender_transition,
+            receiver_transition: result.is_ok().then(Transition::of::<R, N>),
+            message: rendered,
+            delivery,
+        });
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ProtocolError;
+
+    /// A party that sends a value, and one that accepts values below
+    /// 10 and answers with its double.
+    struct Sender(u8);
+    struct Sent;
+    struct Receiver;
+    struct Received(u8);
+    struct Answered;
+
+    impl WriteState for Sender {
+        type NextState = Sent;
+        type Message = u8;
+        fn write(self) -> ProtocolResult<(Sent, u8)> {
+            if self.0 == u8::MAX {
+                Err(ProtocolError::CryptoError)
+            } else {
+                Ok((Sent, self.0))
+            }
+        }
+    }
+
+    impl ReadState<Received> for Receiver {
+        type Message = u8;
+        fn read(self, msg: u8) -> ProtocolResult<Received> {
+            if msg < 10 {
+                Ok(Received(msg))
+            } else {
+                Err(ProtocolError::InvalidMessage)
+            }
+        }
+    }
+
+    impl WriteState for Received {
+        type NextState = Answered;
+        type Message = u16;
+        fn write(self) -> ProtocolResult<(Answered, u16)> {
+            Ok((Answered, u16::from(self.0) * 2))
+        }
+    }
+
+    impl ReadState<Received> for Sent {
+        type Message = u16;
+        fn read(self, msg: u16) -> ProtocolResult<Received> {
+            u8::try_from(msg)
+                .map(Received)
+                .map_err(|_| ProtocolError::InvalidMessage)
+        }
+    }
+
+    #[test]
+    fn honest_run() {
+        let mut runner = Runner::new();
+        let (sent, received) = runner
+            .exchange(Party::Initiator, Sender(3), Receiver)
+            .unwrap();
+        let (Answered, Received(answer)) =
+            runner.exchange(Party::Responder, received, sent).unwrap();
+        assert_eq!(answer, 6);
+        let entries = runner.transcript().entries();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].sender, Party::Initiator);
+        assert_eq!(entries[1].sender, Party::Responder);
+        assert_eq!(entries[1].message, "6");
+        assert!(entries.iter().all(|e| e.delivery == Delivery::Honest));
+        assert_eq!(runner.sent::<u8>(0), Some(3));
+        assert_eq!(runner.sent::<u16>(1), Some(6));
+        // Wrong type or index.
+        assert_eq!(runner.sent::<u16>(0), None);
+        assert_eq!(runner.sent::<u8>(2), None);
+    }
+
+    #[test]
+    fn tampered_message() {
+        let mut runner = Runner::new();
+        let accepted = runner.exchange_tampered(Party::Initiator, Sender(3), Receiver, |m| m + 1);
+        assert!(matches!(accepted, Ok((Sent, Received(4)))));
+        let rejected = runner.exchange_tampered(Party::Initiator, Sender(3), Receiver, |m| m + 10);
+        assert!(matches!(rejected, Err(ProtocolError::InvalidMessage)));
+        let entries = runner.transcript().entries();
+        assert!(entries.iter().all(|e| e.delivery == Delivery::Tampered));
+        assert!(entries[0].receiver_transition.is_some());
+        assert_eq!(entries[1].message, "13");
+        assert_eq!(entries[1].receiver_transition, None);
+        assert_eq!(runner.sent::<u8>(1), Some(13));
+    }
+
+    #[test]
+    fn injected_and_replayed_messages() {
+        let mut runner = Runner::new();
+        runner
+            .exchange(Party::Initiator, Sender(3), Receiver)
+            .unwrap();
+        let replayed = runner.sent::<u8>(0).unwrap();
+        let Received(value) = runner.inject(Party::Initiator, Receiver, replayed).unwrap();
+        assert_eq!(value, 3);
+        let rejected: ProtocolResult<Received> = runner.inject(Party::Initiator, Receiver, 42);
+        assert!(rejected.is_err());
+        let entries = runner.transcript().entries();
+        assert_eq!(entries.len(), 3);
+        for entry in &entries[1..] {
+            assert_eq!(entry.delivery, Delivery::Injected);
+            assert_eq!(entry.sender_transition, None);
+        }
+        assert!(entries[1].receiver_transition.is_some());
+        assert_eq!(entries[2].receiver_transition, None);
+    }
+
+    #[test]
+    fn failed_write_is_not_recorded() {
+        let mut runner = Runner::new();
+        let result = runner.exchange(Party::Initiator, Sender(u8::MAX), Receiver);
+        assert!(matches!(result, Err(ProtocolError::CryptoError)));
+        assert!(runner.transcript().entries().is_empty());
+        assert_eq!(runner.sent::<u8>(0), None);
+    }
+
+    #[test]
+    fn transcript_rendering() {
+        let mut runner = Runner::new();
+        runner
+            .exchange(Party::Initiator, Sender(3), Receiver)
+            .unwrap();
+        let _ = runner.exchange_tampered(Party::Initiator, Sender(3), Receiver, |m| m + 10);
+        let _ = runner.inject(Party::Responder, Sent, 300u16);
+        let name = type_name::<Sender>().trim_end_matches("Sender");
+        let expected = [
+            "Initiator [{m}Sender -> {m}Sent] --Honest 3--> Responder [{m}Receiver -> {m}Received]",
+            "Initiator [{m}Sender -> {m}Sent] --Tampered 13--> Responder [rejected]",
+            "Responder [injected] --Injected 300--> Initiator [rejected]",
+        ]
+        .map(|line| format!("{}\n", line.replace("{m}", name)))
+        .concat();
+        assert_eq!(runner.transcript().to_string(), expected);
+    }
+}
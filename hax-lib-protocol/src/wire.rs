@@ -0,0 +1,248 @@
+//! This module provides the binary wire format of protocol messages.
+//!
+//! Messages are usually not implemented by hand, but derived with
+//! `#[derive(hax_lib_protocol_macros::Message)]`: every field of a
+//! message is encoded with its length as a 4-byte big-endian prefix,
+//! and the fields of an enum variant are preceded by the index of
+//! the variant, as one byte. Field types implement [`Message`]
+//! themselves, so that messages can be nested.
+//!
+//! Besides the [`Message`] implementation, the derive generates a
+//! free encoder and decoder for the message type, which are extracted
+//! to ProVerif as a constructor and its destructor.
+
+use crate::{ProtocolError, ProtocolResult};
+
+/// A type with a binary wire format.
+pub trait Message: Sized {
+    /// Appends the encoding of `self` to `out`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a field of `self` is encoded on 4GiB or more, since
+    /// its length does not fit in its prefix.
+    fn encode_to(&self, out: &mut Vec<u8>);
+
+    /// Decodes a value from exactly the bytes `bytes`.
+    fn decode(bytes: &[u8]) -> ProtocolResult<Self>;
+
+    /// The encoding of `self`.
+    ///
+    /// # Panics
+    ///
+    /// Panics as [`Message::encode_to`] does.
+    fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.encode_to(&mut out);
+        out
+    }
+}
+
+/// Appends the length-prefixed encoding of the field `value` to `out`.
+///
+/// # Panics
+///
+/// Panics if `value` is encoded on 4GiB or more.
+pub fn encode_field<T: Message>(value: &T, out: &mut Vec<u8>) {
+    let encoded = value.encode();
+    let length = u32::try_from(encoded.len()).expect("message fields are shorter than 4GiB");
+    out.extend_from_slice(&length.to_be_bytes());
+    out.extend_from_slice(&encoded);
+}
+
+/// A cursor over the bytes of an encoded message.
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    fn take(&mut self, length: usize) -> ProtocolResult<&'a [u8]> {
+        if self.bytes.len() < length {
+            return Err(ProtocolError::InvalidMessage);
+        }
+        let (taken, rest) = self.bytes.split_at(length);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    /// Reads the index of an enum variant.
+    pub fn tag(&mut self) -> ProtocolResult<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    /// Reads and decodes a length-prefixed field.
+    pub fn field<T: Message>(&mut self) -> ProtocolResult<T> {
+        let length = u32::decode(self.take(4)?)?;
+        let length = usize::try_from(length).map_err(|_| ProtocolError::InvalidMessage)?;
+        T::decode(self.take(length)?)
+    }
+
+    /// Checks that the whole message was read.
+    pub fn finish(self) -> ProtocolResult<()> {
+        if self.bytes.is_empty() {
+            Ok(())
+        } else {
+            Err(ProtocolError::InvalidMessage)
+        }
+    }
+}
+
+macro_rules! impl_message_for_integers {
+    ($($t:ty),*) => {
+        $(
+            impl Message for $t {
+                fn encode_to(&self, out: &mut Vec<u8>) {
+                    out.extend_from_slice(&self.to_be_bytes());
+                }
+
+                fn decode(bytes: &[u8]) -> ProtocolResult<Self> {
+                    bytes
+                        .try_into()
+                        .map(<$t>::from_be_bytes)
+                        .map_err(|_| ProtocolError::InvalidMessage)
+                }
+            }
+        )*
+    };
+}
+
+impl_message_for_integers!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl Message for bool {
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        out.push(u8::from(*self));
+    }
+
+    fn decode(bytes: &[u8]) -> ProtocolResult<Self> {
+        match bytes {
+            [0] => Ok(false),
+            [1] => Ok(true),
+            _ => Err(ProtocolError::InvalidMessage),
+        }
+    }
+}
+
+impl Message for () {
+    fn encode_to(&self, _out: &mut Vec<u8>) {}
+
+    fn decode(bytes: &[u8]) -> ProtocolResult<Self> {
+        Reader::new(bytes).finish()
+    }
+}
+
+/// Byte strings are encoded as is: their length is given by the
+/// prefix of the field.
+impl Message for Vec<u8> {
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(self);
+    }
+
+    fn decode(bytes: &[u8]) -> ProtocolResult<Self> {
+        Ok(bytes.to_vec())
+    }
+}
+
+impl<const N: usize> Message for [u8; N] {
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(self);
+    }
+
+    fn decode(bytes: &[u8]) -> ProtocolResult<Self> {
+        bytes.try_into().map_err(|_| ProtocolError::InvalidMessage)
+    }
+}
+
+impl<T: Message> Message for Option<T> {
+    fn encode_to(&self, out: &mut Vec<u8>) {
+        match self {
+            None => out.push(0),
+            Some(value) => {
+                out.push(1);
+                encode_field(value, out);
+            }
+        }
+    }
+
+    fn decode(bytes: &[u8]) -> ProtocolResult<Self> {
+        let mut reader = Reader::new(bytes);
+        let value = match reader.tag()? {
+            0 => None,
+            1 => Some(reader.field()?),
+            _ => return Err(ProtocolError::InvalidMessage),
+        };
+        reader.finish()?;
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip<T: Message + PartialEq + std::fmt::Debug>(value: T) {
+        assert_eq!(T::decode(&value.encode()).unwrap(), value);
+    }
+
+    #[test]
+    fn primitives_round_trip() {
+        round_trip(0x1234u16);
+        round_trip(-5i64);
+        round_trip(u128::MAX);
+        round_trip(true);
+        round_trip(());
+        round_trip(vec![1u8, 2, 3]);
+        round_trip([7u8; 4]);
+        round_trip(Some(vec![1u8]));
+        round_trip(None::<u8>);
+        assert_eq!(0x1234u16.encode(), [0x12, 0x34]);
+        assert_eq!(Some(1u8).encode(), [1, 0, 0, 0, 1, 1]);
+    }
+
+    #[test]
+    fn invalid_primitives() {
+        assert!(u32::decode(&[0; 3]).is_err());
+        assert!(u32::decode(&[0; 5]).is_err());
+        assert!(bool::decode(&[2]).is_err());
+        assert!(<()>::decode(&[0]).is_err());
+        assert!(<[u8; 4]>::decode(&[0; 3]).is_err());
+    }
+
+    #[test]
+    fn field_prefix() {
+        let mut out = Vec::new();
+        encode_field(&vec![9u8; 3], &mut out);
+        assert_eq!(out, [0, 0, 0, 3, 9, 9, 9]);
+        let mut reader = Reader::new(&out);
+        assert_eq!(reader.field::<Vec<u8>>().unwrap(), [9; 3]);
+        assert!(reader.finish().is_ok());
+    }
+
+    #[test]
+    fn truncated_field() {
+        // The prefix announces more bytes than available.
+        assert!(Reader::new(&[0, 0, 0, 4, 9, 9, 9])
+            .field::<Vec<u8>>()
+            .is_err());
+        // The prefix itself is truncated.
+        assert!(Reader::new(&[0, 0, 0]).field::<Vec<u8>>().is_err());
+        assert!(Option::<u8>::decode(&[1, 0, 0, 0, 1]).is_err());
+    }
+
+    #[test]
+    fn trailing_bytes() {
+        let mut reader = Reader::new(&[0, 0, 0, 1, 9, 0]);
+        assert_eq!(reader.field::<u8>().unwrap(), 9);
+        assert!(reader.finish().is_err());
+        assert!(Option::<u8>::decode(&[0, 0]).is_err());
+    }
+
+    #[test]
+    fn bad_tag() {
+        assert!(Option::<u8>::decode(&[2, 0, 0, 0, 1, 9]).is_err());
+        assert!(Reader::new(&[]).tag().is_err());
+    }
+}
@@ -0,0 +1,94 @@
+//! Tests of the wire format derived by
+//! `#[derive(hax_lib_protocol_macros::Message)]`.
+
+use hax_lib_protocol::wire::Message;
+
+#[derive(Debug, PartialEq, hax_lib_protocol_macros::Message)]
+pub struct Handshake {
+    version: u8,
+    key: Vec<u8>,
+    id: Option<[u8; 2]>,
+}
+
+#[derive(Debug, PartialEq, hax_lib_protocol_macros::Message)]
+pub enum WireMessage {
+    Hello,
+    Data(u16, Vec<u8>),
+    Handshake { handshake: Handshake, last: bool },
+}
+
+fn handshake() -> Handshake {
+    Handshake {
+        version: 1,
+        key: vec![0xaa, 0xbb],
+        id: Some([3, 4]),
+    }
+}
+
+#[test]
+fn round_trip() {
+    let messages = [
+        WireMessage::Hello,
+        WireMessage::Data(7, vec![]),
+        WireMessage::Handshake {
+            handshake: handshake(),
+            last: true,
+        },
+    ];
+    for message in messages {
+        assert_eq!(
+            decode_wire_message(&encode_wire_message(&message)).unwrap(),
+            message
+        );
+    }
+    assert_eq!(
+        decode_handshake(&encode_handshake(&handshake())).unwrap(),
+        handshake()
+    );
+}
+
+#[test]
+fn encoding() {
+    assert_eq!(WireMessage::Hello.encode(), [0]);
+    assert_eq!(
+        WireMessage::Data(7, vec![9]).encode(),
+        [1, 0, 0, 0, 2, 0, 7, 0, 0, 0, 1, 9]
+    );
+    assert_eq!(
+        handshake().encode(),
+        [0, 0, 0, 1, 1, 0, 0, 0, 2, 0xaa, 0xbb, 0, 0, 0, 7, 1, 0, 0, 0, 2, 3, 4]
+    );
+}
+
+#[test]
+fn truncated_field() {
+    let encoded = encode_wire_message(&WireMessage::Data(7, vec![9, 9]));
+    for length in 1..encoded.len() {
+        assert!(
+            decode_wire_message(&encoded[..length]).is_err(),
+            "decoded a prefix of length {length}"
+        );
+    }
+    // A length prefix larger than the message.
+    assert!(decode_wire_message(&[1, 0, 0, 0, 2, 0, 7, 0, 0, 0, 9, 9]).is_err());
+    // A field of the wrong length for its type.
+    assert!(decode_wire_message(&[1, 0, 0, 0, 1, 7, 0, 0, 0, 0]).is_err());
+}
+
+#[test]
+fn trailing_bytes() {
+    let mut encoded = encode_handshake(&handshake());
+    encoded.push(0);
+    assert!(decode_handshake(&encoded).is_err());
+    assert!(decode_wire_message(&[0, 0]).is_err());
+}
+
+#[test]
+fn bad_tag() {
+    assert!(decode_wire_message(&[3]).is_err());
+    assert!(decode_wire_message(&[]).is_err());
+    let mut encoded = encode_handshake(&handshake());
+    // The tag of the `id` option.
+    encoded[15] = 2;
+    assert!(decode_handshake(&encoded).is_err());
+}
@@ -1,4 +1,10 @@
+mod frame;
+mod ghost;
 mod hax_paths;
+mod loop_checks;
+mod property_tests;
+mod pure;
+mod refinement;
 
 use hax_paths::*;
 use proc_macro::{TokenStream, TokenTree};
@@ -21,6 +27,7 @@ identity_proc_macro_attribute!(
     fstar_verification_status,
     include,
     exclude,
+    modeled_by,
     requires,
     ensures,
     decreases,
@@ -33,7 +40,6 @@ identity_proc_macro_attribute!(
     opaque,
     opaque_type,
     transparent,
-    refinement_type,
     fstar_replace,
     coq_replace,
     proverif_replace,
@@ -63,6 +69,38 @@ pub fn proverif_expr(_payload: TokenStream) -> TokenStream {
     quote! { () }.into()
 }
 
+#[proc_macro_attribute]
+pub fn refinement_type(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(item as ItemStruct);
+    let args = parse_macro_input!(attr as refinement::RefinementTypeArgs);
+    let inner_ty = match refinement::newtype_inner_type(&item) {
+        Ok(inner_ty) => inner_ty,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let runtime_impls = refinement::runtime_impls(&item, inner_ty, &args);
+    quote! {
+        #item
+        #runtime_impls
+    }
+    .into()
+}
+
+#[proc_macro_attribute]
+pub fn invariant(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(item as Item);
+    let args = parse_macro_input!(attr as refinement::RefinementTypeArgs);
+    let (ident, generics) = match refinement::invariant_target(&item) {
+        Ok(target) => target,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let invariant_impl = refinement::invariant_impl(ident, generics, &args);
+    quote! {
+        #item
+        #invariant_impl
+    }
+    .into()
+}
+
 #[proc_macro_attribute]
 pub fn lemma(_attr: TokenStream, _item: TokenStream) -> TokenStream {
     quote! {}.into()
@@ -101,9 +139,47 @@ pub fn proverif_prop_expr(_payload: TokenStream) -> TokenStream {
     quote! {::hax_lib::Prop::from_bool(true)}.into()
 }
 
+#[proc_macro_attribute]
+pub fn modifies(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let places = parse_macro_input!(attr as frame::Places);
+    // Functions with no body, in traits, have nothing to check.
+    let Ok(fun) = parse::<ImplItemFn>(item.clone()) else {
+        return item;
+    };
+    if !cfg!(feature = "runtime-contracts") {
+        return item;
+    }
+    match frame::runtime_check(&fun, places) {
+        Ok(checked) => quote! {#checked}.into(),
+        Err(error) => error.into_compile_error().into(),
+    }
+}
+
+#[proc_macro_attribute]
+pub fn pure(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let _ = parse_macro_input!(attr as parse::Nothing);
+    match pure::check_item(item.clone().into()) {
+        Ok(()) => item,
+        Err(error) => error.into_compile_error().into(),
+    }
+}
+
+/// Makes the `modifies` attributes of a function refer to
+/// `::hax_lib::modifies`, so that they are checked at run time.
+fn qualify_modifies(attrs: &mut [Attribute]) {
+    for attr in attrs {
+        if let Meta::List(ml) = &mut attr.meta {
+            if let Ok(Some(_)) = expects_modifies(&ml.path) {
+                ml.path = parse_quote! {::hax_lib::modifies};
+            }
+        }
+    }
+}
+
 fn not_hax_attribute(attr: &syn::Attribute) -> bool {
     if let Meta::List(ml) = &attr.meta {
-        !matches!(expects_path_decoration(&ml.path), Ok(Some(_)))
+        !(matches!(expects_path_decoration(&ml.path), Ok(Some(_)))
+            || matches!(expects_modeled_by(&ml.path), Ok(Some(_))))
     } else {
         true
     }
@@ -122,14 +198,38 @@ fn not_field_attribute(attr: &syn::Attribute) -> bool {
 pub fn attributes(_attr: TokenStream, item: TokenStream) -> TokenStream {
     let item: Item = parse_macro_input!(item);
 
-    struct AttrVisitor;
+    struct AttrVisitor {
+        ghosts: ghost::Ghosts,
+        errors: Vec<syn::Error>,
+    }
 
     use syn::visit_mut;
     impl VisitMut for AttrVisitor {
+        fn visit_item_fn_mut(&mut self, item: &mut ItemFn) {
+            let ghost = ghost::take_ghost_attr(&mut item.attrs);
+            self.ghosts
+                .function(ghost, &mut item.sig, Some(&mut item.block));
+        }
+        fn visit_impl_item_fn_mut(&mut self, item: &mut ImplItemFn) {
+            let ghost = ghost::take_ghost_attr(&mut item.attrs);
+            self.ghosts
+                .function(ghost, &mut item.sig, Some(&mut item.block));
+        }
+        fn visit_trait_item_fn_mut(&mut self, item: &mut TraitItemFn) {
+            let ghost = ghost::take_ghost_attr(&mut item.attrs);
+            self.ghosts
+                .function(ghost, &mut item.sig, item.default.as_mut());
+        }
         fn visit_item_trait_mut(&mut self, item: &mut ItemTrait) {
             for ti in item.items.iter_mut() {
                 if let TraitItem::Fn(fun) = ti {
-                    fun.attrs.retain(not_hax_attribute)
+                    if pure::take_pure_attr(&mut fun.attrs) {
+                        if let Err(error) = pure::check_item(quote! {#fun}) {
+                            self.errors.push(error);
+                        }
+                    }
+                    fun.attrs.retain(not_hax_attribute);
+                    qualify_modifies(&mut fun.attrs);
                 }
             }
             visit_mut::visit_item_trait_mut(self, item);
@@ -138,7 +238,13 @@ pub fn attributes(_attr: TokenStream, item: TokenStream) -> TokenStream {
         fn visit_item_impl_mut(&mut self, item: &mut ItemImpl) {
             for ii in item.items.iter_mut() {
                 if let ImplItem::Fn(fun) = ii {
-                    fun.attrs.retain(not_hax_attribute)
+                    if pure::take_pure_attr(&mut fun.attrs) {
+                        if let Err(error) = pure::check_item(quote! {#fun}) {
+                            self.errors.push(error);
+                        }
+                    }
+                    fun.attrs.retain(not_hax_attribute);
+                    qualify_modifies(&mut fun.attrs);
                 }
             }
             visit_mut::visit_item_impl_mut(self, item);
@@ -157,10 +263,16 @@ pub fn attributes(_attr: TokenStream, item: TokenStream) -> TokenStream {
         }
     }
 
+    let mut v = AttrVisitor {
+        ghosts: ghost::Ghosts::new(true, ghost::ghost_fns(&item)),
+        errors: vec![],
+    };
     let mut item = item;
-    AttrVisitor.visit_item_mut(&mut item);
+    v.visit_item_mut(&mut item);
+    let errors = v.ghosts.errors.map(|errors| errors.into_compile_error());
+    let purity_errors = v.errors.into_iter().map(|error| error.into_compile_error());
 
-    quote! { #item }.into()
+    quote! { #errors #(#purity_errors)* #item }.into()
 }
 
 #[proc_macro]
@@ -174,7 +286,9 @@ pub fn int(payload: TokenStream) -> TokenStream {
         return quote! { ::std::compile_error!("Expected exactly one numeric literal") }.into();
     };
     let lit: proc_macro2::TokenStream = TokenStream::from(lit.clone()).into();
-    quote! {::hax_lib::int::Int(#lit)}.into()
+    // Only the `Int`s of `runtime-contracts` builds are signed
+    let sign = (negative && cfg!(feature = "runtime-contracts")).then(|| quote! {-});
+    quote! {::hax_lib::int::Int(#sign #lit)}.into()
 }
 
 #[proc_macro_attribute]
@@ -187,6 +301,55 @@ pub fn trait_fn_decoration(_attr: TokenStream, _item: TokenStream) -> TokenStrea
     quote! { ::std::compile_error!("`trait_fn_decoration` is an internal macro and should never be used directly.") }.into()
 }
 
+#[proc_macro_attribute]
+pub fn check_loops(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let _ = parse_macro_input!(attr as parse::Nothing);
+    let mut item = parse_macro_input!(item as ItemFn);
+    let mut visitor = loop_checks::CheckLoops::default();
+    visitor.visit_block_mut(&mut item.block);
+    let errors = visitor.errors.map(|errors| errors.into_compile_error());
+    quote! {
+        #errors
+        #item
+    }
+    .into()
+}
+
+#[proc_macro_attribute]
+pub fn ghost(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let _ = parse_macro_input!(attr as parse::Nothing);
+    let mut item = parse_macro_input!(item as ItemFn);
+    // The calls to the function in its own body are ghost code.
+    let ghost_fns = [item.sig.ident.clone()].into_iter().collect();
+    let mut ghosts = ghost::Ghosts::new(true, ghost_fns);
+    ghosts.function(true, &mut item.sig, Some(&mut item.block));
+    let errors = ghosts.errors.map(|errors| errors.into_compile_error());
+    quote! {
+        #errors
+        #item
+    }
+    .into()
+}
+
+#[proc_macro]
+pub fn ghost_expr(payload: TokenStream) -> TokenStream {
+    let payload: proc_macro2::TokenStream = payload.into();
+    quote! {::hax_lib::Ghost::erased(|| { #payload })}.into()
+}
+
+#[proc_macro_attribute]
+pub fn property_test(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr as property_tests::PropertyTestArgs);
+    let fun = parse_macro_input!(item as ItemFn);
+    let test = property_tests::property_test(args, &fun)
+        .unwrap_or_else(|error| error.into_compile_error());
+    quote! {
+        #fun
+        #test
+    }
+    .into()
+}
+
 #[proc_macro]
 pub fn loop_invariant(_predicate: TokenStream) -> TokenStream {
     quote! {}.into()
@@ -0,0 +1,392 @@
+//! Frame conditions of the `modifies` attribute, shared by its hax
+//! and non-hax versions: the postcondition stating which parts of the
+//! `&mut` inputs of a function are left untouched, and its run-time
+//! check.
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::*;
+
+/// A place that a function may modify: an input, some of its
+/// (nested) fields, or some of its indices.
+struct Place {
+    input: Ident,
+    kind: PlaceKind,
+}
+
+enum PlaceKind {
+    Whole,
+    Field(Vec<Member>),
+    /// A range or an index.
+    Indices(Expr),
+}
+
+impl Place {
+    fn from_expr(expr: &Expr) -> Result<Self> {
+        let error = || {
+            Error::new(
+                expr.span(),
+                "Expected a `&mut` input `x`, a field `x.field` of such an input, or a range `x[a..b]` or an index `x[i]` of such an input",
+            )
+        };
+        match expr {
+            Expr::Path(path) => Ok(Self {
+                input: path.path.get_ident().ok_or_else(error)?.clone(),
+                kind: PlaceKind::Whole,
+            }),
+            Expr::Field(field) => {
+                let mut members = vec![field.member.clone()];
+                let mut base = &*field.base;
+                while let Expr::Field(field) = base {
+                    members.insert(0, field.member.clone());
+                    base = &field.base;
+                }
+                let Expr::Path(path) = base else {
+                    return Err(error());
+                };
+                Ok(Self {
+                    input: path.path.get_ident().ok_or_else(error)?.clone(),
+                    kind: PlaceKind::Field(members),
+                })
+            }
+            Expr::Index(index) => {
+                let Expr::Path(path) = &*index.expr else {
+                    return Err(error());
+                };
+                Ok(Self {
+                    input: path.path.get_ident().ok_or_else(error)?.clone(),
+                    kind: PlaceKind::Indices((*index.index).clone()),
+                })
+            }
+            _ => Err(error()),
+        }
+    }
+}
+
+/// The arguments of `#[modifies(...)]`: a comma-separated list of
+/// places.
+pub struct Places(Vec<Place>);
+
+impl Parse for Places {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let exprs = Punctuated::<Expr, Token![,]>::parse_terminated(input)?;
+        exprs
+            .iter()
+            .map(Place::from_expr)
+            .collect::<Result<_>>()
+            .map(Self)
+    }
+}
+
+/// What a function may modify in one of its `&mut` inputs.
+enum Frame {
+    Nothing,
+    Everything,
+    Fields(Vec<Vec<Member>>),
+    Indices(Vec<Expr>),
+}
+
+/// A `&mut` input of a function, with the type it points to.
+struct MutInput {
+    name: Ident,
+    ty: Type,
+    frame: Frame,
+}
+
+/// The `&mut` inputs of a function signature, with their frames.
+fn mut_inputs(sig: &Signature, places: Places) -> Result<Vec<MutInput>> {
+    let mut inputs: Vec<MutInput> = vec![];
+    for input in &sig.inputs {
+        let (name, ty) = match input {
+            FnArg::Receiver(receiver) => match &*receiver.ty {
+                Type::Reference(TypeReference {
+                    mutability: Some(_),
+                    elem,
+                    ..
+                }) => (
+                    Ident::new("self", receiver.self_token.span),
+                    (**elem).clone(),
+                ),
+                _ => continue,
+            },
+            FnArg::Typed(PatType { pat, ty, .. }) => match (&**pat, &**ty) {
+                (
+                    Pat::Ident(PatIdent { ident, .. }),
+                    Type::Reference(TypeReference {
+                        mutability: Some(_),
+                        elem,
+                        ..
+                    }),
+                ) => (ident.clone(), (**elem).clone()),
+                _ => continue,
+            },
+        };
+        inputs.push(MutInput {
+            name,
+            ty,
+            frame: Frame::Nothing,
+        });
+    }
+    if inputs.is_empty() {
+        return Err(Error::new(
+            sig.ident.span(),
+            "`modifies` requires a function with `&mut` inputs",
+        ));
+    }
+    for Place { input, kind } in places.0 {
+        let Some(mut_input) = inputs.iter_mut().find(|i| i.name == input) else {
+            return Err(Error::new(
+                input.span(),
+                format!("`{input}` is not a `&mut` input of this function"),
+            ));
+        };
+        mut_input.frame = match (
+            std::mem::replace(&mut mut_input.frame, Frame::Nothing),
+            kind,
+        ) {
+            (_, PlaceKind::Whole) | (Frame::Everything, _) => Frame::Everything,
+            (Frame::Nothing, PlaceKind::Field(field)) => Frame::Fields(vec![field]),
+            (Frame::Fields(mut fields), PlaceKind::Field(field)) => {
+                fields.push(field);
+                Frame::Fields(fields)
+            }
+            (Frame::Nothing, PlaceKind::Indices(indices)) => Frame::Indices(vec![indices]),
+            (Frame::Indices(mut all), PlaceKind::Indices(indices)) => {
+                all.push(indices);
+                Frame::Indices(all)
+            }
+            _ => {
+                return Err(Error::new(
+                    input.span(),
+                    format!("The places of `{input}` must be either all fields or all indices"),
+                ))
+            }
+        };
+    }
+    Ok(inputs)
+}
+
+/// The proposition "`i` is in `indices`", where `indices` is a range
+/// or an index.
+fn is_in(i: &Ident, indices: &Expr) -> TokenStream {
+    match indices {
+        Expr::Range(ExprRange {
+            start, limits, end, ..
+        }) => {
+            let start = start.iter().map(|start| quote! {(#start) <= #i});
+            let end = end.iter().map(|end| match limits {
+                RangeLimits::HalfOpen(_) => quote! {#i < (#end)},
+                RangeLimits::Closed(_) => quote! {#i <= (#end)},
+            });
+            quote! {(true #(&& #start)* #(&& #end)*)}
+        }
+        index => quote! {(#i == (#index))},
+    }
+}
+
+/// The frame condition of a function: a proposition relating the
+/// `&mut` inputs `x` of the function to their values `future(x)` at
+/// the end of the function, to be used in an `ensures` clause.
+#[cfg_attr(not(hax), allow(dead_code))]
+pub fn frame_condition(sig: &Signature, places: Places) -> Result<Expr> {
+    let i = Ident::new("hax_frame_index", Span::call_site());
+    let conditions = mut_inputs(sig, places)?
+        .into_iter()
+        .filter_map(|MutInput { name, ty, frame }| match frame {
+            Frame::Everything => None,
+            Frame::Nothing => Some(quote! {
+                ::hax_lib::prop::constructors::eq(future(#name), #name)
+            }),
+            Frame::Fields(fields) => Some(quote! {
+                ::hax_lib::prop::constructors::eq(future(#name), &{
+                    let mut expected = <#ty as ::core::clone::Clone>::clone(#name);
+                    #(expected.#(#fields).* = ::core::clone::Clone::clone(&future(#name).#(#fields).*);)*
+                    expected
+                })
+            }),
+            Frame::Indices(indices) => {
+                let is_in = indices.iter().map(|indices| is_in(&i, indices));
+                Some(quote! {
+                    ::hax_lib::prop::constructors::and(
+                        ::hax_lib::Prop::from(future(#name).len() == #name.len()),
+                        ::hax_lib::prop::constructors::forall(|#i: usize| {
+                            ::hax_lib::prop::constructors::implies(
+                                ::hax_lib::Prop::from(#i < #name.len() #(&& !#is_in)*),
+                                ::hax_lib::prop::constructors::eq(&future(#name)[#i], &#name[#i]),
+                            )
+                        }),
+                    )
+                })
+            }
+        });
+    Ok(parse_quote! {
+        {
+            let condition = ::hax_lib::Prop::from(true);
+            #(let condition = ::hax_lib::prop::constructors::and(condition, #conditions);)*
+            condition
+        }
+    })
+}
+
+/// The span of a non-`'static` reference or lifetime in `ty`, which
+/// the result of a function of type `ty` may borrow from the inputs of
+/// the function.
+fn borrow_in(ty: &Type) -> Option<Span> {
+    #[derive(Default)]
+    struct FindBorrow(Option<Span>);
+    impl<'a> syn::visit::Visit<'a> for FindBorrow {
+        fn visit_type_reference(&mut self, reference: &'a TypeReference) {
+            match &reference.lifetime {
+                Some(lifetime) => self.visit_lifetime(lifetime),
+                None => {
+                    self.0.get_or_insert(reference.and_token.span);
+                }
+            }
+            self.visit_type(&reference.elem);
+        }
+        fn visit_lifetime(&mut self, lifetime: &'a Lifetime) {
+            if lifetime.ident != "static" {
+                self.0.get_or_insert(lifetime.span());
+            }
+        }
+    }
+    let mut visitor = FindBorrow::default();
+    syn::visit::Visit::visit_type(&mut visitor, ty);
+    visitor.0
+}
+
+/// A version of the function `item` that checks its frame condition
+/// at run time, panicking if it modifies a place not listed in
+/// `places`. The `&mut` inputs are compared with their copies after
+/// the function returns, which is rejected when its result may borrow
+/// from them.
+#[cfg_attr(hax, allow(dead_code))]
+pub fn runtime_check(item: &ImplItemFn, places: Places) -> Result<ImplItemFn> {
+    let sig = &item.sig;
+    if let Some(asyncness) = sig.asyncness {
+        return Err(Error::new(
+            asyncness.span,
+            "The frame conditions of `async` functions cannot be checked at run time",
+        ));
+    }
+    if let Some(constness) = sig.constness {
+        return Err(Error::new(
+            constness.span,
+            "The frame conditions of `const` functions cannot be checked at run time",
+        ));
+    }
+    if let ReturnType::Type(_, ty) = &sig.output {
+        if let Some(span) = borrow_in(ty) {
+            return Err(Error::new(
+                span,
+                "The frame conditions of functions whose result may borrow from their inputs cannot be checked at run time",
+            ));
+        }
+    }
+    let function = &sig.ident;
+    let mut before: Vec<Stmt> = vec![];
+    let mut after: Vec<Stmt> = vec![];
+    for MutInput { name, ty, frame } in mut_inputs(sig, places)? {
+        let snapshot = format_ident!("__hax_frame_{}", name);
+        let message = quote! {
+            ::core::concat!(
+                "`", ::core::stringify!(#function), "` modifies `", ::core::stringify!(#name),
+                "` outside of its `modifies` clause"
+            )
+        };
+        if !matches!(frame, Frame::Everything) {
+            before.push(parse_quote! {
+                let #snapshot = ::std::borrow::ToOwned::to_owned(&*#name);
+            });
+        }
+        match frame {
+            Frame::Everything => (),
+            Frame::Nothing => after.push(parse_quote! {
+                ::core::assert!(*#name == *::core::borrow::Borrow::<#ty>::borrow(&#snapshot), #message);
+            }),
+            Frame::Fields(fields) => after.push(parse_quote! {
+                {
+                    let mut expected = #snapshot;
+                    #(expected.#(#fields).* = ::core::clone::Clone::clone(&#name.#(#fields).*);)*
+                    ::core::assert!(*#name == expected, #message);
+                }
+            }),
+            Frame::Indices(indices) => {
+                let ranges: Vec<Ident> = (0..indices.len())
+                    .map(|k| format_ident!("__hax_frame_{}_{}", name, k))
+                    .collect();
+                for (range, indices) in ranges.iter().zip(indices) {
+                    let indices = match indices {
+                        Expr::Range(range) => quote! {#range},
+                        index => quote! {{ let index: usize = #index; index..=index }},
+                    };
+                    before.push(parse_quote! {
+                        let #range = #indices;
+                    });
+                }
+                after.push(parse_quote! {
+                    ::core::assert!(
+                        ::hax_lib::_internal_check_frame_indices(&#snapshot[..], &#name[..], |i| {
+                            false #(|| ::core::ops::RangeBounds::contains(&#ranges, &i))*
+                        }),
+                        #message
+                    );
+                });
+            }
+        }
+    }
+    let output = match &sig.output {
+        ReturnType::Type(_, ty) if !matches!(&**ty, Type::ImplTrait(_)) => quote! {-> #ty},
+        _ => quote! {},
+    };
+    let body = &item.block;
+    let mut checked = item.clone();
+    checked.block = parse_quote! {
+        {
+            #(#before)*
+            #[allow(clippy::redundant_closure_call)]
+            let result = (|| #output #body)();
+            #(#after)*
+            result
+        }
+    };
+    Ok(checked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn runtime_check_error(places: &str, item: &str) -> Option<String> {
+        let places: Places = parse_str(places).unwrap();
+        let item: ImplItemFn = parse_str(item).unwrap();
+        runtime_check(&item, places)
+            .err()
+            .map(|error| error.to_string())
+    }
+
+    #[test]
+    fn borrowing_results_are_rejected() {
+        let error = "The frame conditions of functions whose result may borrow from their inputs cannot be checked at run time";
+        for item in [
+            "fn first(x: &mut Vec<u8>) -> &mut u8 { &mut x[0] }",
+            "fn first<'a>(x: &'a mut Vec<u8>) -> Option<&'a u8> { x.first() }",
+            "fn iter(x: &mut Vec<u8>) -> std::slice::Iter<'_, u8> { x.iter() }",
+        ] {
+            assert_eq!(runtime_check_error("x[0]", item).as_deref(), Some(error));
+        }
+    }
+
+    #[test]
+    fn owned_results_are_checked() {
+        for item in [
+            "fn first(x: &mut Vec<u8>) -> u8 { x[0] }",
+            "fn name(x: &mut Vec<u8>) -> &'static str { \"x\" }",
+        ] {
+            assert_eq!(runtime_check_error("x[0]", item), None);
+        }
+    }
+}
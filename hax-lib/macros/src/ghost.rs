@@ -0,0 +1,505 @@
+//! Ghost code, shared by the hax and non-hax versions of `attributes`
+//! and `ghost`: ghost functions, parameters, `let` bindings and
+//! statements are checked not to leak into non-ghost code, and, in
+//! non-hax builds, erased.
+//!
+//! Erasure keeps ghost code type-checked: a ghost variable of type `T`
+//! becomes a `::hax_lib::Ghost<T>`, which carries no value, and ghost
+//! code is moved into closures that are never called, in which the
+//! ghost variables are rebound to values of their original types. A
+//! ghost function returning a `T` returns a `::hax_lib::Ghost<T>`,
+//! whose body is such a closure: it cannot be called from non-ghost
+//! code, and the calls to the ghost functions of an item in its ghost
+//! code are rebound to values of their original types too.
+
+use crate::hax_paths::expects_hax_path;
+use proc_macro2::{TokenStream, TokenTree};
+use quote::quote;
+use std::collections::HashSet;
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+use syn::visit_mut::{self, VisitMut};
+use syn::*;
+
+/// The macros whose arguments are specifications or ghost code, in
+/// which ghost variables may be used.
+const SPEC_MACROS: &[&str] = &[
+    "assert_prop",
+    "assume",
+    "coq",
+    "fstar",
+    "ghost_expr",
+    "loop_decreases",
+    "loop_invariant",
+    "proverif",
+];
+
+/// Removes the `ghost` attributes from `attrs`, telling whether there
+/// was one.
+pub fn take_ghost_attr(attrs: &mut Vec<Attribute>) -> bool {
+    let before = attrs.len();
+    attrs.retain(|attr| {
+        !(matches!(attr.meta, Meta::Path(_))
+            && matches!(expects_hax_path(&["ghost"], attr.path()), Ok(Some(_))))
+    });
+    attrs.len() != before
+}
+
+/// The attributes of an expression statement.
+fn expr_attrs_mut(expr: &mut Expr) -> Option<&mut Vec<Attribute>> {
+    // The attributes of `#[ghost] x = e` are those of `x`.
+    Some(match expr {
+        Expr::Assign(e) => return expr_attrs_mut(&mut e.left),
+        Expr::Binary(e) => return expr_attrs_mut(&mut e.left),
+        Expr::Block(e) => &mut e.attrs,
+        Expr::Call(e) => &mut e.attrs,
+        Expr::ForLoop(e) => &mut e.attrs,
+        Expr::If(e) => &mut e.attrs,
+        Expr::Loop(e) => &mut e.attrs,
+        Expr::Macro(e) => &mut e.attrs,
+        Expr::Match(e) => &mut e.attrs,
+        Expr::MethodCall(e) => &mut e.attrs,
+        Expr::Path(e) => &mut e.attrs,
+        Expr::Unary(e) => &mut e.attrs,
+        Expr::While(e) => &mut e.attrs,
+        _ => return None,
+    })
+}
+
+/// The names of the ghost functions defined in `item`.
+pub fn ghost_fns(item: &Item) -> HashSet<Ident> {
+    #[derive(Default)]
+    struct Collect(HashSet<Ident>);
+    impl Collect {
+        fn function(&mut self, attrs: &[Attribute], sig: &Signature) {
+            let mut attrs = attrs.to_vec();
+            if take_ghost_attr(&mut attrs) {
+                self.0.insert(sig.ident.clone());
+            }
+        }
+    }
+    impl<'a> Visit<'a> for Collect {
+        fn visit_item_fn(&mut self, item: &'a ItemFn) {
+            self.function(&item.attrs, &item.sig);
+        }
+        fn visit_impl_item_fn(&mut self, item: &'a ImplItemFn) {
+            self.function(&item.attrs, &item.sig);
+        }
+        fn visit_trait_item_fn(&mut self, item: &'a TraitItemFn) {
+            self.function(&item.attrs, &item.sig);
+        }
+    }
+    let mut collect = Collect::default();
+    collect.visit_item(item);
+    collect.0
+}
+
+/// The variable bound by a ghost parameter or `let` binding, with
+/// its type if any.
+fn ghost_binder(pat: &mut Pat) -> Option<(&mut PatIdent, Option<Type>)> {
+    match pat {
+        Pat::Ident(pat @ PatIdent { subpat: None, .. }) => Some((pat, None)),
+        Pat::Type(PatType { pat, ty, .. }) => match &mut **pat {
+            Pat::Ident(pat @ PatIdent { subpat: None, .. }) => Some((pat, Some((**ty).clone()))),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The identifiers occurring in some tokens.
+fn idents(tokens: TokenStream, found: &mut HashSet<Ident>) {
+    for token in tokens {
+        match token {
+            TokenTree::Ident(ident) => {
+                found.insert(ident);
+            }
+            TokenTree::Group(group) => idents(group.stream(), found),
+            _ => (),
+        }
+    }
+}
+
+/// Checks the ghost functions, parameters, `let` bindings and
+/// statements of functions, and erases them if `erase` holds.
+pub struct Ghosts {
+    erase: bool,
+    ghost_fns: HashSet<Ident>,
+    /// The variables in scope that are bound by ghost bindings, or
+    /// that shadow such variables, with whether they are ghost.
+    vars: Vec<(Ident, bool)>,
+    pub errors: Option<Error>,
+}
+
+impl Ghosts {
+    pub fn new(erase: bool, ghost_fns: HashSet<Ident>) -> Self {
+        Self {
+            erase,
+            ghost_fns,
+            vars: vec![],
+            errors: None,
+        }
+    }
+
+    fn error(&mut self, span: proc_macro2::Span, message: impl std::fmt::Display) {
+        let error = Error::new(span, message);
+        match &mut self.errors {
+            Some(errors) => errors.combine(error),
+            None => self.errors = Some(error),
+        }
+    }
+
+    fn is_ghost_var(&self, ident: &Ident) -> bool {
+        self.vars
+            .iter()
+            .rev()
+            .find(|(var, _)| var == ident)
+            .is_some_and(|(_, ghost)| *ghost)
+    }
+
+    /// Processes a function with signature `sig` and body `block`,
+    /// which is a ghost function if `ghost` holds.
+    pub fn function(&mut self, ghost: bool, sig: &mut Signature, block: Option<&mut Block>) {
+        for input in sig.inputs.iter_mut() {
+            match input {
+                FnArg::Receiver(receiver) => {
+                    if take_ghost_attr(&mut receiver.attrs) {
+                        self.error(receiver.span(), "`self` cannot be ghost");
+                    }
+                }
+                FnArg::Typed(input) => {
+                    if !take_ghost_attr(&mut input.attrs) || ghost {
+                        continue;
+                    }
+                    let Some((binder, None)) = ghost_binder(&mut input.pat) else {
+                        self.error(input.pat.span(), "Expected a variable");
+                        continue;
+                    };
+                    self.vars.push((binder.ident.clone(), true));
+                    if self.erase {
+                        binder.mutability = None;
+                        let ty = &input.ty;
+                        input.ty = parse_quote! {::hax_lib::Ghost<#ty>};
+                    }
+                }
+            }
+        }
+        // The body of a ghost function is ghost code.
+        match block {
+            Some(block) if !ghost => self.block(block),
+            block if ghost && self.erase => self.erase_ghost_fn(sig, block),
+            _ => (),
+        }
+        self.vars.clear();
+    }
+
+    /// Erases a ghost function: its result becomes a `Ghost`, and its
+    /// body ghost code that is never executed.
+    fn erase_ghost_fn(&mut self, sig: &mut Signature, block: Option<&mut Block>) {
+        let ty: Type = match &sig.output {
+            ReturnType::Default => parse_quote! {()},
+            ReturnType::Type(_, ty) => (**ty).clone(),
+        };
+        sig.output = parse_quote! {-> ::hax_lib::Ghost<#ty>};
+        if let Some(block) = block {
+            let mut body = Expr::Block(ExprBlock {
+                attrs: vec![],
+                label: None,
+                block: block.clone(),
+            });
+            self.unghost_calls(&mut body);
+            *block = parse_quote! {{
+                #[allow(unused_mut)]
+                ::hax_lib::Ghost::erased(|| #body)
+            }};
+        }
+    }
+
+    /// Rebinds the results of the calls to the ghost functions of the
+    /// item in the erased ghost code `expr` to values of their
+    /// original types.
+    fn unghost_calls(&self, expr: &mut Expr) {
+        struct Unghost<'a>(&'a HashSet<Ident>);
+        impl VisitMut for Unghost<'_> {
+            fn visit_item_mut(&mut self, _item: &mut Item) {}
+            fn visit_expr_mut(&mut self, expr: &mut Expr) {
+                visit_mut::visit_expr_mut(self, expr);
+                let ghost = match &*expr {
+                    Expr::Call(ExprCall { func, .. }) => match &**func {
+                        Expr::Path(ExprPath {
+                            qself: None, path, ..
+                        }) => {
+                            let segments: Vec<_> = path.segments.iter().collect();
+                            match &segments[..] {
+                                [f] => self.0.contains(&f.ident),
+                                [ty, f] => ty.ident == "Self" && self.0.contains(&f.ident),
+                                _ => false,
+                            }
+                        }
+                        _ => false,
+                    },
+                    Expr::MethodCall(call) => self.0.contains(&call.method),
+                    _ => false,
+                };
+                if ghost {
+                    *expr = parse_quote! {::hax_lib::Ghost::get(&#expr)};
+                }
+            }
+        }
+        Unghost(&self.ghost_fns).visit_expr_mut(expr);
+    }
+
+    /// Rebinds the ghost variables occurring in `tokens` to values of
+    /// their original types, for erased ghost code.
+    fn rebind_ghost_vars(&self, tokens: TokenStream) -> TokenStream {
+        let mut found = HashSet::new();
+        idents(tokens, &mut found);
+        let mut vars: Vec<&Ident> = found.iter().filter(|i| self.is_ghost_var(i)).collect();
+        vars.sort_by_key(|var| var.to_string());
+        quote! {
+            #(let mut #vars = ::hax_lib::Ghost::get(&#vars);)*
+        }
+    }
+
+    /// Checks the ghost code `expr`: it may not assign non-ghost
+    /// variables, nor return.
+    fn check_ghost_code(&mut self, expr: &Expr) {
+        struct Locals(HashSet<Ident>);
+        impl<'a> Visit<'a> for Locals {
+            fn visit_pat_ident(&mut self, pat: &'a PatIdent) {
+                self.0.insert(pat.ident.clone());
+                visit::visit_pat_ident(self, pat);
+            }
+        }
+        struct Check<'a> {
+            ghosts: &'a mut Ghosts,
+            locals: HashSet<Ident>,
+        }
+        impl Check<'_> {
+            fn assigned(&mut self, place: &Expr) {
+                let root = match place {
+                    Expr::Field(e) => return self.assigned(&e.base),
+                    Expr::Index(e) => return self.assigned(&e.expr),
+                    Expr::Paren(e) => return self.assigned(&e.expr),
+                    Expr::Unary(e) => return self.assigned(&e.expr),
+                    Expr::Path(path) => path.path.get_ident(),
+                    _ => None,
+                };
+                match root {
+                    Some(root) if self.locals.contains(root) || self.ghosts.is_ghost_var(root) => {}
+                    _ => self
+                        .ghosts
+                        .error(place.span(), "Ghost code cannot modify non-ghost variables"),
+                }
+            }
+        }
+        impl<'a> Visit<'a> for Check<'_> {
+            fn visit_item(&mut self, _item: &'a Item) {}
+            fn visit_expr_closure(&mut self, _closure: &'a ExprClosure) {}
+            fn visit_expr(&mut self, expr: &'a Expr) {
+                match expr {
+                    Expr::Assign(e) => self.assigned(&e.left),
+                    Expr::Binary(e)
+                        if matches!(
+                            e.op,
+                            BinOp::AddAssign(_)
+                                | BinOp::SubAssign(_)
+                                | BinOp::MulAssign(_)
+                                | BinOp::DivAssign(_)
+                                | BinOp::RemAssign(_)
+                                | BinOp::BitXorAssign(_)
+                                | BinOp::BitAndAssign(_)
+                                | BinOp::BitOrAssign(_)
+                                | BinOp::ShlAssign(_)
+                                | BinOp::ShrAssign(_)
+                        ) =>
+                    {
+                        self.assigned(&e.left)
+                    }
+                    Expr::Return(e) => self
+                        .ghosts
+                        .error(e.span(), "Ghost code cannot return from a function"),
+                    Expr::Try(e) => self.ghosts.error(
+                        e.question_token.span(),
+                        "Ghost code cannot return from a function",
+                    ),
+                    _ => (),
+                }
+                visit::visit_expr(self, expr);
+            }
+        }
+        let mut locals = Locals(HashSet::new());
+        locals.visit_expr(expr);
+        let mut check = Check {
+            ghosts: self,
+            locals: locals.0,
+        };
+        check.visit_expr(expr);
+    }
+
+    /// Processes the statements of a block, which is not ghost code.
+    fn block(&mut self, block: &mut Block) {
+        let scope = self.vars.len();
+        for stmt in block.stmts.iter_mut() {
+            self.stmt(stmt);
+        }
+        self.vars.truncate(scope);
+    }
+
+    fn stmt(&mut self, stmt: &mut Stmt) {
+        let lints = quote! {#[allow(unused_assignments, unused_mut, unused_variables)]};
+        let ghost = match stmt {
+            Stmt::Local(local) => take_ghost_attr(&mut local.attrs),
+            Stmt::Expr(expr, _) => expr_attrs_mut(expr).is_some_and(take_ghost_attr),
+            Stmt::Macro(mac) => take_ghost_attr(&mut mac.attrs),
+            Stmt::Item(_) => false,
+        };
+        match stmt {
+            Stmt::Local(local) if ghost => {
+                let Some(init) = &local.init else {
+                    return self.error(local.span(), "A ghost binding needs an initializer");
+                };
+                if let Some((_, diverge)) = &init.diverge {
+                    return self.error(
+                        diverge.span(),
+                        "A ghost binding cannot have an `else` block",
+                    );
+                }
+                self.check_ghost_code(&init.expr);
+                let mut init = init.expr.clone();
+                self.unghost_calls(&mut init);
+                let rebinds = self.rebind_ghost_vars(quote! {#init});
+                let Some((binder, ty)) = ghost_binder(&mut local.pat) else {
+                    return self.error(local.pat.span(), "Expected a variable");
+                };
+                self.vars.push((binder.ident.clone(), true));
+                if self.erase {
+                    binder.mutability = None;
+                    let name = &binder.ident;
+                    let ty = ty.map(|ty| quote! {-> #ty});
+                    *stmt = parse_quote! {
+                        #lints
+                        let #name = ::hax_lib::Ghost::erased(|| #ty { #rebinds #init });
+                    };
+                }
+            }
+            Stmt::Local(local) => {
+                if let Some(init) = &mut local.init {
+                    self.visit_expr_mut(&mut init.expr);
+                    if let Some((_, diverge)) = &mut init.diverge {
+                        self.visit_expr_mut(diverge);
+                    }
+                }
+                struct Bound<'a>(&'a mut Vec<(Ident, bool)>);
+                impl<'a> Visit<'a> for Bound<'_> {
+                    fn visit_pat_ident(&mut self, pat: &'a PatIdent) {
+                        self.0.push((pat.ident.clone(), false));
+                        visit::visit_pat_ident(self, pat);
+                    }
+                }
+                Bound(&mut self.vars).visit_pat(&local.pat);
+            }
+            Stmt::Expr(expr, _) if !ghost => self.visit_expr_mut(expr),
+            Stmt::Expr(expr, semi) => {
+                if semi.is_none()
+                    && !matches!(
+                        expr,
+                        Expr::Block(_)
+                            | Expr::ForLoop(_)
+                            | Expr::If(_)
+                            | Expr::Loop(_)
+                            | Expr::Match(_)
+                            | Expr::While(_)
+                    )
+                {
+                    return self.error(
+                        expr.span(),
+                        "A ghost statement cannot be the value of a block: add a `;`",
+                    );
+                }
+                self.check_ghost_code(expr);
+                if self.erase {
+                    let mut expr = expr.clone();
+                    self.unghost_calls(&mut expr);
+                    let rebinds = self.rebind_ghost_vars(quote! {#expr});
+                    *stmt = parse_quote! {
+                        #lints
+                        let _ = || { #rebinds #expr; };
+                    };
+                }
+            }
+            Stmt::Macro(mac) if ghost => {
+                if self.erase {
+                    let rebinds = self.rebind_ghost_vars(quote! {#mac});
+                    *stmt = parse_quote! {
+                        #lints
+                        let _ = || { #rebinds #mac };
+                    };
+                }
+            }
+            Stmt::Macro(mac) => self.mac(&mut mac.mac),
+            Stmt::Item(_) => (),
+        }
+    }
+
+    /// Checks a macro invocation in non-ghost code.
+    fn mac(&mut self, mac: &mut Macro) {
+        let spec = matches!(expects_hax_path(SPEC_MACROS, &mac.path), Ok(Some(_)));
+        if spec {
+            return;
+        }
+        let mut found = HashSet::new();
+        idents(mac.tokens.clone(), &mut found);
+        for ident in found {
+            if self.is_ghost_var(&ident) || self.ghost_fns.contains(&ident) {
+                self.error(
+                    ident.span(),
+                    format!("`{ident}` is ghost: it can only be used in ghost code, specifications and `ghost_expr!`"),
+                );
+            }
+        }
+    }
+}
+
+impl VisitMut for Ghosts {
+    /// Nested items are processed on their own.
+    fn visit_item_mut(&mut self, _item: &mut Item) {}
+
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        self.block(block)
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        match expr {
+            Expr::Path(path) => {
+                if let Some(ident) = path.path.get_ident() {
+                    if self.is_ghost_var(ident) || self.ghost_fns.contains(ident) {
+                        self.error(
+                            ident.span(),
+                            format!("`{ident}` is ghost: it can only be used in ghost code, specifications and `ghost_expr!`"),
+                        );
+                    }
+                }
+            }
+            Expr::Macro(ExprMacro { mac, .. })
+                if matches!(expects_hax_path(&["ghost_expr"], &mac.path), Ok(Some(_))) =>
+            {
+                let Ok(ghost) = mac.parse_body::<Expr>() else {
+                    // Let `ghost_expr!` report the error.
+                    return;
+                };
+                self.check_ghost_code(&ghost);
+                if self.erase {
+                    let mut ghost = ghost;
+                    self.unghost_calls(&mut ghost);
+                    let rebinds = self.rebind_ghost_vars(quote! {#ghost});
+                    *expr = parse_quote! {
+                        ::hax_lib::Ghost::erased(|| { #rebinds #ghost })
+                    };
+                }
+            }
+            Expr::Macro(ExprMacro { mac, .. }) => self.mac(mac),
+            _ => visit_mut::visit_expr_mut(self, expr),
+        }
+    }
+}
@@ -38,6 +38,16 @@ pub fn expects_order(path: &Path) -> Result<Option<String>> {
     expects_hax_path(&["order"], path)
 }
 
+/// Expects a path to be `[[::]hax_lib]::modifies`
+pub fn expects_modifies(path: &Path) -> Result<Option<String>> {
+    expects_hax_path(&["modifies"], path)
+}
+
+/// Expects a path to be `[[::]hax_lib]::modeled_by`
+pub fn expects_modeled_by(path: &Path) -> Result<Option<String>> {
+    expects_hax_path(&["modeled_by"], path)
+}
+
 /// Expects a `Path` to be a hax path: `::hax_lib::<KW>`,
 /// `hax_lib::<KW>` or `<KW>` in (with `KW` in `allowlist`).
 pub fn expects_hax_path(allowlist: &[&str], path: &Path) -> Result<Option<String>> {
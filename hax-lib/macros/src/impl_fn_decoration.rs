@@ -28,6 +28,17 @@ impl parse::Parse for ImplFnDecoration {
 
         let path = input.parse::<Path>()?;
         let path_span = path.span();
+        if expects_modeled_by(&path)?.is_some() {
+            let (generics, self_ty) = parse_next()?;
+            let model = input.parse::<Path>()?;
+            input.parse::<syn::parse::Nothing>()?;
+            return Ok(ImplFnDecoration {
+                kind: FnDecorationKind::ModeledBy,
+                phi: parse_quote! {#model},
+                generics,
+                self_ty,
+            });
+        }
         let kind = match expects_path_decoration(&path)? {
             Some(s) => match s.as_str() {
                 "decreases" => FnDecorationKind::Decreases,
@@ -49,7 +60,10 @@ impl parse::Parse for ImplFnDecoration {
         };
 
         let (generics, self_ty) = parse_next()?;
-        let phi = input.parse::<Expr>()?;
+        let phi = match &kind {
+            FnDecorationKind::Decreases => input.parse::<Measure>()?.into_expr(),
+            _ => input.parse::<Expr>()?,
+        };
         input.parse::<syn::parse::Nothing>()?;
         Ok(ImplFnDecoration {
             kind,
@@ -1,6 +1,10 @@
+mod frame;
+mod ghost;
 mod hax_paths;
 mod impl_fn_decoration;
+mod pure;
 mod quote;
+mod refinement;
 mod rewrite_self;
 mod syn_ext;
 mod utils;
@@ -57,17 +61,43 @@ pub fn fstar_options(attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenS
 ///  - `for i in slice.enumerate() {...}`
 ///  - `for i in slice.chunks_exact(n).enumerate() {...}`
 ///
+/// On loops over other iterators built from ranges and slices with
+/// `iter`, `enumerate`, `step_by`, `zip`, `rev`, `windows`, `chunks`
+/// and `chunks_exact`, and on `while let Some(x) = it.next() {...}`
+/// loops over such an iterator `it`, the index is the number of
+/// iterations done so far. On these loops, the closure may also take
+/// a second argument: the sequence (a `hax_lib::Seq`) of the items
+/// consumed by these iterations, e.g. `|i: usize, prefix:
+/// Seq<(&u8, &u8)>| ...`. An invariant on any other loop is rejected
+/// by hax.
+///
+/// The invariant of a `while` loop is a proposition instead of a
+/// closure.
+///
 /// This function must be called on the first line of a loop body to
 /// be effective. Note that in the invariant expression, `forall`,
 /// `exists`, and `BACKEND!` (`BACKEND` can be `fstar`, `proverif`,
 /// `coq`...) are in scope.
+///
+/// See [`macro@check_loops`] to evaluate invariants at run time.
+#[proc_macro_error]
 #[proc_macro]
 pub fn loop_invariant(predicate: pm::TokenStream) -> pm::TokenStream {
     let predicate2: TokenStream = predicate.clone().into();
     let predicate_expr: syn::Expr = parse_macro_input!(predicate);
 
     let (invariant_f, predicate) = match predicate_expr {
-        syn::Expr::Closure(_) => (quote!(hax_lib::_internal_loop_invariant), predicate2),
+        syn::Expr::Closure(closure) => match closure.inputs.len() {
+            1 => (quote!(hax_lib::_internal_loop_invariant), predicate2),
+            2 => (
+                quote!(hax_lib::_internal_iterator_loop_invariant),
+                predicate2,
+            ),
+            _ => abort!(
+                closure.or1_token,
+                "A loop invariant closure takes the index of the loop, and optionally the sequence of the items consumed so far"
+            ),
+        },
         _ => (
             quote!(hax_lib::_internal_while_loop_invariant),
             quote!(::hax_lib::Prop::from(#predicate2)),
@@ -91,6 +121,8 @@ pub fn loop_invariant(predicate: pm::TokenStream) -> pm::TokenStream {
 ///
 /// This function must be called just after `loop_invariant`, or at the first
 /// line of the loop if there is no invariant.
+///
+/// See [`macro@check_loops`] to evaluate measures at run time.
 #[proc_macro]
 pub fn loop_decreases(predicate: pm::TokenStream) -> pm::TokenStream {
     let predicate: TokenStream = predicate.into();
@@ -108,6 +140,50 @@ pub fn loop_decreases(predicate: pm::TokenStream) -> pm::TokenStream {
     ts
 }
 
+/// Checks the loop specifications of a function at run time, in
+/// non-hax builds with debug assertions: the `loop_invariant!` and
+/// `loop_decreases!` calls at the start of the loops of the function
+/// are evaluated at the start of each iteration, and a violation
+/// panics with the location of the loop.
+///
+/// The invariant closure of a `for` loop over a range, a range with a
+/// step or an enumeration is applied to the index of the loop, that
+/// is, the variable bound by the loop, or the first component of its
+/// pattern for `enumerate` loops. The invariant closures of the other
+/// `for` loops and of `while let Some(item) = ...` loops are applied
+/// to the number of iterations done so far and, for closures with two
+/// arguments, to the sequence of the items consumed by these
+/// iterations: this clones the items, and requires the `alloc`
+/// feature of `hax-lib`. A decreases measure must be non-negative, and
+/// smaller than at the previous iteration of the loop. Specifications
+/// on `Int`s need the `runtime-contracts` feature of `hax-lib`, which
+/// backs them with `i128`s outside of hax: a specification whose
+/// evaluation overflows them panics.
+///
+/// This attribute has no effect on extraction.
+///
+/// # Example:
+///
+/// ```should_panic
+/// #[hax_lib::check_loops]
+/// fn count_up(n: u32) -> u32 {
+///     let mut i = 0;
+///     while i < n {
+///         hax_lib::loop_invariant!(i <= n);
+///         hax_lib::loop_decreases!(i); // wrong: `i` increases
+///         i += 1;
+///     }
+///     i
+/// }
+/// count_up(2);
+/// ```
+#[proc_macro_error]
+#[proc_macro_attribute]
+pub fn check_loops(attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStream {
+    let _ = parse_macro_input!(attr as parse::Nothing);
+    item
+}
+
 /// When extracting to F*, inform about what is the current
 /// verification status for an item. It can either be `lax` or
 /// `panic_free`.
@@ -194,34 +270,42 @@ pub fn exclude(attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStream
     quote! {#attr #item}.into()
 }
 
-/*
-TODO: no support in any backends (see #297)
-
-/// Exclude this item from the Hax translation, and replace it with a
-/// axiomatized model in each backends. The path of the axiomatized
-/// model should be given in Rust syntax.
+/// Exclude this function from the Hax translation, and replace its
+/// call sites with calls to a model. The model is given as a Rust
+/// path to a function with a compatible signature, which is checked
+/// by Rustc. This is useful to extract code relying on SIMD
+/// intrinsics or on FFI, with models written in plain Rust.
+///
+/// The model of a method takes its receiver as first argument. On a
+/// `fn` in an `impl`, `modeled_by` requires a `#[hax_lib::attributes]`
+/// on the `impl` block, as `requires` and `ensures` do.
 ///
 /// # Example
 ///
 /// ```
 /// use hax_lib_macros::*;
-/// #[modeled_by(FStar::IO::debug_print_string)]
-/// fn f(line: String) {
-///   println!("{}", line)
+/// fn add_model(x: [u32; 4], y: [u32; 4]) -> [u32; 4] {
+///     core::array::from_fn(|i| x[i].wrapping_add(y[i]))
+/// }
+///
+/// #[modeled_by(add_model)]
+/// fn add(x: [u32; 4], y: [u32; 4]) -> [u32; 4] {
+///     // Calls to SIMD intrinsics, e.g. `_mm_add_epi32`
+///     # add_model(x, y)
 /// }
 /// ```
 #[proc_macro_error]
 #[proc_macro_attribute]
 pub fn modeled_by(attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStream {
-    use quote::ToTokens;
-    let model_path = parse_macro_input!(attr as syn::Path).to_token_stream();
-    let item: TokenStream = item.into();
-    let attr = AttrPayload::ItemStatus(ItemStatus::Excluded {
-        modeled_by: Some(model_path.to_string()),
-    });
-    quote! {#attr #item}.into()
+    let model_path: syn::Path = parse_macro_input!(attr);
+    let mut item: FnLike = parse_macro_input!(item);
+    let (decoration, attrs) =
+        make_modeled_by_decoration(parse_quote! {#model_path}, item.sig.clone(), None, None);
+    item.block
+        .stmts
+        .insert(0, Stmt::Item(Item::Verbatim(decoration)));
+    quote! {#attrs #item}.into()
 }
-*/
 
 /// Mark a `Proof<{STATEMENT}>`-returning function as a lemma, where
 /// `STATEMENT` is a `Prop` expression capturing any input
@@ -232,7 +316,7 @@ pub fn modeled_by(attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStre
 ///
 /// ```
 /// use hax_lib_macros::*;
-// #[decreases((m, n))] (TODO: see #297)
+/// #[decreases((m, n))]
 /// pub fn ackermann(m: u64, n: u64) -> u64 {
 ///     match (m, n) {
 ///         (0, _) => n + 1,
@@ -306,11 +390,24 @@ pub fn lemma(attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStream {
 }
 
 /// Provide a measure for a function: this measure will be used once
-/// extracted in a backend for checking termination. The expression
-/// that decreases can be of any type. (TODO: this is probably as it
-/// is true only for F*, see #297)
+/// extracted in a backend for checking termination: the measure must
+/// decrease at every recursive call of the function. A measure can be:
+///  - an integer or an `Int`, which must be non-negative;
+///  - a value of an ADT (e.g. a `struct` or an `enum`), ordered
+///    structurally: a value is greater than any of its fields;
+///  - a tuple `(a, b, ...)` of measures, ordered lexicographically.
 ///
-/// # Example
+/// Mutually recursive functions must share the shape of their
+/// measures: the measure must decrease at every call between two
+/// functions of the group. A function can be given a rank with
+/// `#[decreases(e, rank = N)]`: the rank is appended to the measure
+/// `e` (as the last component of a lexicographic tuple), so that a
+/// call from a function to a function of lower rank may keep the
+/// rest of the measure unchanged.
+///
+/// Currently, measures are extracted only by the F* backend.
+///
+/// # Examples
 ///
 /// ```
 /// use hax_lib_macros::*;
@@ -323,10 +420,34 @@ pub fn lemma(attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStream {
 ///     }
 /// }
 /// ```
+///
+/// A parser of nested lists and atoms, where `parse_list` calls
+/// `parse_item` on the same input:
+///
+/// ```
+/// use hax_lib_macros::*;
+/// #[decreases(input.len(), rank = 1)]
+/// pub fn parse_list(input: &[u8]) -> usize {
+///     if input.is_empty() {
+///         0
+///     } else {
+///         parse_item(input)
+///     }
+/// }
+///
+/// #[decreases(input.len(), rank = 0)]
+/// pub fn parse_item(input: &[u8]) -> usize {
+///     match input {
+///         [b'(', rest @ ..] => 1 + parse_list(rest),
+///         [_, rest @ ..] => 1 + parse_item(rest),
+///         [] => 0,
+///     }
+/// }
+/// ```
 #[proc_macro_error]
 #[proc_macro_attribute]
 pub fn decreases(attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStream {
-    let phi: syn::Expr = parse_macro_input!(attr);
+    let phi = parse_macro_input!(attr as Measure).into_expr();
     let item: FnLike = parse_macro_input!(item);
     let (requires, attr) = make_fn_decoration(
         phi,
@@ -443,6 +564,69 @@ pub fn ensures(attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStream
     .into()
 }
 
+/// Add a frame condition to a function with `&mut` inputs: the
+/// function may only modify the listed places of its `&mut` inputs,
+/// and leaves the other inputs unchanged. A place is an input `x`, a
+/// (nested) field `x.field` of an input, or a range `x[a..b]` or an
+/// index `x[i]` of an input indexable by `usize` with a `len` method
+/// (e.g. a slice or a `Vec`).
+///
+/// The frame condition is extracted as a postcondition of the
+/// function. Listing fields requires the type of the input to
+/// implement `Clone`.
+///
+/// The `runtime-contracts` feature of `hax-lib` checks frame conditions
+/// at run time: the `&mut` inputs are copied when the function is
+/// called, and compared with their final values when it returns, which
+/// panics if a place outside of the frame was modified. This requires
+/// the types of the inputs to implement `ToOwned` and `PartialEq`, and
+/// rejects functions whose result may borrow from their inputs, i.e.
+/// whose result type has a reference or a lifetime.
+///
+/// # Example
+///
+/// ```
+/// use hax_lib_macros::*;
+/// #[modifies(x[1..], y.0)]
+/// pub fn bump_tail(x: &mut [u8], y: &mut (u8, u8), z: &mut u8) {
+///     for i in 1..x.len() {
+///         x[i] = x[i].wrapping_add(*z);
+///     }
+///     y.0 = *z;
+/// }
+/// ```
+#[proc_macro_error]
+#[proc_macro_attribute]
+pub fn modifies(attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStream {
+    let places: frame::Places = parse_macro_input!(attr);
+    let item: FnLike = parse_macro_input!(item);
+    let condition = match frame::frame_condition(&item.sig, places) {
+        Ok(condition) => condition,
+        Err(error) => return error.into_compile_error().into(),
+    };
+    quote! {
+        #[::hax_lib::ensures(|_| #condition)]
+        #item
+    }
+    .into()
+}
+
+/// Turns the `modifies` attributes of a function into `ensures`
+/// attributes, so that they are handled as the other decorations of
+/// functions in `impl` blocks and traits.
+fn modifies_as_ensures(attrs: &mut [Attribute], sig: &Signature) {
+    for attr in attrs {
+        let Ok(Some(_)) = expects_modifies(attr.path()) else {
+            continue;
+        };
+        let condition = attr
+            .parse_args()
+            .and_then(|places| frame::frame_condition(sig, places))
+            .unwrap_or_else(|error| abort!(error.span(), "{}", error));
+        *attr = parse_quote! {#[ensures(|_| #condition)]};
+    }
+}
+
 mod kw {
     syn::custom_keyword!(hax_lib);
     syn::custom_keyword!(decreases);
@@ -451,6 +635,73 @@ mod kw {
     syn::custom_keyword!(refine);
 }
 
+/// Marks a function as pure: a function with no side effects, that
+/// terminates and does not panic, and that can thus be used in
+/// specifications (e.g. in `requires`, `ensures` or `loop_invariant`).
+///
+/// The function is checked to be pure at compile time. A pure
+/// function:
+///  - has no `&mut` inputs, and borrows nothing mutably;
+///  - has no `unsafe` code, performs no I/O (e.g. with `println!`),
+///    and is not `async`;
+///  - has no `loop` or `while` loops (`for` loops are allowed), and
+///    is recursive only with a termination measure (`decreases`);
+///  - panics (e.g. with `panic!`, `assert!`, `unwrap`, an indexing, a
+///    division or a remainder) only under a precondition (`requires`).
+///
+/// The `requires` and `decreases` clauses of a pure function must be
+/// placed below `#[pure]`. Arithmetic overflows are left to the proof
+/// obligations of the backends. When extracted via hax, the frontend
+/// checks in addition that a pure function calls only pure functions:
+/// the pure functions of its crate, and the functions of `core`,
+/// `alloc` and `hax_lib` except for interior mutability (e.g.
+/// `Cell::set`), raw pointers and atomics; those that may panic (e.g.
+/// `Option::unwrap` or `core::panicking::panic`) are called only under
+/// a precondition, as in the body of the function. It also checks that
+/// mutually recursive pure functions have termination measures, and
+/// warns about the specifications (including `loop_invariant!` and
+/// `loop_decreases!`) that call a function that is not pure. The F*
+/// backend puts the definitions of pure functions in interfaces, so
+/// that the specifications of other modules can unfold them.
+///
+/// # Example
+///
+/// ```
+/// use hax_lib_macros::*;
+/// #[pure]
+/// #[requires(!v.is_empty())]
+/// pub fn last(v: &[u64]) -> u64 {
+///     v[v.len() - 1]
+/// }
+///
+/// #[pure]
+/// #[decreases(n)]
+/// pub fn triangle(n: u64) -> u64 {
+///     if n == 0 {
+///         0
+///     } else {
+///         n + triangle(n - 1)
+///     }
+/// }
+///
+/// #[requires(!v.is_empty() && last(v) < 100)]
+/// #[ensures(|result| result == last(v) + 1)]
+/// pub fn after_last(v: &[u64]) -> u64 {
+///     v[v.len() - 1] + 1
+/// }
+/// ```
+#[proc_macro_error]
+#[proc_macro_attribute]
+pub fn pure(attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStream {
+    let _ = parse_macro_input!(attr as parse::Nothing);
+    if let Err(error) = pure::check_item(item.clone().into()) {
+        return error.into_compile_error().into();
+    }
+    let item = TokenStream::from(item);
+    let pure_attr = AttrPayload::Pure;
+    quote! {#pure_attr #item}.into()
+}
+
 /// Internal macro for dealing with function decorations
 /// (`#[decreases(...)]`, `#[ensures(...)]`, `#[requires(...)]`) on
 /// `fn` items within an `impl` block. There is special handling since
@@ -467,8 +718,13 @@ pub fn impl_fn_decoration(attr: pm::TokenStream, item: pm::TokenStream) -> pm::T
         self_ty,
     } = parse_macro_input!(attr);
     let mut item: FnLike = parse_macro_input!(item);
-    let (decoration, attr) =
-        make_fn_decoration(phi, item.sig.clone(), kind, Some(generics), Some(self_ty));
+    let (decoration, attr) = if let FnDecorationKind::ModeledBy = kind {
+        make_modeled_by_decoration(phi, item.sig.clone(), Some(generics), Some(self_ty))
+    } else {
+        let (decoration, attr) =
+            make_fn_decoration(phi, item.sig.clone(), kind, Some(generics), Some(self_ty));
+        (decoration, quote! {#attr})
+    };
     let decoration = Stmt::Item(Item::Verbatim(decoration));
     item.block.stmts.insert(0, decoration);
     quote! {#attr #item}.into()
@@ -483,6 +739,9 @@ pub fn trait_fn_decoration(attr: pm::TokenStream, item: pm::TokenStream) -> pm::
         generics,
         self_ty,
     } = parse_macro_input!(attr);
+    if let FnDecorationKind::ModeledBy = kind {
+        abort_call_site!("`modeled_by` is not supported on trait items");
+    }
     let mut item: syn::TraitItemFn = parse_macro_input!(item);
     let (decoration, attr) =
         make_fn_decoration(phi, item.sig.clone(), kind, Some(generics), Some(self_ty));
@@ -514,9 +773,13 @@ pub fn trait_fn_decoration(attr: pm::TokenStream, item: pm::TokenStream) -> pm::
 /// By default, the order of a field is its index, e.g. the first field has
 /// order 0, the i-th field has order i+1.
 ///
-/// ### `decreases`, `ensures` and `requires` (on a `fn` in an `impl`)
-/// `decreases`, `ensures`, `requires`: behave exactly as documented above on
-/// the proc attributes of the same name.
+/// ### `ghost` (on a `fn`, a parameter, a `let` binding or a statement)
+/// Marks ghost code, as documented above on the proc attribute of the same
+/// name.
+///
+/// ### `decreases`, `ensures`, `modeled_by`, `modifies` and `requires` (on a `fn` in an `impl`)
+/// `decreases`, `ensures`, `modeled_by`, `modifies`, `requires`: behave exactly
+/// as documented above on the proc attributes of the same name.
 ///
 /// # Example
 ///
@@ -546,17 +809,49 @@ pub fn trait_fn_decoration(attr: pm::TokenStream, item: pm::TokenStream) -> pm::
 pub fn attributes(_attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStream {
     let item: Item = parse_macro_input!(item);
 
-    #[derive(Default)]
     struct AttrVisitor {
         extra_items: Vec<TokenStream>,
+        ghosts: ghost::Ghosts,
+    }
+
+    impl AttrVisitor {
+        /// Checks a function marked `pure` before its clauses are
+        /// rewritten, and replaces the attribute with its payload.
+        fn pure(&mut self, attrs: &mut Vec<Attribute>, fun: TokenStream) {
+            if pure::take_pure_attr(attrs) {
+                if let Err(error) = pure::check_item(fun) {
+                    self.extra_items.push(error.into_compile_error());
+                }
+                let pure_attr = AttrPayload::Pure;
+                attrs.push(parse_quote! {#pure_attr});
+            }
+        }
     }
 
     use syn::visit_mut;
     impl VisitMut for AttrVisitor {
+        fn visit_item_fn_mut(&mut self, item: &mut ItemFn) {
+            let ghost = ghost::take_ghost_attr(&mut item.attrs);
+            self.ghosts
+                .function(ghost, &mut item.sig, Some(&mut item.block));
+        }
+        fn visit_impl_item_fn_mut(&mut self, item: &mut ImplItemFn) {
+            let ghost = ghost::take_ghost_attr(&mut item.attrs);
+            self.ghosts
+                .function(ghost, &mut item.sig, Some(&mut item.block));
+        }
+        fn visit_trait_item_fn_mut(&mut self, item: &mut TraitItemFn) {
+            let ghost = ghost::take_ghost_attr(&mut item.attrs);
+            self.ghosts
+                .function(ghost, &mut item.sig, item.default.as_mut());
+        }
         fn visit_item_trait_mut(&mut self, item: &mut ItemTrait) {
             let span = item.span();
             for ti in item.items.iter_mut() {
                 if let TraitItem::Fn(fun) = ti {
+                    let tokens = quote! {#fun};
+                    self.pure(&mut fun.attrs, tokens);
+                    modifies_as_ensures(&mut fun.attrs, &fun.sig);
                     for attr in &mut fun.attrs {
                         let Meta::List(ml) = attr.meta.clone() else {
                             continue;
@@ -603,10 +898,17 @@ pub fn attributes(_attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStr
         fn visit_item_impl_mut(&mut self, item: &mut ItemImpl) {
             for ii in item.items.iter_mut() {
                 if let ImplItem::Fn(fun) = ii {
+                    let tokens = quote! {#fun};
+                    self.pure(&mut fun.attrs, tokens);
+                    modifies_as_ensures(&mut fun.attrs, &fun.sig);
                     for attr in fun.attrs.iter_mut() {
                         if let Meta::List(ml) = &mut attr.meta {
-                            let Ok(Some(decoration)) = expects_path_decoration(&ml.path) else {
-                                continue;
+                            let decoration = match (
+                                expects_path_decoration(&ml.path),
+                                expects_modeled_by(&ml.path),
+                            ) {
+                                (Ok(Some(decoration)), _) | (_, Ok(Some(decoration))) => decoration,
+                                _ => continue,
                             };
                             let decoration = syn::Ident::new(&decoration, ml.path.span());
                             let tokens = ml.tokens.clone();
@@ -668,6 +970,7 @@ pub fn attributes(_attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStr
                             (ident, field.ty.clone())
                         })
                         .collect();
+                    let struct_name = s.ident.clone();
                     for (i, field) in s.fields.iter_mut().enumerate() {
                         let prev = &idents[0..=i];
                         let refine: Option<(&mut Attribute, Expr)> =
@@ -684,7 +987,7 @@ pub fn attributes(_attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStr
                                 .iter()
                                 .map(|(name, ty)| quote! {#name: #ty, })
                                 .collect();
-                            let uid = ItemUid::fresh();
+                            let uid = item_uid("refine", quote! {#struct_name #binders #refine});
                             let uid_attr = AttrPayload::Uid(uid.clone());
                             let assoc_attr = AttrPayload::AssociatedItem {
                                 role: AssociationRole::Refine,
@@ -712,12 +1015,149 @@ pub fn attributes(_attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStr
         }
     }
 
-    let mut v = AttrVisitor::default();
+    let mut v = AttrVisitor {
+        extra_items: vec![],
+        ghosts: ghost::Ghosts::new(false, ghost::ghost_fns(&item)),
+    };
     let mut item = item;
     v.visit_item_mut(&mut item);
     let extra_items = v.extra_items;
+    let errors = v.ghosts.errors.map(|errors| errors.into_compile_error());
 
-    quote! { #item #(#extra_items)* }.into()
+    quote! { #errors #item #(#extra_items)* }.into()
+}
+
+/// Tests the lemma or the contract of a function on sampled inputs,
+/// to find false lemmas and wrong contracts before proving them. This
+/// generates a `#[test]` function `<NAME>_property_test`, which samples
+/// the inputs of the function satisfying its precondition
+/// (`#[requires(...)]`), and evaluates on them:
+///  - the statement of the function if it is a lemma (`#[lemma]`);
+///  - otherwise, its postcondition (`#[ensures(...)]`) on its result,
+///    or just whether it runs without panicking.
+///
+/// This attribute must be placed above the `lemma`, `requires` and
+/// `ensures` attributes of the function. It applies to functions with
+/// no generics and no `self` input, in a module (not in an `impl`).
+/// Inputs are sampled with the trait `hax_lib::testing::Arbitrary`,
+/// implemented for integers, `bool`, tuples, arrays, `Option`s and,
+/// with the `alloc` feature of `hax-lib`, `Vec`s; a `&[T]` input is
+/// sampled as a `Vec<T>`. Inputs and results must implement `Clone`
+/// and `Debug`.
+///
+/// The number of tested inputs is 256, or `N` with
+/// `#[property_test(cases = N)]`. The inputs are sampled
+/// deterministically, favoring edge cases such as `0` and the bounds
+/// of integer types. The test fails with a counterexample if the
+/// property does not hold, or if no input satisfying the
+/// precondition was sampled.
+///
+/// Note that propositions are evaluated as in Rust. Mathematical
+/// integers (`Int`) are only evaluated with the `runtime-contracts`
+/// feature of `hax-lib`, as `i128`s whose overflows panic. Quantifiers
+/// (`forall`, `exists`), `eq`, `ne` and backend-specific propositions
+/// (e.g. `fstar!`) cannot be evaluated: the test of a property using
+/// them is inconclusive, and ignored.
+///
+/// This attribute has no effect on extraction.
+///
+/// # Example
+///
+/// ```
+/// use hax_lib::*;
+/// #[property_test]
+/// #[requires(x < 100)]
+/// #[ensures(|result| result >= x)]
+/// pub fn double(x: u32) -> u32 {
+///     x * 2
+/// }
+///
+/// #[property_test(cases = 1000)]
+/// #[lemma]
+/// pub fn double_is_even(x: u32) -> Proof<{ x >= 100 || double(x) % 2 == 0 }> {}
+/// ```
+#[proc_macro_error]
+#[proc_macro_attribute]
+pub fn property_test(attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStream {
+    let _ = attr;
+    item
+}
+
+/// Mark a function as ghost: a function used only in ghost code. Its
+/// parameters are implicitly ghost. Ghost code is kept in extraction,
+/// and erased otherwise (rustc still type-checks it): it is never
+/// executed.
+///
+/// Inside an item annotated with [`macro@attributes`], `#[ghost]` also
+/// marks the following:
+///  - a parameter `#[ghost] x: T` of a function, which callers pass as
+///    `ghost_expr!(...)`;
+///  - a `let` binding `#[ghost] let x = ...;`, e.g. a specification-only
+///    accumulator updated in a loop;
+///  - a statement `#[ghost] x = ...;` or `#[ghost] {...}`, e.g. an
+///    update of such an accumulator, or a lemma call.
+///
+/// Ghost variables can only be used in ghost code, in specifications
+/// (`loop_invariant!`, `assert_prop!`, `assume!`, `fstar!`...), and in
+/// `ghost_expr!`. Ghost code cannot modify non-ghost variables, nor
+/// return from the function. Calls to the ghost functions of the item
+/// from non-ghost code are rejected.
+///
+/// Outside of hax, a ghost variable of type `T` has type
+/// `hax_lib::Ghost<T>`, which carries no value: run-time checks
+/// (e.g. [`macro@check_loops`]) cannot mention ghost variables. A ghost
+/// function returning a `T` returns a `hax_lib::Ghost<T>` and its body
+/// is never executed, so that no code can use its result, except for
+/// the ghost code of the item defining it (annotated with
+/// [`macro@attributes`]), in which its calls have type `T`.
+///
+/// # Example
+///
+/// ```
+/// use hax_lib::*;
+/// #[ghost]
+/// fn triangle(n: u32) -> u64 {
+///     (n as u64) * (n as u64 + 1) / 2
+/// }
+///
+/// #[attributes]
+/// fn sum(v: &[u32]) -> u64 {
+///     let mut s: u64 = 0;
+///     #[ghost]
+///     let mut steps: u32 = 0;
+///     for x in v {
+///         loop_invariant!(|_: usize| steps as usize <= v.len());
+///         s += *x as u64;
+///         #[ghost]
+///         steps += 1;
+///     }
+///     assume!(steps as usize == v.len());
+///     s
+/// }
+/// ```
+#[proc_macro_error]
+#[proc_macro_attribute]
+pub fn ghost(attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStream {
+    let _ = parse_macro_input!(attr as parse::Nothing);
+    let mut item: ItemFn = parse_macro_input!(item);
+    let mut ghosts = ghost::Ghosts::new(false, HashSet::new());
+    ghosts.function(true, &mut item.sig, Some(&mut item.block));
+    let errors = ghosts.errors.map(|errors| errors.into_compile_error());
+    quote! {
+        #errors
+        #item
+    }
+    .into()
+}
+
+/// A ghost expression, e.g. the argument of a ghost parameter: see
+/// [`macro@ghost`]. Outside of hax, `ghost_expr!(e)` is never
+/// evaluated and has type `hax_lib::Ghost<T>`, where `T` is the type
+/// of `e`.
+#[proc_macro]
+pub fn ghost_expr(payload: pm::TokenStream) -> pm::TokenStream {
+    let payload: TokenStream = payload.into();
+    quote! {(#payload)}.into()
 }
 
 /// Mark an item opaque: the extraction will assume the
@@ -1023,17 +1463,27 @@ make_quoting_proc_macro!(fstar coq proverif);
 /// that contains a value of type `T` and a proof that this value
 /// satisfies the formula `f`.
 ///
-/// In debug mode, the refinement will be checked at run-time. This
-/// requires the base type `T` to implement `Clone`. Pass a first
-/// parameter `no_debug_runtime_check` to disable this behavior.
+/// In debug mode, the refinement will be checked at run-time when the
+/// base type `T` implements `Clone`. Pass a first parameter
+/// `no_debug_runtime_check` to disable this behavior.
+///
+/// Values can also be constructed with `try_new` (or `TryFrom`), which
+/// always checks the refinement at run-time and fails with a
+/// [`hax_lib::RefinementError`] when the value does not satisfy it.
+/// With the parameters `debug` and `display` (e.g.
+/// `#[hax_lib::refinement_type(debug, display, |x| x > 0)]`), `Debug`
+/// and `Display` are implemented by the ones of `T`. With the `serde`
+/// feature of `hax-lib`, the refinement type is serialized as `T`,
+/// and deserialization checks the refinement.
 ///
 /// When extracted via hax, this is interpreted in the backend as a
 /// refinement type: the use of such a type yields static proof
 /// obligations.
 #[proc_macro_error]
 #[proc_macro_attribute]
-pub fn refinement_type(mut attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStream {
+pub fn refinement_type(attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStream {
     let mut item = parse_macro_input!(item as syn::ItemStruct);
+    let args = parse_macro_input!(attr as refinement::RefinementTypeArgs);
 
     let syn::Fields::Unnamed(fields) = &item.fields else {
         proc_macro_error2::abort!(
@@ -1042,41 +1492,13 @@ pub fn refinement_type(mut attr: pm::TokenStream, item: pm::TokenStream) -> pm::
         );
     };
     let paren_token = fields.paren_token;
-    let fields = fields.unnamed.iter().collect::<Vec<_>>();
-    let [field] = &fields[..] else {
-        proc_macro_error2::abort!(
-            item.generics.span(),
-            "Expected a newtype (a struct with one unnamed field), got {} fields",
-            fields.len()
-        );
+    let inner_ty = match refinement::newtype_inner_type(&item) {
+        Ok(inner_ty) => inner_ty.clone(),
+        Err(err) => proc_macro_error2::abort!(err.span(), "{}", err),
     };
-    if !matches!(field.vis, syn::Visibility::Inherited) {
-        proc_macro_error2::abort!(field.vis.span(), "This field was expected to be private");
-    }
-
-    let no_debug_assert = {
-        let mut tokens = attr.clone().into_iter();
-        if let (Some(pm::TokenTree::Ident(ident)), Some(pm::TokenTree::Punct(comma))) =
-            (tokens.next(), tokens.next())
-        {
-            if ident.to_string() != "no_debug_runtime_check" {
-                proc_macro_error2::abort!(ident.span(), "Expected 'no_debug_runtime_check'");
-            }
-            if comma.as_char() != ',' {
-                proc_macro_error2::abort!(ident.span(), "Expected a comma");
-            }
-            attr = pm::TokenStream::from_iter(tokens);
-            true
-        } else {
-            false
-        }
-    };
-
-    let ExprClosure1 {
-        arg: ret_binder,
-        body: phi,
-    } = parse_macro_input!(attr);
 
+    let ret_binder = args.binder.clone();
+    let phi = args.invariant.clone();
     let kind = FnDecorationKind::Ensures {
         ret_binder: ret_binder.clone(),
     };
@@ -1091,40 +1513,18 @@ pub fn refinement_type(mut attr: pm::TokenStream, item: pm::TokenStream) -> pm::
         generics: item.generics.clone(),
         paren_token,
         inputs: syn::punctuated::Punctuated::new(),
-        output: syn::ReturnType::Type(parse_quote! {->}, Box::new(field.ty.clone())),
+        output: syn::ReturnType::Type(parse_quote! {->}, Box::new(inner_ty.clone())),
     };
-    let ident = &item.ident;
-    let generics = &item.generics;
+    let ident = item.ident.clone();
     let vis = item.vis.clone();
-    let generics_args: syn::punctuated::Punctuated<_, syn::token::Comma> = item
-        .generics
-        .params
-        .iter()
-        .map(|g| match g {
-            syn::GenericParam::Lifetime(p) => {
-                let i = &p.lifetime;
-                quote! { #i }
-            }
-            syn::GenericParam::Type(p) => {
-                let i = &p.ident;
-                quote! { #i }
-            }
-            syn::GenericParam::Const(p) => {
-                let i = &p.ident;
-                quote! { #i }
-            }
-        })
-        .collect();
-    let inner_ty = &field.ty;
-    let (refinement_item, refinement_attr) = make_fn_decoration(phi.clone(), sig, kind, None, None);
+    let (refinement_item, refinement_attr) = make_fn_decoration(phi, sig, kind, None, None);
     let module_ident = syn::Ident::new(
         &format!("hax__autogenerated_refinement__{}", ident),
         ident.span(),
     );
 
     item.vis = parse_quote! {pub};
-    let debug_assert =
-        no_debug_assert.then_some(quote! {::core::debug_assert!(Self::invariant(x.clone()));});
+    let runtime_impls = refinement::runtime_impls(&item, &inner_ty, &args);
     let newtype_as_ref_attr = AttrPayload::NewtypeAsRefinement;
     quote! {
         #[allow(non_snake_case)]
@@ -1138,44 +1538,92 @@ pub fn refinement_type(mut attr: pm::TokenStream, item: pm::TokenStream) -> pm::
             #refinement_attr
             #item
 
-            #[::hax_lib::exclude]
-            impl #generics ::hax_lib::Refinement for #ident <#generics_args> {
-
-                type InnerType = #inner_ty;
+            #runtime_impls
+        }
+        #vis use #module_ident::#ident;
 
-                fn new(x: Self::InnerType) -> Self {
-                    #debug_assert
-                    Self(x)
-                }
-                fn get(self) -> Self::InnerType {
-                    self.0
-                }
-                fn get_mut(&mut self) -> &mut Self::InnerType {
-                    &mut self.0
-                }
-                fn invariant(#ret_binder: Self::InnerType) -> ::hax_lib::Prop {
-                    ::hax_lib::Prop::from(#phi)
-                }
-            }
+    }
+    .into()
+}
 
-            #[::hax_lib::exclude]
-            impl #generics ::std::ops::Deref for #ident <#generics_args> {
-                type Target = #inner_ty;
-                fn deref(&self) -> &Self::Target {
-                    &self.0
-                }
-            }
+/// Marks a `struct` or an `enum` with an invariant: a `Prop`
+/// proposition that all the values of the type satisfy. This macro
+/// takes one argument: a closure `|self_| ...` whose argument is a
+/// reference to a value of the type.
+///
+/// For example, the following type defines intervals.
+///
+/// ```
+/// #[hax_lib::invariant(|self_| self_.lo <= self_.hi)]
+/// pub struct Interval {
+///     lo: u64,
+///     hi: u64,
+/// }
+///
+/// #[hax_lib::invariant(|self_| match self_ {
+///     Shape::Circle { radius } => *radius > 0,
+///     Shape::Rectangle { width, height } => *width > 0 && *height > 0,
+/// })]
+/// pub enum Shape {
+///     Circle { radius: u32 },
+///     Rectangle { width: u32, height: u32 },
+/// }
+/// ```
+///
+/// This macro will generate an implementation of the
+/// [`hax_lib::Invariant`] trait, whose smart constructors `checked`
+/// and `try_checked` check the invariant of a value (e.g.
+/// `Interval { lo, hi }.checked()`). In debug mode, `checked` checks
+/// the invariant at run-time: pass a first parameter
+/// `no_debug_runtime_check` to disable this behavior. `try_checked`
+/// always checks the invariant at run-time and fails with a
+/// [`hax_lib::RefinementError`] when the value does not satisfy it.
+///
+/// When extracted via hax, the type is interpreted in the backend as
+/// a refinement of the type by its invariant: building a value of the
+/// type, and thus returning from a function that takes a `&mut` to
+/// such a value (e.g. a `&mut self` method), yields a proof obligation
+/// that the invariant holds. In exchange, the invariant can be
+/// assumed on any value of the type. For now, this is supported by
+/// the F* backend, and only on non-recursive types.
+#[proc_macro_error]
+#[proc_macro_attribute]
+pub fn invariant(attr: pm::TokenStream, item: pm::TokenStream) -> pm::TokenStream {
+    let item = parse_macro_input!(item as Item);
+    let args = parse_macro_input!(attr as refinement::RefinementTypeArgs);
+    let (ident, generics) = match refinement::invariant_target(&item) {
+        Ok(target) => target,
+        Err(err) => proc_macro_error2::abort!(err.span(), "{}", err),
+    };
+    let invariant_impl = refinement::invariant_impl(ident, generics, &args);
 
-            #[::hax_lib::exclude]
-            impl #generics ::hax_lib::RefineAs<#ident <#generics_args>> for #inner_ty {
-                fn into_checked(self) -> #ident <#generics_args> {
-                    use ::hax_lib::Refinement;
-                    #ident::new(self)
-                }
+    let binder = match &args.binder {
+        Pat::Type(PatType { pat, .. }) => &**pat,
+        binder => binder,
+    };
+    let phi = &args.invariant;
+    let uid = item_uid("invariant", quote! {#ident #binder #phi});
+    let uid_attr = AttrPayload::Uid(uid.clone());
+    let assoc_attr = AttrPayload::AssociatedItem {
+        role: AssociationRole::Refine,
+        item: uid,
+    };
+    let status_attr = &AttrPayload::ItemStatus(ItemStatus::Included { late_skip: true });
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        #[cfg(#HaxCfgOptionName)]
+        #status_attr
+        const _: () = {
+            #HaxQuantifiers
+            #uid_attr
+            #status_attr
+            fn invariant #impl_generics (#binder: &#ident #ty_generics) -> ::hax_lib::Prop #where_clause {
+                ::hax_lib::Prop::from(#phi)
             }
-        }
-        #vis use #module_ident::#ident;
-
+        };
+        #assoc_attr
+        #item
+        #invariant_impl
     }
     .into()
 }
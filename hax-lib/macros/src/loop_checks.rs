@@ -0,0 +1,340 @@
+//! Run-time checks of loop specifications, for the non-hax version of
+//! `check_loops`: the `loop_invariant!` and `loop_decreases!` calls at
+//! the start of the loops of a function are turned into checks
+//! evaluated at the start of each iteration.
+
+use crate::hax_paths::expects_hax_path;
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, quote_spanned};
+use syn::visit_mut::{self, VisitMut};
+use syn::*;
+
+/// Rewrites the loops of a function body, collecting the errors about
+/// loop specifications that cannot be checked.
+#[derive(Default)]
+pub struct CheckLoops {
+    pub errors: Option<Error>,
+}
+
+impl CheckLoops {
+    fn error(&mut self, span: Span, message: &str) {
+        let error = Error::new(span, message);
+        match &mut self.errors {
+            Some(errors) => errors.combine(error),
+            None => self.errors = Some(error),
+        }
+    }
+}
+
+/// The name of the variable holding the measure of the previous
+/// iteration of a loop.
+fn measure_var() -> Ident {
+    Ident::new("__hax_loop_measure", Span::call_site())
+}
+
+/// The name of the variable holding the number of previous iterations
+/// of a loop.
+fn iterations_var() -> Ident {
+    Ident::new("__hax_loop_iterations", Span::call_site())
+}
+
+/// The name of the variable holding the items consumed by the
+/// previous iterations of a loop.
+fn items_var() -> Ident {
+    Ident::new("__hax_loop_items", Span::call_site())
+}
+
+/// The name of the variable bound to the item of an iteration.
+fn item_var() -> Ident {
+    Ident::new("__hax_loop_item", Span::call_site())
+}
+
+/// The argument of a `loop_invariant!` or `loop_decreases!` statement.
+fn loop_spec(stmt: &Stmt) -> Option<(String, &Macro)> {
+    let mac = match stmt {
+        Stmt::Macro(StmtMacro { mac, .. }) => mac,
+        Stmt::Expr(Expr::Macro(ExprMacro { mac, .. }), _) => mac,
+        _ => return None,
+    };
+    let kind = expects_hax_path(&["loop_invariant", "loop_decreases"], &mac.path).ok()??;
+    Some((kind, mac))
+}
+
+/// The variable bound to the index of a `for` loop: the pattern of
+/// the loop itself, or the first component of an `enumerate` pattern.
+fn loop_index(pat: &Pat) -> Option<&Ident> {
+    match pat {
+        Pat::Ident(pat) => Some(&pat.ident),
+        Pat::Type(pat) => loop_index(&pat.pat),
+        Pat::Tuple(pat) => match pat.elems.first()? {
+            Pat::Ident(pat) => Some(&pat.ident),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Tells whether the index of a `for` loop over `it` is given by the
+/// pattern of the loop, i.e. whether `it` is a range, a range with a
+/// step, or an enumeration. The index of the other loops is their
+/// number of iterations.
+fn indexed_by_pattern(it: &Expr) -> bool {
+    match it {
+        Expr::Paren(e) => indexed_by_pattern(&e.expr),
+        Expr::Range(_) => true,
+        Expr::MethodCall(e) if e.method == "enumerate" => true,
+        Expr::MethodCall(e) if e.method == "step_by" => {
+            matches!(&*e.receiver, Expr::Range(_))
+                || matches!(&*e.receiver, Expr::Paren(r) if matches!(&*r.expr, Expr::Range(_)))
+        }
+        _ => false,
+    }
+}
+
+/// The pattern of the item of a `while let Some(item) = ...` loop.
+fn while_let_item(pat: &mut Pat) -> Option<&mut Pat> {
+    match pat {
+        Pat::TupleStruct(pat) if pat.path.is_ident("Some") && pat.elems.len() == 1 => {
+            pat.elems.first_mut()
+        }
+        _ => None,
+    }
+}
+
+/// The kinds of loops, as far as their invariants are concerned.
+enum Shape<'a> {
+    /// A `for` loop, whose invariant closures take the index given by
+    /// the pattern `pat` if `indexed_by_pattern` holds.
+    For {
+        pat: &'a Pat,
+        indexed_by_pattern: bool,
+    },
+    /// A `while let` loop, whose item is bound by `item`, if it is of
+    /// the form `while let Some(item) = ...`.
+    WhileLet { item: Option<&'a Pat> },
+    /// A `while` loop or a `loop` loop.
+    Other,
+}
+
+impl VisitMut for CheckLoops {
+    /// Nested items are not part of the function.
+    fn visit_item_mut(&mut self, _item: &mut Item) {}
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        visit_mut::visit_expr_mut(self, expr);
+        let (keyword, shape, body) = match &mut *expr {
+            Expr::ForLoop(e) => (
+                e.for_token.span,
+                Shape::For {
+                    pat: &e.pat,
+                    indexed_by_pattern: indexed_by_pattern(&e.expr),
+                },
+                &mut e.body,
+            ),
+            Expr::While(e) => match &mut *e.cond {
+                Expr::Let(cond) => (
+                    e.while_token.span,
+                    Shape::WhileLet {
+                        item: while_let_item(&mut cond.pat).map(|pat| &*pat),
+                    },
+                    &mut e.body,
+                ),
+                _ => (e.while_token.span, Shape::Other, &mut e.body),
+            },
+            Expr::Loop(e) => (e.loop_token.span, Shape::Other, &mut e.body),
+            _ => return,
+        };
+        let location = quote_spanned! {keyword=>
+            ::core::concat!(::core::file!(), ":", ::core::line!(), ":", ::core::column!())
+        };
+        let quantifiers = quote! {
+            #[allow(unused_imports)]
+            use ::hax_lib::fstar::prop as fstar;
+            #[allow(unused_imports)]
+            use ::hax_lib::coq::prop as coq;
+            #[allow(unused_imports)]
+            use ::hax_lib::proverif::prop as proverif;
+        };
+        let measure = measure_var();
+        let iterations = iterations_var();
+        let items = items_var();
+
+        let mut checks: Vec<Stmt> = vec![];
+        let mut has_measure = false;
+        // Invariants taking only the index of a loop which is not
+        // indexed by its pattern count its iterations, the others
+        // record its items as well.
+        let mut has_iterations = false;
+        let mut has_items = false;
+        let specs = body
+            .stmts
+            .iter()
+            .take_while(|stmt| loop_spec(stmt).is_some())
+            .count();
+        for stmt in body.stmts.drain(..specs) {
+            let (kind, mac) = loop_spec(&stmt).expect("`stmt` is a loop specification");
+            let spec: Expr = match mac.parse_body() {
+                Ok(spec) => spec,
+                Err(error) => {
+                    self.error(error.span(), &error.to_string());
+                    continue;
+                }
+            };
+            let closure = match (kind.as_str(), &spec) {
+                ("loop_decreases", _) => {
+                    has_measure = true;
+                    checks.push(parse_quote! {
+                        #[cfg(debug_assertions)]
+                        { #measure.check(#location, { #quantifiers #spec }); }
+                    });
+                    continue;
+                }
+                (_, Expr::Closure(closure)) => closure,
+                _ => {
+                    checks.push(parse_quote! {
+                        #[cfg(debug_assertions)]
+                        {
+                            ::hax_lib::_internal_check_loop_invariant(#location, {
+                                #quantifiers
+                                ::hax_lib::Prop::from(#spec)
+                            });
+                        }
+                    });
+                    continue;
+                }
+            };
+            let span = closure.or1_token.span;
+            let arity = closure.inputs.len();
+            let check = match (&shape, arity) {
+                (_, 0) | (_, 3..) => {
+                    self.error(span, "A loop invariant closure takes the index of the loop, and optionally the sequence of the items consumed so far");
+                    continue;
+                }
+                (Shape::Other, _) => {
+                    self.error(
+                        span,
+                        "Invariants of `while` loops and `loop` loops are propositions, not closures",
+                    );
+                    continue;
+                }
+                (Shape::WhileLet { item: None }, _) => {
+                    self.error(
+                        span,
+                        "The items of this loop cannot be found: expected a `while let Some(item) = ...` loop",
+                    );
+                    continue;
+                }
+                (
+                    Shape::For {
+                        pat,
+                        indexed_by_pattern: true,
+                    },
+                    1,
+                ) => match loop_index(pat) {
+                    Some(index) => quote! {
+                        ::hax_lib::_internal_check_loop_invariant(#location, {
+                            #quantifiers
+                            ::core::convert::Into::<::hax_lib::Prop>::into((#spec)(#index))
+                        });
+                    },
+                    None => {
+                        self.error(
+                            span,
+                            "The index of this loop cannot be found: expected the pattern of the loop to be a variable or a tuple whose first component is a variable",
+                        );
+                        continue;
+                    }
+                },
+                (_, 1) => {
+                    has_iterations = true;
+                    quote! {
+                        #iterations.check(#location, |index| {
+                            #quantifiers
+                            (#spec)(index)
+                        });
+                    }
+                }
+                (_, _) => {
+                    has_items = true;
+                    quote! {
+                        #items.check(#location, |index, prefix| {
+                            #quantifiers
+                            (#spec)(index, prefix)
+                        });
+                    }
+                }
+            };
+            checks.push(parse_quote! {
+                #[cfg(debug_assertions)]
+                { #check }
+            });
+        }
+
+        if has_iterations {
+            checks.push(parse_quote! {
+                #[cfg(debug_assertions)]
+                { #iterations.step(); }
+            });
+        }
+        if has_items {
+            // Bind the items of the loop to a variable, to record them
+            // before destructuring them with the pattern of the loop.
+            let item = item_var();
+            let item_pat: Pat = parse_quote!(#item);
+            let pat = match expr {
+                Expr::ForLoop(e) => std::mem::replace(&mut *e.pat, item_pat),
+                Expr::While(e) => match &mut *e.cond {
+                    Expr::Let(cond) => std::mem::replace(
+                        while_let_item(&mut cond.pat).expect("the items of the loop were found"),
+                        item_pat,
+                    ),
+                    _ => unreachable!("the loop is a `while let` loop"),
+                },
+                _ => unreachable!("the loop has items"),
+            };
+            checks.push(parse_quote! {
+                #[cfg(debug_assertions)]
+                { #items.push(&#item); }
+            });
+            checks.push(parse_quote! {
+                let #pat = #item;
+            });
+        }
+        let body = match expr {
+            Expr::ForLoop(e) => &mut e.body,
+            Expr::While(e) => &mut e.body,
+            Expr::Loop(e) => &mut e.body,
+            _ => unreachable!("`expr` is a loop"),
+        };
+        body.stmts.splice(0..0, checks);
+
+        if has_measure || has_iterations || has_items {
+            let mut state: Vec<Stmt> = vec![];
+            if has_measure {
+                state.push(parse_quote! {
+                    #[cfg(debug_assertions)]
+                    let mut #measure = ::hax_lib::LoopMeasure::default();
+                });
+            }
+            if has_iterations {
+                state.push(parse_quote! {
+                    #[cfg(debug_assertions)]
+                    let mut #iterations = ::hax_lib::LoopIterations::default();
+                });
+            }
+            if has_items {
+                state.push(parse_quote! {
+                    #[cfg(debug_assertions)]
+                    let mut #items = ::hax_lib::LoopItems::default();
+                });
+            }
+            let looped = std::mem::replace(expr, Expr::Verbatim(TokenStream::new()));
+            *expr = parse_quote! {
+                {
+                    #(#state)*
+                    #looped
+                }
+            };
+        }
+    }
+}
@@ -0,0 +1,344 @@
+//! Property tests of lemmas and contracts, for the non-hax version of
+//! `property_test`: a test sampling the inputs of a function that
+//! satisfy its precondition, and evaluating its lemma statement or
+//! postcondition on them.
+
+use crate::hax_paths::expects_hax_path;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
+use syn::visit_mut::{self, VisitMut};
+use syn::*;
+
+/// The arguments of `#[property_test(...)]`: optionally, the number
+/// of cases to test, as `cases = N`.
+pub struct PropertyTestArgs {
+    cases: Option<LitInt>,
+}
+
+impl Parse for PropertyTestArgs {
+    fn parse(input: ParseStream) -> Result<Self> {
+        if input.is_empty() {
+            return Ok(Self { cases: None });
+        }
+        let key: Ident = input.parse()?;
+        if key != "cases" {
+            return Err(Error::new(key.span(), "Expected `cases = N`"));
+        }
+        input.parse::<Token![=]>()?;
+        Ok(Self {
+            cases: Some(input.parse()?),
+        })
+    }
+}
+
+/// The statement of a lemma returning `Proof<{STATEMENT}>`.
+fn lemma_statement(output: &ReturnType) -> Option<Expr> {
+    let ReturnType::Type(_, ty) = output else {
+        return None;
+    };
+    let Type::Path(TypePath { qself: None, path }) = &**ty else {
+        return None;
+    };
+    let [segment] = path.segments.iter().collect::<Vec<_>>()[..] else {
+        return None;
+    };
+    if segment.ident != "Proof" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.iter().collect::<Vec<_>>()[..] {
+        [GenericArgument::Const(statement)] => Some(statement.clone()),
+        _ => None,
+    }
+}
+
+/// Rewrites `future(x)` into `&x_future`, as in postconditions.
+struct RewriteFuture;
+
+impl VisitMut for RewriteFuture {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        visit_mut::visit_expr_mut(self, expr);
+        if let Expr::Call(call) = expr {
+            if let (Expr::Path(f), [Expr::Path(arg)]) =
+                (&*call.func, &call.args.iter().collect::<Vec<_>>()[..])
+            {
+                if let (true, Some(arg)) = (f.path.is_ident("future"), arg.path.get_ident()) {
+                    let future = future_var(arg);
+                    *expr = parse_quote! {(&#future)};
+                }
+            }
+        }
+    }
+}
+
+fn future_var(input: &Ident) -> Ident {
+    format_ident!("__hax_future_{}", input)
+}
+
+/// Converts a specification into a boolean.
+fn holds(spec: &Expr) -> TokenStream {
+    quote! {
+        ::core::convert::Into::<::hax_lib::Prop>::into({
+            #[allow(unused_imports)]
+            use ::hax_lib::fstar::prop as fstar;
+            #[allow(unused_imports)]
+            use ::hax_lib::coq::prop as coq;
+            #[allow(unused_imports)]
+            use ::hax_lib::proverif::prop as proverif;
+            #spec
+        })._internal_to_bool()
+    }
+}
+
+/// Whether `path` is the `eq` or `ne` constructor of `hax_lib`
+/// propositions, e.g. `eq`, `hax_lib::eq` or
+/// `::hax_lib::prop::constructors::ne`.
+fn is_prop_equality(path: &Path) -> bool {
+    let segments: Vec<_> = path
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect();
+    let segments: Vec<_> = segments.iter().map(String::as_str).collect();
+    let segments = match segments.as_slice() {
+        ["hax_lib", rest @ ..] => rest,
+        rest if path.leading_colon.is_none() => rest,
+        _ => return false,
+    };
+    matches!(
+        segments,
+        ["eq" | "ne"] | ["prop", "eq" | "ne"] | ["prop", "constructors", "eq" | "ne"]
+    )
+}
+
+/// The first construct of a specification that cannot be evaluated in
+/// Rust, if any: `forall`, `exists` and the `eq` and `ne` propositions
+/// are always true, and backend-specific propositions are not
+/// evaluated.
+fn unevaluable(spec: &Expr) -> Option<Ident> {
+    #[derive(Default)]
+    struct Find(Option<Ident>);
+    impl<'a> syn::visit::Visit<'a> for Find {
+        fn visit_expr_call(&mut self, call: &'a ExprCall) {
+            if let Expr::Path(ExprPath { path, .. }) = &*call.func {
+                let f = &path.segments.last().expect("paths are not empty").ident;
+                if f == "forall" || f == "exists" || is_prop_equality(path) {
+                    self.0.get_or_insert(f.clone());
+                }
+            }
+            syn::visit::visit_expr_call(self, call);
+        }
+        fn visit_macro(&mut self, mac: &'a Macro) {
+            let name = &mac.path.segments.last().expect("paths are not empty").ident;
+            if ["fstar", "coq", "proverif", "prop"]
+                .iter()
+                .any(|backend| name == backend)
+            {
+                self.0.get_or_insert(name.clone());
+            }
+        }
+    }
+    let mut find = Find::default();
+    syn::visit::Visit::visit_expr(&mut find, spec);
+    find.0
+}
+
+/// A test of the lemma or of the contract of the function `item`.
+pub fn property_test(test_args: PropertyTestArgs, item: &ItemFn) -> Result<TokenStream> {
+    let sig = &item.sig;
+    let name = &sig.ident;
+    if !sig.generics.params.is_empty() {
+        return Err(Error::new(
+            sig.generics.span(),
+            "The inputs of generic functions cannot be sampled",
+        ));
+    }
+
+    let mut lemma = false;
+    let mut requires: Vec<Expr> = vec![];
+    let mut ensures: Vec<(Pat, Expr)> = vec![];
+    for attr in &item.attrs {
+        let Ok(Some(kind)) = expects_hax_path(&["lemma", "requires", "ensures"], attr.path())
+        else {
+            continue;
+        };
+        match kind.as_str() {
+            "lemma" => lemma = true,
+            "requires" => requires.push(attr.parse_args()?),
+            _ => {
+                let closure: ExprClosure = attr.parse_args()?;
+                let [binder] = &closure.inputs.iter().collect::<Vec<_>>()[..] else {
+                    return Err(Error::new(
+                        closure.inputs.span(),
+                        "Expected a closure with one argument",
+                    ));
+                };
+                ensures.push(((*binder).clone(), (*closure.body).clone()));
+            }
+        }
+    }
+    if !lemma && requires.is_empty() && ensures.is_empty() {
+        return Err(Error::new(
+            name.span(),
+            "`property_test` tests a lemma or a contract: expected `#[lemma]`, `#[requires(...)]` or `#[ensures(...)]` below `#[property_test]`",
+        ));
+    }
+
+    let mut types: Vec<Type> = vec![];
+    let mut bindings: Vec<Stmt> = vec![];
+    let mut args: Vec<TokenStream> = vec![];
+    for (i, input) in sig.inputs.iter().enumerate() {
+        let FnArg::Typed(PatType { pat, ty, .. }) = input else {
+            return Err(Error::new(
+                input.span(),
+                "The inputs of methods cannot be sampled",
+            ));
+        };
+        let sample = format_ident!("__hax_input_{}", i);
+        match &**ty {
+            Type::Reference(TypeReference {
+                mutability: Some(_),
+                elem,
+                ..
+            }) => {
+                let Pat::Ident(PatIdent { ident, .. }) = &**pat else {
+                    return Err(Error::new(pat.span(), "Expected a variable"));
+                };
+                let future = future_var(ident);
+                types.push((**elem).clone());
+                bindings.push(parse_quote! {
+                    let mut #future: #elem = ::core::clone::Clone::clone(&#sample);
+                });
+                bindings.push(parse_quote! {
+                    let #ident: &#elem = &#sample;
+                });
+                args.push(quote! {&mut #future});
+            }
+            Type::Reference(TypeReference { elem, .. }) => {
+                let owned: Type = match &**elem {
+                    Type::Slice(TypeSlice { elem, .. }) => parse_quote! {::std::vec::Vec<#elem>},
+                    elem => elem.clone(),
+                };
+                types.push(owned);
+                bindings.push(parse_quote! {
+                    let #pat: #ty = &#sample;
+                });
+                args.push(quote! {&#sample});
+            }
+            Type::ImplTrait(_) => {
+                return Err(Error::new(
+                    ty.span(),
+                    "`impl Trait` inputs cannot be sampled",
+                ))
+            }
+            _ => {
+                types.push((**ty).clone());
+                bindings.push(parse_quote! {
+                    let #pat: #ty = ::core::clone::Clone::clone(&#sample);
+                });
+                args.push(quote! {::core::clone::Clone::clone(&#sample)});
+            }
+        }
+    }
+    let samples = (0..types.len()).map(|i| format_ident!("__hax_input_{}", i));
+
+    let statement =
+        if lemma {
+            Some(lemma_statement(&sig.output).ok_or_else(|| {
+                Error::new(sig.output.span(), "A lemma returns `Proof<{STATEMENT}>`")
+            })?)
+        } else {
+            None
+        };
+    // A property whose specification cannot be evaluated is
+    // inconclusive: its test is still type-checked, but ignored.
+    let ignore = requires
+        .iter()
+        .chain(&statement)
+        .chain(ensures.iter().map(|(_, phi)| phi))
+        .find_map(unevaluable)
+        .map(|construct| {
+            let reason = format!(
+                "inconclusive: the specification of `{name}` uses `{construct}`, which cannot be evaluated"
+            );
+            quote! {#[ignore = #reason]}
+        });
+
+    let requires = requires.iter().map(holds);
+    let property = if let Some(statement) = statement {
+        holds(&statement)
+    } else {
+        let ensures = ensures.into_iter().map(|(binder, mut phi)| {
+            RewriteFuture.visit_expr_mut(&mut phi);
+            let phi = holds(&phi);
+            quote! {{ let #binder = ::core::clone::Clone::clone(&__hax_output); #phi }}
+        });
+        quote! {{
+            let __hax_output = #name(#(#args),*);
+            true #(&& #ensures)*
+        }}
+    };
+    let cases = test_args
+        .cases
+        .map_or(quote! {256}, |cases| quote! {#cases});
+    let test = format_ident!("{}_property_test", name);
+    Ok(quote! {
+        #[cfg(test)]
+        #[test]
+        #ignore
+        #[allow(unused_braces, unused_mut, unused_variables)]
+        fn #test() {
+            ::hax_lib::testing::check(
+                ::core::concat!(::core::module_path!(), "::", ::core::stringify!(#name)),
+                #cases,
+                |(#(#samples,)*): (#(#types,)*)| {
+                    #(#bindings)*
+                    if !(true #(&& #requires)*) {
+                        return ::core::option::Option::None;
+                    }
+                    ::core::option::Option::Some(#property)
+                },
+            );
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unevaluable_construct(spec: &str) -> Option<String> {
+        unevaluable(&parse_str(spec).unwrap()).map(|construct| construct.to_string())
+    }
+
+    #[test]
+    fn prop_constructors_are_unevaluable() {
+        for (spec, construct) in [
+            ("forall(|y: u8| y <= x)", "forall"),
+            ("hax_lib::exists(|y: u8| y == x)", "exists"),
+            ("eq(x, y)", "eq"),
+            ("::hax_lib::eq(x, y)", "eq"),
+            ("hax_lib::prop::constructors::ne(x, y)", "ne"),
+            ("x < 3 && fstar!(\"True\")", "fstar"),
+        ] {
+            assert_eq!(unevaluable_construct(spec).as_deref(), Some(construct), "{spec}");
+        }
+    }
+
+    #[test]
+    fn equalities_of_values_are_evaluable() {
+        for spec in [
+            "x.eq(&y)",
+            "result.ne(&0) || x == 0",
+            "PartialEq::eq(&x, &y)",
+            "core::cmp::PartialEq::ne(&x, &y)",
+            "u8::eq(&x, &y)",
+        ] {
+            assert_eq!(unevaluable_construct(spec), None, "{spec}");
+        }
+    }
+}
@@ -0,0 +1,247 @@
+//! Purity checks of the `pure` attribute, shared by its hax and
+//! non-hax versions: a pure function has no `&mut` inputs, no mutable
+//! borrows, no `unsafe` code, no I/O and no loops other than `for`
+//! loops, may panic only under a precondition, and is recursive only
+//! with a termination measure.
+
+use crate::hax_paths::expects_hax_path;
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+use syn::*;
+
+/// Macros that panic on some (or all) of their inputs.
+const PANICKING_MACROS: &[&str] = &[
+    "assert",
+    "assert_eq",
+    "assert_ne",
+    "panic",
+    "todo",
+    "unimplemented",
+    "unreachable",
+];
+
+/// Macros that perform I/O.
+const IO_MACROS: &[&str] = &[
+    "dbg", "eprint", "eprintln", "print", "println", "write", "writeln",
+];
+
+/// Methods that panic on some of their inputs (e.g. `Option::unwrap`).
+const PANICKING_METHODS: &[&str] = &["expect", "unwrap"];
+
+/// Does the function have a `requires` or a `decreases` clause, as an
+/// attribute below `pure`?
+fn has_clause(attrs: &[Attribute], clause: &str) -> bool {
+    attrs
+        .iter()
+        .any(|attr| matches!(expects_hax_path(&[clause], attr.path()), Ok(Some(_))))
+}
+
+/// Does the type `ty` mention a `&mut` reference?
+fn has_mut_reference(ty: &Type) -> bool {
+    struct Finder(bool);
+    impl<'a> Visit<'a> for Finder {
+        fn visit_type_reference(&mut self, reference: &'a TypeReference) {
+            self.0 |= reference.mutability.is_some();
+            visit::visit_type_reference(self, reference);
+        }
+    }
+    let mut finder = Finder(false);
+    finder.visit_type(ty);
+    finder.0
+}
+
+struct Checker<'a> {
+    name: &'a Ident,
+    requires: bool,
+    decreases: bool,
+    errors: Vec<Error>,
+}
+
+impl Checker<'_> {
+    fn error(&mut self, span: proc_macro2::Span, message: impl std::fmt::Display) {
+        self.errors.push(Error::new(span, message))
+    }
+
+    fn may_panic(&mut self, span: proc_macro2::Span, what: impl std::fmt::Display) {
+        if !self.requires {
+            self.error(
+                span,
+                format!("{what} may panic: a pure function may panic only under a precondition (`requires`)"),
+            )
+        }
+    }
+
+    fn recursive_call(&mut self, span: proc_macro2::Span) {
+        if !self.decreases {
+            self.error(
+                span,
+                "A recursive pure function requires a termination measure (`decreases`)",
+            )
+        }
+    }
+
+    fn mac(&mut self, mac: &Macro) {
+        if let Some(name) = mac.path.segments.last() {
+            if PANICKING_MACROS.contains(&name.ident.to_string().as_str()) {
+                self.may_panic(mac.span(), format!("`{}!`", name.ident));
+            }
+            if IO_MACROS.contains(&name.ident.to_string().as_str()) {
+                self.error(
+                    mac.span(),
+                    format!("`{}!` performs I/O: a pure function cannot have side effects", name.ident),
+                );
+            }
+        }
+    }
+}
+
+impl<'a> Visit<'a> for Checker<'_> {
+    fn visit_expr(&mut self, expr: &'a Expr) {
+        match expr {
+            Expr::Reference(ExprReference {
+                mutability: Some(_),
+                ..
+            }) => self.error(expr.span(), "A pure function cannot borrow mutably"),
+            Expr::Loop(_) | Expr::While(_) => self.error(
+                expr.span(),
+                "A pure function must terminate: use a `for` loop, or a recursion with a termination measure (`decreases`)",
+            ),
+            Expr::Unsafe(_) => self.error(expr.span(), "A pure function cannot use `unsafe` code"),
+            Expr::Index(_) => self.may_panic(expr.span(), "Indexing"),
+            Expr::Binary(ExprBinary { op, .. }) => match op {
+                BinOp::Div(_) | BinOp::DivAssign(_) => self.may_panic(expr.span(), "Division"),
+                BinOp::Rem(_) | BinOp::RemAssign(_) => self.may_panic(expr.span(), "Remainder"),
+                _ => (),
+            },
+            Expr::Macro(ExprMacro { mac, .. }) => self.mac(mac),
+            Expr::MethodCall(call) => {
+                let method = call.method.to_string();
+                if PANICKING_METHODS.contains(&method.as_str()) {
+                    self.may_panic(call.method.span(), format!("`{method}`"));
+                }
+                let on_self = matches!(&*call.receiver, Expr::Path(path) if path.path.is_ident("self"));
+                if on_self && call.method == *self.name {
+                    self.recursive_call(expr.span());
+                }
+            }
+            Expr::Call(ExprCall { func, .. }) => {
+                if let Expr::Path(ExprPath { path, .. }) = &**func {
+                    let segments: Vec<_> = path.segments.iter().map(|s| &s.ident).collect();
+                    let recursive = match &segments[..] {
+                        [function] => *function == self.name,
+                        [self_ty, function] => *self_ty == "Self" && *function == self.name,
+                        _ => false,
+                    };
+                    if recursive {
+                        self.recursive_call(expr.span());
+                    }
+                }
+            }
+            _ => (),
+        }
+        visit::visit_expr(self, expr)
+    }
+
+    fn visit_stmt_macro(&mut self, stmt: &'a StmtMacro) {
+        self.mac(&stmt.mac);
+    }
+
+    // Nested items are not part of the function.
+    fn visit_item(&mut self, _item: &'a Item) {}
+}
+
+/// Checks that the function of signature `sig`, body `body` (if any)
+/// and attributes `attrs` is pure.
+fn check(attrs: &[Attribute], sig: &Signature, body: Option<&Block>) -> Result<()> {
+    let mut checker = Checker {
+        name: &sig.ident,
+        requires: has_clause(attrs, "requires"),
+        decreases: has_clause(attrs, "decreases"),
+        errors: vec![],
+    };
+    if let Some(asyncness) = sig.asyncness {
+        checker.error(asyncness.span, "A pure function cannot be `async`");
+    }
+    if let Some(unsafety) = sig.unsafety {
+        checker.error(unsafety.span, "A pure function cannot be `unsafe`");
+    }
+    for input in &sig.inputs {
+        let mut_input = match input {
+            FnArg::Receiver(receiver) => has_mut_reference(&receiver.ty),
+            FnArg::Typed(PatType { ty, .. }) => has_mut_reference(ty),
+        };
+        if mut_input {
+            checker.error(input.span(), "A pure function cannot take `&mut` inputs");
+        }
+    }
+    if let Some(body) = body {
+        checker.visit_block(body);
+    }
+    let mut errors = checker.errors.into_iter();
+    match errors.next() {
+        None => Ok(()),
+        Some(mut error) => {
+            errors.for_each(|other| error.combine(other));
+            Err(error)
+        }
+    }
+}
+
+/// Removes the `pure` attribute of a function, if any, telling whether
+/// there was one. In an `impl` or a `trait` marked with `attributes`,
+/// functions are checked before their clauses are rewritten.
+pub fn take_pure_attr(attrs: &mut Vec<Attribute>) -> bool {
+    let before = attrs.len();
+    attrs.retain(|attr| {
+        !(matches!(attr.meta, Meta::Path(_))
+            && matches!(expects_hax_path(&["pure"], attr.path()), Ok(Some(_))))
+    });
+    attrs.len() != before
+}
+
+/// Checks that `item`, a function with or without a body (e.g. in a
+/// trait), is pure.
+pub fn check_item(item: proc_macro2::TokenStream) -> Result<()> {
+    match parse2::<ImplItemFn>(item.clone()) {
+        Ok(fun) => check(&fun.attrs, &fun.sig, Some(&fun.block)),
+        Err(_) => {
+            let fun: TraitItemFn = parse2(item)?;
+            check(&fun.attrs, &fun.sig, fun.default.as_ref())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_error(item: &str) -> Option<String> {
+        check_item(parse_str(item).unwrap())
+            .err()
+            .map(|error| error.to_string())
+    }
+
+    #[test]
+    fn io_is_rejected() {
+        for (item, error) in [
+            ("fn f(x: u8) -> u8 { println!(\"{x}\"); x }", "`println!` performs I/O"),
+            ("fn f(x: u8) -> u8 { dbg!(x) }", "`dbg!` performs I/O"),
+        ] {
+            let message = check_error(item).unwrap();
+            assert!(message.starts_with(error), "{message}");
+        }
+    }
+
+    #[test]
+    fn divisions_require_a_precondition() {
+        for (item, error) in [
+            ("fn f(x: u8, y: u8) -> u8 { x / y }", "Division may panic"),
+            ("fn f(x: u8, y: u8) -> u8 { x % y }", "Remainder may panic"),
+        ] {
+            let message = check_error(item).unwrap();
+            assert!(message.starts_with(error), "{message}");
+        }
+        let item = "#[requires(y != 0)] fn f(x: u8, y: u8) -> u8 { x / y + x % y }";
+        assert_eq!(check_error(item), None);
+    }
+}
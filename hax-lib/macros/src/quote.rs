@@ -13,6 +13,7 @@
 //!  - `:`, the antiquotation is a type.
 
 use crate::prelude::*;
+use crate::utils::item_uid;
 
 /// Marker that indicates a place where a antiquotation will be inserted
 const SPLIT_MARK: &str = "SPLIT_QUOTE";
@@ -133,7 +134,10 @@ pub(super) fn item(
 ) -> pm::TokenStream {
     let expr = TokenStream::from(expression(InlineExprType::Unit, payload));
     let item = TokenStream::from(item);
-    let uid = ItemUid::fresh();
+    let uid = item_uid(
+        &format!("{kind:?}"),
+        quote! {#attribute_to_inject #expr #item},
+    );
     let uid_attr = AttrPayload::Uid(uid.clone());
     let assoc_attr = AttrPayload::AssociatedItem {
         role: AssociationRole::ItemQuote,
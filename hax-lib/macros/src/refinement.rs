@@ -0,0 +1,295 @@
+//! Run-time part of the `refinement_type` macro, shared by its hax
+//! and non-hax versions: the implementations of `Refinement`,
+//! `RefineAs`, `Deref`, `TryFrom`, optionally `Debug` and `Display`,
+//! and (with the `serde` feature of `hax-lib`) `Serialize` and
+//! `Deserialize`. Also the run-time part of the `invariant` macro: the
+//! implementation of `Invariant`.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
+use syn::*;
+
+/// The arguments of `#[refinement_type]`: optional flags (each
+/// followed by a comma) and a closure `|x| phi`. The flags are
+/// `no_debug_runtime_check`, and `debug` and `display`, which opt in
+/// to the implementations of `Debug` and `Display` by the ones of the
+/// inner type.
+pub struct RefinementTypeArgs {
+    pub debug_runtime_check: bool,
+    pub debug: bool,
+    pub display: bool,
+    pub binder: Pat,
+    pub invariant: Expr,
+}
+
+impl Parse for RefinementTypeArgs {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let (mut debug_runtime_check, mut debug, mut display) = (true, false, false);
+        while input.peek(Ident) && input.peek2(Token![,]) {
+            let flag: Ident = input.parse()?;
+            if flag == "no_debug_runtime_check" {
+                debug_runtime_check = false;
+            } else if flag == "debug" {
+                debug = true;
+            } else if flag == "display" {
+                display = true;
+            } else {
+                return Err(Error::new(
+                    flag.span(),
+                    "Expected 'no_debug_runtime_check', 'debug' or 'display'",
+                ));
+            }
+            input.parse::<Token![,]>()?;
+        }
+        let closure: ExprClosure = input.parse()?;
+        let [binder] = &closure.inputs.iter().collect::<Vec<_>>()[..] else {
+            return Err(Error::new(
+                closure.inputs.span(),
+                "Expected exactly one argument",
+            ));
+        };
+        Ok(Self {
+            debug_runtime_check,
+            debug,
+            display,
+            binder: (*binder).clone(),
+            invariant: *closure.body,
+        })
+    }
+}
+
+/// Expects `item` to be a newtype with a private field, returns the
+/// type of this field.
+pub fn newtype_inner_type(item: &ItemStruct) -> Result<&Type> {
+    let Fields::Unnamed(fields) = &item.fields else {
+        return Err(Error::new(
+            item.generics.span(),
+            "Expected a newtype (a struct with one unnamed field), got one or more named field",
+        ));
+    };
+    let fields = fields.unnamed.iter().collect::<Vec<_>>();
+    let [field] = &fields[..] else {
+        return Err(Error::new(
+            item.generics.span(),
+            format!(
+                "Expected a newtype (a struct with one unnamed field), got {} fields",
+                fields.len()
+            ),
+        ));
+    };
+    if !matches!(field.vis, Visibility::Inherited) {
+        return Err(Error::new(
+            field.vis.span(),
+            "This field was expected to be private",
+        ));
+    }
+    Ok(&field.ty)
+}
+
+/// Generates the run-time implementations for the refinement type
+/// `item`, a newtype over `inner_ty`.
+pub fn runtime_impls(item: &ItemStruct, inner_ty: &Type, args: &RefinementTypeArgs) -> TokenStream {
+    let ident = &item.ident;
+    let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+    let predicates = where_clause.map(|where_clause| &where_clause.predicates);
+    let RefinementTypeArgs {
+        debug_runtime_check,
+        debug,
+        display,
+        binder,
+        invariant,
+    } = args;
+    // The invariant is evaluated on a clone of `x`, and only when the
+    // inner type implements `Clone` (see `hax_lib::_InternalDebugCheck`).
+    let debug_assert = debug_runtime_check.then(|| {
+        quote! {
+            #[allow(unused_imports)]
+            use ::hax_lib::{_InternalDebugCheckCloned as _, _InternalDebugCheckSkipped as _};
+            ::core::debug_assert!(
+                (&::hax_lib::_InternalDebugCheck(&x)).check(<Self as ::hax_lib::Refinement>::invariant),
+                "value does not satisfy the invariant of refinement type `{}`",
+                ::core::any::type_name::<Self>(),
+            );
+        }
+    });
+    let debug_impl = debug.then(|| {
+        quote! {
+            #[::hax_lib::exclude]
+            impl #impl_generics ::core::fmt::Debug for #ident #ty_generics
+            where
+                for<'__hax> #inner_ty: ::core::fmt::Debug,
+                #predicates
+            {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    ::core::fmt::Debug::fmt(&self.0, f)
+                }
+            }
+        }
+    });
+    let display_impl = display.then(|| {
+        quote! {
+            #[::hax_lib::exclude]
+            impl #impl_generics ::core::fmt::Display for #ident #ty_generics
+            where
+                for<'__hax> #inner_ty: ::core::fmt::Display,
+                #predicates
+            {
+                fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                    ::core::fmt::Display::fmt(&self.0, f)
+                }
+            }
+        }
+    });
+    let mut de_generics = item.generics.clone();
+    de_generics.params.insert(0, parse_quote! {'__hax_de});
+    let (de_impl_generics, _, _) = de_generics.split_for_impl();
+
+    // Bounds on `inner_ty` are made higher-ranked so that they are
+    // never trivial: a trivial unsatisfied bound is an error, while
+    // here it should only disable the implementation.
+    quote! {
+        #[::hax_lib::exclude]
+        impl #impl_generics ::hax_lib::Refinement for #ident #ty_generics #where_clause {
+            type InnerType = #inner_ty;
+
+            fn new(x: Self::InnerType) -> Self {
+                #debug_assert
+                Self(x)
+            }
+            fn get(self) -> Self::InnerType {
+                self.0
+            }
+            fn get_mut(&mut self) -> &mut Self::InnerType {
+                &mut self.0
+            }
+            fn invariant(#binder: Self::InnerType) -> ::hax_lib::Prop {
+                ::hax_lib::Prop::from(#invariant)
+            }
+        }
+
+        #[::hax_lib::exclude]
+        impl #impl_generics ::core::ops::Deref for #ident #ty_generics #where_clause {
+            type Target = #inner_ty;
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        #[::hax_lib::exclude]
+        impl #impl_generics ::hax_lib::RefineAs<#ident #ty_generics> for #inner_ty #where_clause {
+            fn into_checked(self) -> #ident #ty_generics {
+                <#ident #ty_generics as ::hax_lib::Refinement>::new(self)
+            }
+        }
+
+        #[::hax_lib::exclude]
+        impl #impl_generics ::core::convert::TryFrom<#inner_ty> for #ident #ty_generics
+        where
+            for<'__hax> #inner_ty: ::core::clone::Clone,
+            #predicates
+        {
+            type Error = ::hax_lib::RefinementError;
+            fn try_from(x: #inner_ty) -> ::core::result::Result<Self, Self::Error> {
+                <Self as ::hax_lib::Refinement>::try_new(x)
+            }
+        }
+
+        #debug_impl
+
+        #display_impl
+
+        ::hax_lib::_internal_if_serde! {
+            #[::hax_lib::exclude]
+            impl #impl_generics ::hax_lib::_serde::Serialize for #ident #ty_generics
+            where
+                for<'__hax> #inner_ty: ::hax_lib::_serde::Serialize,
+                #predicates
+            {
+                fn serialize<S: ::hax_lib::_serde::Serializer>(
+                    &self,
+                    serializer: S,
+                ) -> ::core::result::Result<S::Ok, S::Error> {
+                    ::hax_lib::_serde::Serialize::serialize(&self.0, serializer)
+                }
+            }
+
+            #[::hax_lib::exclude]
+            impl #de_impl_generics ::hax_lib::_serde::Deserialize<'__hax_de> for #ident #ty_generics
+            where
+                #inner_ty: ::hax_lib::_serde::Deserialize<'__hax_de>,
+                for<'__hax> #inner_ty: ::core::clone::Clone,
+                #predicates
+            {
+                fn deserialize<D: ::hax_lib::_serde::Deserializer<'__hax_de>>(
+                    deserializer: D,
+                ) -> ::core::result::Result<Self, D::Error> {
+                    let x = <#inner_ty as ::hax_lib::_serde::Deserialize>::deserialize(deserializer)?;
+                    <Self as ::hax_lib::Refinement>::try_new(x)
+                        .map_err(<D::Error as ::hax_lib::_serde::de::Error>::custom)
+                }
+            }
+        }
+    }
+}
+
+/// Expects `item` to be a `struct` or an `enum`, returns its name and
+/// its generics.
+pub fn invariant_target(item: &Item) -> Result<(&Ident, &Generics)> {
+    match item {
+        Item::Struct(ItemStruct {
+            ident, generics, ..
+        })
+        | Item::Enum(ItemEnum {
+            ident, generics, ..
+        }) => Ok((ident, generics)),
+        _ => Err(Error::new(item.span(), "Expected a struct or an enum")),
+    }
+}
+
+/// Generates the implementation of `Invariant` for the type `ident`,
+/// whose values satisfy the invariant `args` on a reference to them.
+pub fn invariant_impl(
+    ident: &Ident,
+    generics: &Generics,
+    args: &RefinementTypeArgs,
+) -> TokenStream {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let RefinementTypeArgs {
+        debug_runtime_check,
+        debug,
+        display,
+        binder,
+        invariant,
+    } = args;
+    if *debug || *display {
+        return Error::new(
+            ident.span(),
+            "The flags 'debug' and 'display' are specific to refinement types: derive or implement `Debug` and `Display` instead",
+        )
+        .to_compile_error();
+    }
+    let debug_assert = debug_runtime_check.then(|| {
+        quote! {
+            ::core::debug_assert!(
+                <Self as ::hax_lib::Invariant>::invariant(&self)._internal_to_bool(),
+                "value does not satisfy the invariant of type `{}`",
+                ::core::any::type_name::<Self>(),
+            );
+        }
+    });
+    quote! {
+        #[::hax_lib::exclude]
+        impl #impl_generics ::hax_lib::Invariant for #ident #ty_generics #where_clause {
+            fn checked(self) -> Self {
+                #debug_assert
+                self
+            }
+            fn invariant(&self) -> ::hax_lib::Prop {
+                let #binder = self;
+                ::hax_lib::Prop::from(#invariant)
+            }
+        }
+    }
+}
@@ -22,6 +22,48 @@ impl Parse for ExprClosure1 {
     }
 }
 
+/// The argument of `decreases`: a measure, optionally followed by a
+/// rank, e.g. `(m, n)` or `n, rank = 1`.
+pub struct Measure {
+    pub measure: Expr,
+    pub rank: Option<LitInt>,
+}
+
+impl Parse for Measure {
+    fn parse(ps: ParseStream) -> Result<Self> {
+        let measure = ps.parse()?;
+        let rank = if ps.is_empty() {
+            None
+        } else {
+            ps.parse::<Token![,]>()?;
+            let key: Ident = ps.parse()?;
+            if key != "rank" {
+                Err(Error::new(key.span(), "Expected `rank = N`"))?;
+            }
+            ps.parse::<Token![=]>()?;
+            Some(ps.parse()?)
+        };
+        Ok(Measure { measure, rank })
+    }
+}
+
+impl Measure {
+    /// The measure as an expression: the rank, if any, is appended to
+    /// the measure, as the last component of a (lexicographic) tuple.
+    pub fn into_expr(self) -> Expr {
+        let Some(rank) = self.rank else {
+            return self.measure;
+        };
+        match self.measure {
+            Expr::Tuple(ExprTuple { elems, .. }) if !elems.is_empty() => {
+                let elems = elems.iter();
+                parse_quote! {(#(#elems,)* #rank)}
+            }
+            measure => parse_quote! {(#measure, #rank)},
+        }
+    }
+}
+
 /// Utility trait to extract an `Ident` from various syn types
 pub trait ExpectIdent {
     /// Is `self` an `Ident`?
@@ -19,9 +19,13 @@ impl ToTokens for HaxQuantifiers {
 /// Meta informations about functions decorations
 pub enum FnDecorationKind {
     Requires,
-    Ensures { ret_binder: Pat },
+    Ensures {
+        ret_binder: Pat,
+    },
     Decreases,
     SMTPat,
+    /// Not a logical decoration: see `make_modeled_by_decoration`.
+    ModeledBy,
 }
 
 impl ToString for FnDecorationKind {
@@ -31,6 +35,7 @@ impl ToString for FnDecorationKind {
             FnDecorationKind::Ensures { .. } => "ensures".to_string(),
             FnDecorationKind::Decreases { .. } => "decreases".to_string(),
             FnDecorationKind::SMTPat { .. } => "SMTPat".to_string(),
+            FnDecorationKind::ModeledBy => "modeled_by".to_string(),
         }
     }
 }
@@ -42,10 +47,24 @@ impl From<FnDecorationKind> for AssociationRole {
             FnDecorationKind::Ensures { .. } => AssociationRole::Ensures,
             FnDecorationKind::Decreases => AssociationRole::Decreases,
             FnDecorationKind::SMTPat => AssociationRole::SMTPat,
+            FnDecorationKind::ModeledBy => AssociationRole::ModeledBy,
         }
     }
 }
 
+/// A deterministic UID for a decoration of kind `kind`, derived from
+/// `seed` (the decoration and the decorated item) and from the
+/// location of the macro invocation.
+pub(crate) fn item_uid(kind: &str, seed: impl ToTokens) -> ItemUid {
+    let span = pm::Span::call_site();
+    ItemUid::deterministic(&format!(
+        "{kind}@{}:{}:{}",
+        span.line(),
+        span.column(),
+        seed.to_token_stream()
+    ))
+}
+
 /// Merge two `syn::Generics`, respecting lifetime orders
 pub(crate) fn merge_generics(x: Generics, y: Generics) -> Generics {
     Generics {
@@ -234,6 +253,10 @@ pub fn make_fn_decoration(
     mut generics: Option<Generics>,
     self_type: Option<Type>,
 ) -> (TokenStream, AttrPayload) {
+    let uid = item_uid(
+        &kind.to_string(),
+        quote! {#phi #signature #generics #self_type},
+    );
     let self_ident: Ident = {
         let mut idents = IdentCollector::default();
         idents.visit_expr(&phi);
@@ -249,7 +272,6 @@ pub fn make_fn_decoration(
         }
         rewriter.get_error()
     };
-    let uid = ItemUid::fresh();
     let mut_ref_inputs = unmut_references_in_inputs(&mut signature);
     let decoration = {
         let decoration_sig = {
@@ -344,3 +366,74 @@ pub fn make_fn_decoration(
     };
     (quote! {#error #decoration}, assoc_attr)
 }
+
+/// Generates the function witnessing that the function of signature
+/// `signature` is modeled by `model`: a function with the same
+/// signature that calls `model`. Rustc checks the signatures are
+/// compatible, and the engine rewrites the call sites of the modeled
+/// function with the model it calls. As for `make_fn_decoration`,
+/// `generics` and `self_type` are given for functions in impls.
+/// Returns the witness and the attributes of the modeled function.
+pub fn make_modeled_by_decoration(
+    model: Expr,
+    mut signature: Signature,
+    generics: Option<Generics>,
+    self_type: Option<Type>,
+) -> (TokenStream, TokenStream) {
+    let uid = item_uid(
+        "modeled_by",
+        quote! {#model #signature #generics #self_type},
+    );
+    signature.ident = format_ident!("modeled_by");
+    let mut args: Vec<Expr> = vec![];
+    for (i, input) in signature.inputs.iter_mut().enumerate() {
+        match input {
+            FnArg::Typed(pat_type) => {
+                let arg = format_ident!("arg{i}");
+                *pat_type.pat = parse_quote! {#arg};
+                args.push(parse_quote! {#arg});
+            }
+            FnArg::Receiver(_) => args.push(parse_quote! {self}),
+        }
+    }
+    let mut call: Expr = parse_quote! {#model(#(#args),*)};
+    let self_ident: Ident = {
+        let mut idents = IdentCollector::default();
+        idents.visit_expr(&call);
+        idents.visit_signature(&signature);
+        idents.fresh_ident("self_")
+    };
+    let error = {
+        let mut rewriter = RewriteSelf::new(self_ident, self_type);
+        rewriter.visit_expr_mut(&mut call);
+        rewriter.visit_signature_mut(&mut signature);
+        rewriter.get_error()
+    };
+    if let Some(generics) = generics {
+        signature.generics = merge_generics(generics, signature.generics);
+    }
+
+    let uid_attr = AttrPayload::Uid(uid.clone());
+    let late_skip = AttrPayload::ItemStatus(ItemStatus::Included { late_skip: true });
+    let status = AttrPayload::ItemStatus(ItemStatus::Excluded {
+        modeled_by: Some(model.to_token_stream().to_string()),
+    });
+    let assoc_attr = AttrPayload::AssociatedItem {
+        role: AssociationRole::ModeledBy,
+        item: uid,
+    };
+    let decoration = quote! {
+        #error
+        #[cfg(#DebugOrHaxCfgExpr)]
+        #late_skip
+        const _: () = {
+            #uid_attr
+            #late_skip
+            #[allow(unused)]
+            #signature {
+                #call
+            }
+        };
+    };
+    (decoration, quote! {#status #assoc_attr})
+}
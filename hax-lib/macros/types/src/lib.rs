@@ -18,18 +18,49 @@ use serde::{Deserialize, Serialize};
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[serde(rename = "HaUid")]
 pub struct ItemUid {
-    /// Currently, this is a UUID.
+    /// Currently, this is a 128 bits hash, in hexadecimal.
     pub uid: String,
 }
 
 impl ItemUid {
-    pub fn fresh() -> Self {
-        use uuid::Uuid;
-        let uid = format!("{}", Uuid::new_v4().simple());
-        ItemUid { uid }
+    /// Derives a UID from `seed`, which should describe the decoration
+    /// and the decorated item. The same seed always gives the same UID,
+    /// which makes the output of the macros reproducible.
+    ///
+    /// UIDs are unique within a process (i.e. within the compilation
+    /// of a crate): if the UID of `seed` was already given out, the
+    /// seed is suffixed with a counter until the UID is fresh. Since
+    /// rustc expands macros in a deterministic order, this is
+    /// reproducible as well.
+    pub fn deterministic(seed: &str) -> Self {
+        use std::collections::HashSet;
+        use std::sync::Mutex;
+        static GIVEN_OUT: Mutex<Option<HashSet<u128>>> = Mutex::new(None);
+
+        let mut given_out = GIVEN_OUT.lock().unwrap_or_else(|e| e.into_inner());
+        let given_out = given_out.get_or_insert_with(HashSet::new);
+        let mut hash = fnv1a_128(seed.as_bytes());
+        let mut counter = 0;
+        while !given_out.insert(hash) {
+            counter += 1;
+            hash = fnv1a_128(format!("{seed}#{counter}").as_bytes());
+        }
+        ItemUid {
+            uid: format!("{hash:032x}"),
+        }
     }
 }
 
+/// The 128 bits FNV-1a hash, which is stable across platforms and
+/// compiler versions.
+fn fnv1a_128(bytes: &[u8]) -> u128 {
+    const OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const PRIME: u128 = 0x0000000001000000000000000000013b;
+    bytes.iter().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u128::from(*byte)).wrapping_mul(PRIME)
+    })
+}
+
 /// What shall Hax do with an item?
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
@@ -63,6 +94,9 @@ pub enum AssociationRole {
     ProcessWrite,
     ProcessInit,
     ProtocolMessages,
+    /// A function with the signature of the marked item, calling the
+    /// model of the marked item
+    ModeledBy,
 }
 
 /// Where should a item quote appear?
@@ -123,6 +157,9 @@ pub enum AttrPayload {
     NewtypeAsRefinement,
     /// Mark an item as a lemma statement to prove in the backend
     Lemma,
+    /// Mark a function as pure: free of side effects and total, so
+    /// that it can be used in specifications
+    Pure,
     Language,
     ProcessRead,
     ProcessWrite,
@@ -178,3 +215,59 @@ impl ToTokens for AttrPayload {
         proc_macro2::TokenStream::from(self).to_tokens(tokens)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// UIDs are given out process-wide: every test uses its own seeds.
+    fn uid(seed: &str) -> String {
+        ItemUid::deterministic(seed).uid
+    }
+
+    #[test]
+    fn fnv1a_128_test_vectors() {
+        assert_eq!(fnv1a_128(b""), 0x6c62272e07bb014262b821756295c58d);
+        assert_eq!(fnv1a_128(b"a"), 0xd228cb696f1a8caf78912b704e4a8964);
+    }
+
+    #[test]
+    fn uid_is_the_hash_of_the_seed() {
+        assert_eq!(
+            uid("requires@1:2:fn f"),
+            format!("{:032x}", fnv1a_128(b"requires@1:2:fn f"))
+        );
+        assert_eq!(
+            uid("ensures@1:2:fn f"),
+            format!("{:032x}", fnv1a_128(b"ensures@1:2:fn f"))
+        );
+    }
+
+    #[test]
+    fn collisions_are_suffixed() {
+        let first = uid("decreases@3:4:fn g");
+        let second = uid("decreases@3:4:fn g");
+        let third = uid("decreases@3:4:fn g");
+        assert_eq!(first, format!("{:032x}", fnv1a_128(b"decreases@3:4:fn g")));
+        assert_eq!(
+            second,
+            format!("{:032x}", fnv1a_128(b"decreases@3:4:fn g#1"))
+        );
+        assert_eq!(
+            third,
+            format!("{:032x}", fnv1a_128(b"decreases@3:4:fn g#2"))
+        );
+    }
+
+    #[test]
+    fn suffixes_skip_given_out_uids() {
+        let suffixed = uid("lemma@5:6:fn h#1");
+        assert_eq!(
+            uid("lemma@5:6:fn h"),
+            format!("{:032x}", fnv1a_128(b"lemma@5:6:fn h"))
+        );
+        let next = uid("lemma@5:6:fn h");
+        assert_ne!(next, suffixed);
+        assert_eq!(next, format!("{:032x}", fnv1a_128(b"lemma@5:6:fn h#2")));
+    }
+}
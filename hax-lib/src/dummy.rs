@@ -1,9 +1,27 @@
 mod abstraction;
 pub use abstraction::*;
 
+pub mod testing;
+
 pub mod prop;
 pub use prop::*;
 
+mod refinement;
+pub use refinement::*;
+
+mod modular;
+pub use modular::*;
+
+#[cfg(feature = "alloc")]
+mod seq;
+#[cfg(feature = "alloc")]
+pub use seq::*;
+
+#[cfg(feature = "alloc")]
+mod map;
+#[cfg(feature = "alloc")]
+pub use map::*;
+
 pub use int::*;
 
 #[cfg(feature = "macros")]
@@ -46,15 +64,153 @@ pub fn inline_unsafe<T>(_: &str) -> T {
 #[doc(hidden)]
 pub const fn _internal_loop_invariant<T, R: Into<Prop>, P: FnOnce(T) -> R>(_: &P) {}
 
+#[doc(hidden)]
+pub const fn _internal_iterator_loop_invariant<T, S, R: Into<Prop>, P: FnOnce(T, S) -> R>(_: &P) {}
+
 #[doc(hidden)]
 pub const fn _internal_while_loop_invariant(_: Prop) {}
 
 #[doc(hidden)]
 pub const fn _internal_loop_decreases(_: int::Int) {}
 
+#[doc(hidden)]
+pub fn _internal_check_loop_invariant(location: &str, invariant: Prop) {
+    if !invariant._internal_to_bool() {
+        panic!("loop invariant violated in the loop at {location}")
+    }
+}
+
+/// A ghost value of type `T`, outside of hax: ghost code is erased,
+/// and a ghost value carries no value. See `hax_lib::ghost`.
+///
+/// A ghost function returns a ghost value, so that non-ghost code
+/// cannot use its result:
+///
+/// ```compile_fail
+/// use hax_lib::Ghost;
+///
+/// #[hax_lib::ghost]
+/// fn triangle(n: u32) -> u64 {
+///     (n as u64) * (n as u64 + 1) / 2
+/// }
+///
+/// pub fn leak(n: u32) -> u64 {
+///     triangle(n) + 1
+/// }
+/// ```
+pub struct Ghost<T>(core::marker::PhantomData<T>);
+
+impl<T> Ghost<T> {
+    /// The ghost value computed by `value`, which is never called.
+    #[doc(hidden)]
+    pub fn erased(_value: impl FnOnce() -> T) -> Self {
+        Self(core::marker::PhantomData)
+    }
+
+    /// The value of a ghost value, in erased ghost code, which is never
+    /// executed.
+    #[doc(hidden)]
+    pub fn get(&self) -> T {
+        unreachable!("ghost code is never executed outside of hax")
+    }
+}
+
+impl<T> Clone for Ghost<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Ghost<T> {}
+
+/// Tells whether `after` has the length of `before`, and the same
+/// items outside of the indices satisfying `modified`.
+#[doc(hidden)]
+pub fn _internal_check_frame_indices<T: PartialEq>(
+    before: &[T],
+    after: &[T],
+    modified: impl Fn(usize) -> bool,
+) -> bool {
+    before.len() == after.len() && (0..before.len()).all(|i| modified(i) || before[i] == after[i])
+}
+
+/// The decreases measure of a loop checked by `check_loops`, as of
+/// its previous iteration.
+#[doc(hidden)]
+#[derive(Default)]
+pub struct LoopMeasure<T>(Option<T>);
+
+impl<T: PartialOrd + Default + core::fmt::Debug> LoopMeasure<T> {
+    pub fn check(&mut self, location: &str, measure: T) {
+        if measure < T::default() {
+            panic!("loop measure {measure:?} is negative in the loop at {location}")
+        }
+        if let Some(previous) = &self.0 {
+            match measure.partial_cmp(previous) {
+                Some(core::cmp::Ordering::Less) => (),
+                _ => panic!(
+                    "loop measure {measure:?} does not decrease from {previous:?} in the loop at {location}"
+                ),
+            }
+        }
+        self.0 = Some(measure);
+    }
+}
+
+/// The number of iterations of a loop checked by `check_loops`.
+#[doc(hidden)]
+#[derive(Default)]
+pub struct LoopIterations(usize);
+
+impl LoopIterations {
+    pub fn check<R: Into<Prop>>(&self, location: &str, invariant: impl FnOnce(usize) -> R) {
+        _internal_check_loop_invariant(location, invariant(self.0).into())
+    }
+
+    pub fn step(&mut self) {
+        self.0 += 1
+    }
+}
+
+/// The items consumed by the iterations of a loop checked by
+/// `check_loops`.
+#[doc(hidden)]
+#[cfg(feature = "alloc")]
+pub struct LoopItems<T>(alloc::vec::Vec<T>);
+
+#[cfg(feature = "alloc")]
+impl<T> Default for LoopItems<T> {
+    fn default() -> Self {
+        Self(alloc::vec::Vec::new())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Clone> LoopItems<T> {
+    pub fn check<R: Into<Prop>>(&self, location: &str, invariant: impl FnOnce(usize, Seq<T>) -> R) {
+        let prefix = self.0.clone().lift();
+        _internal_check_loop_invariant(location, invariant(self.0.len(), prefix).into())
+    }
+
+    pub fn push(&mut self, item: &T) {
+        self.0.push(item.clone())
+    }
+}
+
 pub trait Refinement {
     type InnerType;
     fn new(x: Self::InnerType) -> Self;
+    fn try_new(x: Self::InnerType) -> Result<Self, RefinementError>
+    where
+        Self: Sized,
+        Self::InnerType: Clone,
+    {
+        if Self::invariant(x.clone())._internal_to_bool() {
+            Ok(Self::new(x))
+        } else {
+            Err(RefinementError::new::<Self>())
+        }
+    }
     fn get(self) -> Self::InnerType;
     fn get_mut(&mut self) -> &mut Self::InnerType;
     fn invariant(value: Self::InnerType) -> crate::Prop;
@@ -62,9 +218,33 @@ pub trait Refinement {
 
 pub trait RefineAs<RefinedType> {
     fn into_checked(self) -> RefinedType;
+    fn try_into_checked(self) -> Result<RefinedType, RefinementError>
+    where
+        RefinedType: Refinement<InnerType = Self>,
+        Self: Clone + Sized,
+    {
+        RefinedType::try_new(self)
+    }
+}
+
+pub trait Invariant: Sized {
+    fn checked(self) -> Self;
+    fn try_checked(self) -> Result<Self, RefinementError> {
+        if self.invariant()._internal_to_bool() {
+            Ok(self)
+        } else {
+            Err(RefinementError::new::<Self>())
+        }
+    }
+    fn invariant(&self) -> crate::Prop;
 }
 
+/// Mathematical integers. Without the `runtime-contracts` feature,
+/// `Int`s are placeholders outside of hax: their operations do not
+/// compute anything meaningful.
+#[cfg(not(feature = "runtime-contracts"))]
 pub mod int {
+    use crate::abstraction::*;
     use core::ops::*;
 
     #[macro_export]
@@ -128,19 +308,76 @@ pub mod int {
         pub fn rem_euclid(&self, v: Self) -> Self {
             Self::new(self.0.rem_euclid(v.0))
         }
+        pub fn pow(self, _exp: u32) -> Self {
+            self
+        }
+        pub fn abs(self) -> Self {
+            self
+        }
+        pub fn signum(self) -> Self {
+            Int(self.0.min(1))
+        }
+        pub fn min(self, other: Self) -> Self {
+            core::cmp::min(self, other)
+        }
+        pub fn max(self, other: Self) -> Self {
+            core::cmp::max(self, other)
+        }
+        pub fn gcd(self, _other: Self) -> Self {
+            self
+        }
     }
 
-    pub trait ToInt {
-        fn to_int(self) -> Int;
+    impl Rem for Int {
+        type Output = Self;
+
+        fn rem(self, other: Self) -> Self::Output {
+            Int(self.0 % other.0)
+        }
     }
 
-    pub trait Abstraction {
-        type AbstractType;
-        fn lift(self) -> Self::AbstractType;
+    impl Shl<u32> for Int {
+        type Output = Self;
+
+        fn shl(self, _shift: u32) -> Self::Output {
+            self
+        }
     }
 
-    pub trait Concretization<T> {
-        fn concretize(self) -> T;
+    impl Shr<u32> for Int {
+        type Output = Self;
+
+        fn shr(self, _shift: u32) -> Self::Output {
+            self
+        }
+    }
+
+    impl BitAnd for Int {
+        type Output = Self;
+
+        fn bitand(self, other: Self) -> Self::Output {
+            Int(self.0 & other.0)
+        }
+    }
+
+    impl BitOr for Int {
+        type Output = Self;
+
+        fn bitor(self, other: Self) -> Self::Output {
+            Int(self.0 | other.0)
+        }
+    }
+
+    impl BitXor for Int {
+        type Output = Self;
+
+        fn bitxor(self, other: Self) -> Self::Output {
+            Int(self.0 ^ other.0)
+        }
+    }
+
+    pub trait ToInt {
+        fn to_int(self) -> Int;
     }
 
     macro_rules! implement_abstraction {
@@ -156,6 +393,26 @@ pub mod int {
                     self.lift()
                 }
             }
+            impl PartialEq<$ty> for Int {
+                fn eq(&self, other: &$ty) -> bool {
+                    *self == other.lift()
+                }
+            }
+            impl PartialEq<Int> for $ty {
+                fn eq(&self, other: &Int) -> bool {
+                    self.lift() == *other
+                }
+            }
+            impl PartialOrd<$ty> for Int {
+                fn partial_cmp(&self, other: &$ty) -> Option<core::cmp::Ordering> {
+                    self.partial_cmp(&other.lift())
+                }
+            }
+            impl PartialOrd<Int> for $ty {
+                fn partial_cmp(&self, other: &Int) -> Option<core::cmp::Ordering> {
+                    self.lift().partial_cmp(other)
+                }
+            }
         };
         ($($ty:ident)*) => {
             $(implement_abstraction!($ty);)*
@@ -200,3 +457,335 @@ pub mod int {
         isize to_isize,
     );
 }
+
+/// Mathematical integers. With the `runtime-contracts` feature, `Int`s
+/// are backed by an `i128` outside of hax: an operation whose result
+/// doesn't fit in an `i128` panics rather than returning a wrong
+/// result, so that specifications evaluated at run time (e.g. by
+/// `check_loops`) never hold by accident.
+#[cfg(feature = "runtime-contracts")]
+pub mod int {
+    use crate::abstraction::*;
+    use core::fmt;
+    use core::ops::*;
+
+    #[macro_export]
+    macro_rules! int {
+        ($lit:expr) => {
+            $crate::int::Int($lit)
+        };
+    }
+
+    #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Debug, Default, Hash)]
+    pub struct Int(pub i128);
+
+    impl fmt::Display for Int {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}", self.0)
+        }
+    }
+
+    /// The result of an operation on `Int`s, which panics if it
+    /// doesn't fit in an `i128`.
+    fn checked(result: Option<i128>) -> Int {
+        match result {
+            Some(result) => Int(result),
+            None => panic!("`Int` overflow: outside of hax, `Int`s are backed by `i128`s"),
+        }
+    }
+
+    impl Int {
+        pub fn new(x: impl Into<i128>) -> Self {
+            Int(x.into())
+        }
+        pub fn get(self) -> i128 {
+            self.0
+        }
+    }
+
+    impl Add for Int {
+        type Output = Self;
+
+        fn add(self, other: Self) -> Self::Output {
+            checked(self.0.checked_add(other.0))
+        }
+    }
+
+    impl Neg for Int {
+        type Output = Self;
+
+        fn neg(self) -> Self::Output {
+            checked(self.0.checked_neg())
+        }
+    }
+
+    impl Sub for Int {
+        type Output = Self;
+
+        fn sub(self, other: Self) -> Self::Output {
+            checked(self.0.checked_sub(other.0))
+        }
+    }
+
+    impl Mul for Int {
+        type Output = Self;
+
+        fn mul(self, other: Self) -> Self::Output {
+            checked(self.0.checked_mul(other.0))
+        }
+    }
+
+    impl Div for Int {
+        type Output = Self;
+
+        fn div(self, other: Self) -> Self::Output {
+            if other.0 == 0 {
+                panic!("attempt to divide an `Int` by zero")
+            }
+            checked(self.0.checked_div(other.0))
+        }
+    }
+
+    impl Int {
+        pub fn pow2(self) -> Self {
+            let exponent = u32::try_from(self.0).expect("Exponent doesn't fit in a u32");
+            checked(2i128.checked_pow(exponent))
+        }
+        pub fn _unsafe_from_str(s: &str) -> Self {
+            Int(s.parse().expect("`Int` literal doesn't fit in an `i128`"))
+        }
+        pub fn rem_euclid(&self, v: Self) -> Self {
+            if v.0 == 0 {
+                panic!("attempt to compute the remainder of an `Int` divided by zero")
+            }
+            checked(self.0.checked_rem_euclid(v.0))
+        }
+        pub fn pow(self, exp: u32) -> Self {
+            checked(self.0.checked_pow(exp))
+        }
+        pub fn abs(self) -> Self {
+            checked(self.0.checked_abs())
+        }
+        pub fn signum(self) -> Self {
+            Int(self.0.signum())
+        }
+        pub fn min(self, other: Self) -> Self {
+            core::cmp::min(self, other)
+        }
+        pub fn max(self, other: Self) -> Self {
+            core::cmp::max(self, other)
+        }
+        pub fn gcd(self, other: Self) -> Self {
+            let (mut a, mut b) = (self.0.unsigned_abs(), other.0.unsigned_abs());
+            while b != 0 {
+                (a, b) = (b, a % b);
+            }
+            checked(i128::try_from(a).ok())
+        }
+    }
+
+    impl Rem for Int {
+        type Output = Self;
+
+        fn rem(self, other: Self) -> Self::Output {
+            if other.0 == 0 {
+                panic!("attempt to compute the remainder of an `Int` divided by zero")
+            }
+            checked(self.0.checked_rem(other.0))
+        }
+    }
+
+    impl Shl<u32> for Int {
+        type Output = Self;
+
+        fn shl(self, shift: u32) -> Self::Output {
+            checked(
+                2i128
+                    .checked_pow(shift)
+                    .and_then(|factor| self.0.checked_mul(factor)),
+            )
+        }
+    }
+
+    impl Shr<u32> for Int {
+        type Output = Self;
+
+        /// Rounds towards negative infinity, as an arithmetic shift
+        fn shr(self, shift: u32) -> Self::Output {
+            Int(self.0 >> shift.min(127))
+        }
+    }
+
+    impl BitAnd for Int {
+        type Output = Self;
+
+        fn bitand(self, other: Self) -> Self::Output {
+            Int(self.0 & other.0)
+        }
+    }
+
+    impl BitOr for Int {
+        type Output = Self;
+
+        fn bitor(self, other: Self) -> Self::Output {
+            Int(self.0 | other.0)
+        }
+    }
+
+    impl BitXor for Int {
+        type Output = Self;
+
+        fn bitxor(self, other: Self) -> Self::Output {
+            Int(self.0 ^ other.0)
+        }
+    }
+
+    pub trait ToInt {
+        fn to_int(self) -> Int;
+    }
+
+    macro_rules! implement_abstraction {
+        ($ty:ident) => {
+            impl Abstraction for $ty {
+                type AbstractType = Int;
+                #[allow(clippy::useless_conversion, clippy::unnecessary_fallible_conversions)]
+                fn lift(self) -> Self::AbstractType {
+                    checked(i128::try_from(self).ok())
+                }
+            }
+            impl ToInt for $ty {
+                fn to_int(self) -> Int {
+                    self.lift()
+                }
+            }
+            impl PartialEq<$ty> for Int {
+                fn eq(&self, other: &$ty) -> bool {
+                    *self == other.lift()
+                }
+            }
+            impl PartialEq<Int> for $ty {
+                fn eq(&self, other: &Int) -> bool {
+                    self.lift() == *other
+                }
+            }
+            impl PartialOrd<$ty> for Int {
+                fn partial_cmp(&self, other: &$ty) -> Option<core::cmp::Ordering> {
+                    self.partial_cmp(&other.lift())
+                }
+            }
+            impl PartialOrd<Int> for $ty {
+                fn partial_cmp(&self, other: &Int) -> Option<core::cmp::Ordering> {
+                    self.lift().partial_cmp(other)
+                }
+            }
+        };
+        ($($ty:ident)*) => {
+            $(implement_abstraction!($ty);)*
+        };
+    }
+
+    implement_abstraction!(u8 u16 u32 u64 u128 usize);
+    implement_abstraction!(i8 i16 i32 i64 i128 isize);
+
+    macro_rules! implement_concretize {
+        ($ty:ident $method:ident) => {
+            impl Concretization<$ty> for Int {
+                #[allow(clippy::useless_conversion, clippy::unnecessary_fallible_conversions)]
+                fn concretize(self) -> $ty {
+                    match $ty::try_from(self.0) {
+                        Ok(concretized) => concretized,
+                        Err(_) => panic!(
+                            "`Int` {} doesn't fit in a `{}`",
+                            self.0,
+                            ::core::stringify!($ty)
+                        ),
+                    }
+                }
+            }
+            impl Int {
+                pub fn $method(self) -> $ty {
+                    self.concretize()
+                }
+            }
+        };
+        ($ty:ident $method:ident, $($tt:tt)*) => {
+            implement_concretize!($ty $method);
+            implement_concretize!($($tt)*);
+        };
+        () => {};
+    }
+
+    implement_concretize!(
+        u8    to_u8,
+        u16   to_u16,
+        u32   to_u32,
+        u64   to_u64,
+        u128  to_u128,
+        usize to_usize,
+        i8    to_i8,
+        i16   to_i16,
+        i32   to_i32,
+        i64   to_i64,
+        i128  to_i128,
+        isize to_isize,
+    );
+
+    /// This module is only compiled outside of hax, unlike in this
+    /// workspace (see `.cargo/config.toml`).
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn int(x: i128) -> Int {
+            x.lift()
+        }
+
+        #[test]
+        fn lift_and_concretize() {
+            assert_eq!(5u8.lift(), Int(5));
+            assert_eq!((-5i64).to_int(), Int(-5));
+            assert_eq!(u64::MAX.lift().to_u64(), u64::MAX);
+            assert_eq!(int(-1).to_i8(), -1);
+            assert!(3u32 < int(4) && int(4) == 4u16);
+        }
+
+        #[test]
+        fn arithmetic() {
+            assert_eq!(int(3) - int(5), int(-2));
+            assert_eq!(int(-7) / int(2), int(-3));
+            assert_eq!(int(-7) % int(2), int(-1));
+            assert_eq!(int(-7).rem_euclid(int(2)), int(1));
+            assert_eq!(-int(3), int(-3));
+            assert_eq!(int(3).pow(4), int(81));
+            assert_eq!(int(5).pow2(), int(32));
+            assert_eq!(int(-12).gcd(int(18)), int(6));
+            assert_eq!(int(-5).abs(), int(5));
+            assert_eq!(int(-5).signum(), int(-1));
+            assert_eq!(int(3) << 4, int(48));
+            assert_eq!(int(-7) >> 1, int(-4));
+            assert_eq!(int(12) & int(10), int(8));
+            assert_eq!(int(12) | int(10), int(14));
+            assert_eq!(int(12) ^ int(10), int(6));
+            assert_eq!(Int::_unsafe_from_str("-42"), int(-42));
+            assert_eq!(Int::default(), int(0));
+        }
+
+        #[test]
+        #[should_panic(expected = "`Int` overflow")]
+        fn overflow_panics() {
+            let _ = u128::MAX.lift();
+        }
+
+        #[test]
+        #[should_panic(expected = "`Int` overflow")]
+        fn arithmetic_overflow_panics() {
+            let _ = int(i128::MAX) + int(1);
+        }
+
+        #[test]
+        #[should_panic(expected = "doesn't fit")]
+        fn concretization_out_of_range_panics() {
+            let _ = int(256).to_u8();
+        }
+    }
+}
@@ -7,6 +7,22 @@ pub use int::*;
 pub mod prop;
 pub use prop::*;
 
+mod refinement;
+pub use refinement::*;
+
+mod modular;
+pub use modular::*;
+
+#[cfg(feature = "alloc")]
+mod seq;
+#[cfg(feature = "alloc")]
+pub use seq::*;
+
+#[cfg(feature = "alloc")]
+mod map;
+#[cfg(feature = "alloc")]
+pub use map::*;
+
 #[cfg(feature = "macros")]
 pub use crate::proc_macros::*;
 
@@ -143,6 +159,11 @@ pub fn any_to_unit<T>(_: T) -> () {
 #[doc(hidden)]
 pub fn _internal_loop_invariant<T, R: Into<Prop>, P: FnOnce(T) -> R>(_: P) {}
 
+/// A dummy function that holds a loop invariant on the index and on
+/// the items consumed so far.
+#[doc(hidden)]
+pub fn _internal_iterator_loop_invariant<T, S, R: Into<Prop>, P: FnOnce(T, S) -> R>(_: P) {}
+
 /// A dummy function that holds a while loop invariant.
 #[doc(hidden)]
 pub const fn _internal_while_loop_invariant(_: Prop) {}
@@ -164,6 +185,19 @@ pub trait Refinement {
     /// Smart constructor capturing an invariant. Its extraction will
     /// yield a proof obligation.
     fn new(x: Self::InnerType) -> Self;
+    /// Fallible smart constructor: the invariant is always checked at
+    /// run-time, in debug and release builds alike.
+    fn try_new(x: Self::InnerType) -> Result<Self, RefinementError>
+    where
+        Self: Sized,
+        Self::InnerType: Clone,
+    {
+        if Self::invariant(x.clone())._internal_to_bool() {
+            Ok(Self::new(x))
+        } else {
+            Err(RefinementError::new::<Self>())
+        }
+    }
     /// Destructor for the refined type
     fn get(self) -> Self::InnerType;
     /// Gets a mutable reference to a refinement
@@ -189,4 +223,39 @@ pub trait RefineAs<RefinedType> {
     /// refinement type `RefinedType` with the `refinement_type` macro
     /// and its `no_debug_runtime_check` option.
     fn into_checked(self) -> RefinedType;
+    /// Fallible counterpart of `into_checked`: the invariant
+    /// `RefinedType::invariant` is always checked at run-time.
+    fn try_into_checked(self) -> Result<RefinedType, RefinementError>
+    where
+        RefinedType: Refinement<InnerType = Self>,
+        Self: Clone + Sized,
+    {
+        RefinedType::try_new(self)
+    }
+}
+
+/// A type whose values all satisfy an invariant: a `struct` or an
+/// `enum` marked with the `invariant` macro.
+///
+/// Please never implement this trait yourself, use the `invariant`
+/// macro instead.
+pub trait Invariant: Sized {
+    /// Smart constructor checking the invariant of `self`. Its
+    /// extraction will yield a proof obligation.
+    ///
+    /// In addition, in debug mode, the invariant is checked at
+    /// run-time, unless this behavior was disabled with the
+    /// `no_debug_runtime_check` option of the `invariant` macro.
+    fn checked(self) -> Self;
+    /// Fallible smart constructor: the invariant is always checked at
+    /// run-time, in debug and release builds alike.
+    fn try_checked(self) -> Result<Self, RefinementError> {
+        if self.invariant()._internal_to_bool() {
+            Ok(self)
+        } else {
+            Err(RefinementError::new::<Self>())
+        }
+    }
+    /// Tests whether a value satisfies the invariant
+    fn invariant(&self) -> Prop;
 }
@@ -90,8 +90,87 @@ impl Int {
         use num_traits::Euclid;
         Self::new(self.get().rem_euclid(&v.get()))
     }
+
+    /// Raises `self` at the power `exp`
+    pub fn pow(self, exp: u32) -> Self {
+        Self::new(self.get().pow(exp))
+    }
+
+    /// Absolute value of `self`
+    pub fn abs(self) -> Self {
+        use num_traits::Signed;
+        Self::new(self.get().abs())
+    }
+
+    /// `-1`, `0` or `1` depending on the sign of `self`
+    pub fn signum(self) -> Self {
+        use num_traits::Signed;
+        Self::new(self.get().signum())
+    }
+
+    pub fn min(self, other: Self) -> Self {
+        core::cmp::min(self, other)
+    }
+
+    pub fn max(self, other: Self) -> Self {
+        core::cmp::max(self, other)
+    }
+
+    /// Greatest common divisor of `self` and `other`, which is always
+    /// non-negative
+    pub fn gcd(self, other: Self) -> Self {
+        use num_traits::{Signed, Zero};
+        let (mut a, mut b) = (self.get().abs(), other.get().abs());
+        while !b.is_zero() {
+            let r = &a % &b;
+            a = b;
+            b = r;
+        }
+        Self::new(a)
+    }
+}
+
+impl Rem for Int {
+    type Output = Self;
+
+    fn rem(self, other: Self) -> Self::Output {
+        Self::new(self.get() % other.get())
+    }
+}
+
+impl Shl<u32> for Int {
+    type Output = Self;
+
+    fn shl(self, shift: u32) -> Self::Output {
+        Self::new(self.get() << shift)
+    }
+}
+
+impl Shr<u32> for Int {
+    type Output = Self;
+
+    /// Rounds towards negative infinity, as an arithmetic shift
+    fn shr(self, shift: u32) -> Self::Output {
+        Self::new(self.get() >> shift)
+    }
+}
+
+/// Bitwise operations are meant for non-negative integers: in the
+/// backends, they are only defined on those.
+macro_rules! implement_bitwise {
+    ($($trait:ident $method:ident),*) => {
+        $(impl $trait for Int {
+            type Output = Self;
+
+            fn $method(self, other: Self) -> Self::Output {
+                Self::new(self.get().$method(other.get()))
+            }
+        })*
+    };
 }
 
+implement_bitwise!(BitAnd bitand, BitOr bitor, BitXor bitxor);
+
 #[cfg(feature = "macros")]
 pub trait ToInt {
     fn to_int(self) -> Int;
@@ -125,6 +204,26 @@ macro_rules! implement_abstraction {
                 self.lift()
             }
         }
+        impl PartialEq<$ty> for Int {
+            fn eq(&self, other: &$ty) -> bool {
+                *self == other.lift()
+            }
+        }
+        impl PartialEq<Int> for $ty {
+            fn eq(&self, other: &Int) -> bool {
+                self.lift() == *other
+            }
+        }
+        impl PartialOrd<$ty> for Int {
+            fn partial_cmp(&self, other: &$ty) -> Option<core::cmp::Ordering> {
+                self.partial_cmp(&other.lift())
+            }
+        }
+        impl PartialOrd<Int> for $ty {
+            fn partial_cmp(&self, other: &Int) -> Option<core::cmp::Ordering> {
+                self.lift().partial_cmp(other)
+            }
+        }
     };
     ($($ty:ident)*) => {
         $(implement_abstraction!($ty);)*
@@ -170,3 +269,87 @@ implement_concretize!(
     i128  to_i128,
     isize to_isize,
 );
+
+/// This module is only compiled under hax, as in this workspace (see
+/// `.cargo/config.toml`).
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn int(x: i128) -> Int {
+        x.lift()
+    }
+
+    #[test]
+    fn pow_gcd_abs_signum() {
+        assert_eq!(int(3).pow(4), int(81));
+        assert_eq!(int(-2).pow(3), int(-8));
+        assert_eq!(int(7).pow(0), int(1));
+        assert_eq!(
+            int(2).pow(130),
+            int(1 << 100) * int(1 << 30),
+            "`pow` does not overflow"
+        );
+        assert_eq!(int(12).gcd(int(18)), int(6));
+        assert_eq!(int(-12).gcd(int(18)), int(6));
+        assert_eq!(int(0).gcd(int(-5)), int(5));
+        assert_eq!(int(0).gcd(int(0)), int(0));
+        assert_eq!(int(-5).abs(), int(5));
+        assert_eq!(int(5).abs(), int(5));
+        assert_eq!(int(-5).signum(), int(-1));
+        assert_eq!(int(0).signum(), int(0));
+        assert_eq!(int(5).signum(), int(1));
+        assert_eq!(int(-5).min(int(3)), int(-5));
+        assert_eq!(int(-5).max(int(3)), int(3));
+        assert_eq!(int(10).pow2(), int(1024));
+    }
+
+    #[test]
+    fn division_and_remainder() {
+        // `/` and `%` truncate towards zero, `rem_euclid` is non-negative.
+        assert_eq!(int(-7) / int(2), int(-3));
+        assert_eq!(int(-7) % int(2), int(-1));
+        assert_eq!(int(7) % int(-2), int(1));
+        assert_eq!(int(-7).rem_euclid(int(2)), int(1));
+        assert_eq!(int(-7).rem_euclid(int(-2)), int(1));
+    }
+
+    #[test]
+    fn shifts() {
+        assert_eq!(int(1) << 100, int(1 << 100));
+        assert_eq!((int(1) << 200) >> 199, int(2));
+        assert_eq!(int(5) >> 1, int(2));
+        // Arithmetic shift: rounds towards negative infinity.
+        assert_eq!(int(-5) >> 1, int(-3));
+        assert_eq!(int(-1) >> 10, int(-1));
+    }
+
+    #[test]
+    fn bitwise() {
+        assert_eq!(int(0b1100) & int(0b1010), int(0b1000));
+        assert_eq!(int(0b1100) | int(0b1010), int(0b1110));
+        assert_eq!(int(0b1100) ^ int(0b1010), int(0b0110));
+        assert_eq!((int(1) << 100) | int(1), int((1 << 100) | 1));
+    }
+
+    #[test]
+    fn mixed_comparisons() {
+        assert!(int(3) == 3u8);
+        assert!(3u8 == int(3));
+        assert!(int(-1) < 0u8);
+        assert!(0u8 > int(-1));
+        assert!(int(256) > u8::MAX);
+        assert!(u64::MAX < int(1) << 64);
+        assert!(i128::MIN == int(i128::MIN));
+        assert!(int(-1) != u128::MAX);
+        assert!(u128::MAX.lift() > i128::MAX);
+    }
+
+    #[test]
+    fn concretization() {
+        assert_eq!((int(200) + int(55)).to_u8(), 255);
+        assert_eq!(int(-128).to_i8(), i8::MIN);
+        assert_eq!(u128::MAX.lift().to_u128(), u128::MAX);
+        assert_eq!(Int::_unsafe_from_str("-42"), int(-42));
+    }
+}
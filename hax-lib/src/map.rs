@@ -0,0 +1,157 @@
+#[cfg(feature = "std")]
+use crate::abstraction::*;
+use alloc::vec::Vec;
+
+/// Finite mathematical maps, for writing specifications about
+/// dictionaries, e.g. `output.lift() == input.lift().update(k, v)`.
+/// Operations on maps are pure: they consume their operands and
+/// return new maps.
+///
+/// `Map` computes natively in Rust (this requires the `alloc`
+/// feature), and is extracted to the maps of the backends. Keys are
+/// compared with their `Eq` implementation in Rust, and with the
+/// equality of the backends in specifications: the two agree when
+/// `Eq` is structural (e.g. derived). Two maps are equal when they
+/// have the same keys, with equal values.
+#[derive(Clone, Debug)]
+pub struct Map<K: Eq, V>(Vec<(K, V)>);
+
+impl<K: Eq, V> Map<K, V> {
+    /// The empty map.
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// The number of keys of `self`.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Tells whether `self` has no key.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Tells whether `self` has a value for `key`.
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.position(key).is_some()
+    }
+
+    /// The value of `key` in `self`, if any.
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.position(key).map(|i| &self.0[i].1)
+    }
+
+    /// The value of `key` in `self`. This requires
+    /// `self.contains_key(key)`.
+    #[allow(clippy::should_implement_trait)]
+    pub fn index(&self, key: &K) -> &V {
+        self.get(key)
+            .expect("`Map::index`: the key is not in the map")
+    }
+
+    /// `self` where `key` is mapped to `value`.
+    pub fn update(mut self, key: K, value: V) -> Self {
+        match self.position(&key) {
+            Some(i) => self.0[i].1 = value,
+            None => self.0.push((key, value)),
+        }
+        self
+    }
+
+    /// `self` without `key`.
+    pub fn remove(mut self, key: &K) -> Self {
+        if let Some(i) = self.position(key) {
+            self.0.swap_remove(i);
+        }
+        self
+    }
+
+    /// The position of `key` in the entries of `self`, which have
+    /// distinct keys.
+    fn position(&self, key: &K) -> Option<usize> {
+        self.0.iter().position(|(k, _)| k == key)
+    }
+}
+
+impl<K: Eq, V> Default for Map<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Eq, V: PartialEq> PartialEq for Map<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.0.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
+
+impl<K: Eq, V: Eq> Eq for Map<K, V> {}
+
+#[cfg(feature = "std")]
+impl<K: Eq, V, S> Abstraction for std::collections::HashMap<K, V, S> {
+    type AbstractType = Map<K, V>;
+    fn lift(self) -> Self::AbstractType {
+        Map(self.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_update_remove() {
+        let m: Map<u8, &str> = Map::new();
+        assert!(m.is_empty());
+        assert_eq!(m.get(&1), None);
+        let m = m.update(1, "a").update(2, "b").update(1, "c");
+        assert_eq!(m.len(), 2);
+        assert!(m.contains_key(&2));
+        assert_eq!(m.get(&1), Some(&"c"));
+        assert_eq!(m.index(&2), &"b");
+        let m = m.remove(&1).remove(&3);
+        assert_eq!(m.len(), 1);
+        assert!(!m.contains_key(&1));
+        assert_eq!(m.get(&2), Some(&"b"));
+    }
+
+    #[test]
+    #[should_panic(expected = "the key is not in the map")]
+    fn index_of_missing_key() {
+        Map::<u8, u8>::new().update(1, 1).index(&2);
+    }
+
+    #[test]
+    fn extensional_equality() {
+        let m1 = Map::new().update(1, 'a').update(2, 'b');
+        let m2 = Map::new().update(2, 'b').update(1, 'a');
+        assert_eq!(m1, m2);
+        assert_eq!(m1.clone().remove(&1), Map::new().update(2, 'b'));
+        assert_ne!(m1.clone(), m2.clone().update(1, 'c'));
+        assert_ne!(m1, m2.update(3, 'c'));
+        assert_eq!(Map::<u8, u8>::default(), Map::new().update(1, 1).remove(&1));
+    }
+
+    /// Keys are not required to be ordered: they are compared with
+    /// `Eq`, like in the backends.
+    #[derive(Debug, PartialEq, Eq)]
+    struct Key(u8, u8);
+
+    #[test]
+    fn keys_are_compared_with_eq() {
+        let m = Map::new().update(Key(1, 0), 0).update(Key(1, 1), 1);
+        assert_eq!(m.len(), 2);
+        assert_eq!(m.get(&Key(1, 0)), Some(&0));
+        assert_eq!(m.get(&Key(1, 1)), Some(&1));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn lift_hash_map() {
+        let hash_map: std::collections::HashMap<_, _> = [(1, 'a'), (2, 'b')].into_iter().collect();
+        let m = hash_map.clone().lift();
+        assert_eq!(m.len(), hash_map.len());
+        assert_eq!(m, Map::new().update(2, 'b').update(1, 'a'));
+    }
+}
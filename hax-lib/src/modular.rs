@@ -0,0 +1,244 @@
+use crate::abstraction::*;
+use crate::int::Int;
+use core::fmt;
+use core::ops::*;
+
+/// Integers modulo `M`, for writing specifications about modular
+/// arithmetic, e.g. `result.lift_mod::<3329>() ==
+/// value.lift_mod::<3329>()`. Values are represented by their
+/// canonical representative in `0..M`, and operations never over or
+/// underflow. When `M` is prime, `ModInt<M>` is a field: see
+/// [`ModInt::inv`].
+///
+/// `ModInt` computes natively in Rust, and is extracted to the
+/// modular arithmetic of F* (`Hax_lib.Modular`), the only backend
+/// supporting it for now.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct ModInt<const M: u128>(u128);
+
+impl<const M: u128> ModInt<M> {
+    /// The class of `x` modulo `M`.
+    pub fn new(x: u128) -> Self {
+        const { core::assert!(M > 0, "the modulus of a `ModInt` must be positive") };
+        Self(x % M)
+    }
+
+    /// The canonical representative of `self`, in `0..M`.
+    pub fn value(self) -> u128 {
+        self.0
+    }
+
+    /// The modulus `M`.
+    pub fn modulus() -> u128 {
+        M
+    }
+
+    /// Raises `self` at the power `exp`.
+    pub fn pow(self, mut exp: u128) -> Self {
+        let mut base = self;
+        let mut result = Self::new(1);
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result * base;
+            }
+            base = base * base;
+            exp >>= 1;
+        }
+        result
+    }
+
+    /// The multiplicative inverse of `self`. This requires `M` to be
+    /// prime and `self` to be non-zero, and panics otherwise.
+    ///
+    /// In F*, `impl__inv` is an `assume val`: its specification
+    /// (`(r * self) % M == 1` when `M` is prime and `self` non-zero) is
+    /// trusted, not proven.
+    pub fn inv(self) -> Self {
+        // Fermat's little theorem: `x^(M-1) = 1` for `M` prime.
+        let inv = self.pow(M.saturating_sub(2));
+        core::assert!(
+            inv * self == Self::new(1),
+            "{self} is not invertible modulo {M}"
+        );
+        inv
+    }
+}
+
+impl<const M: u128> fmt::Display for ModInt<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl<const M: u128> Add for ModInt<M> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self::Output {
+        // Both operands are below `M`: one subtraction is enough.
+        let (sum, overflow) = self.0.overflowing_add(other.0);
+        if overflow || sum >= M {
+            Self(sum.wrapping_sub(M))
+        } else {
+            Self(sum)
+        }
+    }
+}
+
+impl<const M: u128> Sub for ModInt<M> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self::Output {
+        if self.0 >= other.0 {
+            Self(self.0 - other.0)
+        } else {
+            Self(M - (other.0 - self.0))
+        }
+    }
+}
+
+impl<const M: u128> Mul for ModInt<M> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self::Output {
+        if M <= u64::MAX as u128 {
+            return Self(self.0 * other.0 % M);
+        }
+        // Double-and-add, so that intermediate values stay below `2M`.
+        let mut result = Self(0);
+        let mut addend = self;
+        let mut factor = other.0;
+        while factor > 0 {
+            if factor & 1 == 1 {
+                result = result + addend;
+            }
+            addend = addend + addend;
+            factor >>= 1;
+        }
+        result
+    }
+}
+
+impl<const M: u128> Div for ModInt<M> {
+    type Output = Self;
+
+    /// Multiplies by the inverse of `other`, see [`ModInt::inv`].
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, other: Self) -> Self::Output {
+        self * other.inv()
+    }
+}
+
+impl<const M: u128> Abstraction for ModInt<M> {
+    type AbstractType = Int;
+    fn lift(self) -> Self::AbstractType {
+        self.0.lift()
+    }
+}
+
+/// Maps integers to their class modulo some `M`.
+pub trait LiftMod {
+    fn lift_mod<const M: u128>(self) -> ModInt<M>;
+}
+
+macro_rules! implement_lift_mod {
+    (unsigned $($ty:ident)*) => {
+        $(impl LiftMod for $ty {
+            fn lift_mod<const M: u128>(self) -> ModInt<M> {
+                ModInt::new(self as u128)
+            }
+        })*
+    };
+    (signed $($ty:ident)*) => {
+        $(impl LiftMod for $ty {
+            fn lift_mod<const M: u128>(self) -> ModInt<M> {
+                let magnitude = ModInt::new(self.unsigned_abs() as u128);
+                if self < 0 {
+                    ModInt::new(0) - magnitude
+                } else {
+                    magnitude
+                }
+            }
+        })*
+    };
+}
+
+implement_lift_mod!(unsigned u8 u16 u32 u64 u128 usize);
+implement_lift_mod!(signed i8 i16 i32 i64 i128 isize);
+
+impl LiftMod for Int {
+    #[cfg(hax)]
+    fn lift_mod<const M: u128>(self) -> ModInt<M> {
+        ModInt::new(self.rem_euclid(M.lift()).to_u128())
+    }
+    #[cfg(not(hax))]
+    fn lift_mod<const M: u128>(self) -> ModInt<M> {
+        self.0.lift_mod()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The largest prime below `2^128`.
+    const P128: u128 = u128::MAX - 158;
+
+    #[test]
+    fn reduction() {
+        assert_eq!(ModInt::<7>::new(23).value(), 2);
+        assert_eq!(ModInt::<7>::new(7).value(), 0);
+        assert_eq!(ModInt::<7>::modulus(), 7);
+        assert_eq!(ModInt::<1>::new(u128::MAX).value(), 0);
+        assert_eq!(ModInt::<P128>::new(u128::MAX).value(), 158);
+        assert_eq!(3u8.lift_mod::<7>(), ModInt::new(3));
+        assert_eq!((-3i32).lift_mod::<7>(), ModInt::new(4));
+        assert_eq!(i64::MIN.lift_mod::<10>(), ModInt::new(2));
+        assert_eq!((-14i8).lift_mod::<7>(), ModInt::new(0));
+    }
+
+    #[test]
+    fn arithmetic() {
+        let (a, b) = (ModInt::<7>::new(5), ModInt::<7>::new(4));
+        assert_eq!(a + b, ModInt::new(2));
+        assert_eq!(a - b, ModInt::new(1));
+        assert_eq!(b - a, ModInt::new(6));
+        assert_eq!(a * b, ModInt::new(6));
+        assert_eq!(a.pow(0), ModInt::new(1));
+        assert_eq!(a.pow(3), ModInt::new(6));
+    }
+
+    #[test]
+    fn arithmetic_near_u128_max() {
+        let a = ModInt::<P128>::new(P128 - 1);
+        let b = ModInt::<P128>::new(P128 - 2);
+        assert_eq!(a + b, ModInt::new(P128 - 3));
+        assert_eq!(ModInt::<P128>::new(0) - a, ModInt::new(1));
+        // `(-1) * (-2) = 2`
+        assert_eq!(a * b, ModInt::new(2));
+        assert_eq!(a.pow(2), ModInt::new(1));
+    }
+
+    #[test]
+    fn inverse() {
+        for x in 1..3329u128 {
+            let x = ModInt::<3329>::new(x);
+            assert_eq!(x * x.inv(), ModInt::new(1));
+            assert_eq!(x / x, ModInt::new(1));
+        }
+        let a = ModInt::<P128>::new(12345);
+        assert_eq!(a * a.inv(), ModInt::new(1));
+        assert_eq!(ModInt::<7>::new(6) / ModInt::new(3), ModInt::new(2));
+    }
+
+    #[test]
+    #[should_panic(expected = "is not invertible")]
+    fn inverse_of_zero() {
+        ModInt::<7>::new(0).inv();
+    }
+
+    #[test]
+    #[should_panic(expected = "is not invertible")]
+    fn inverse_modulo_composite() {
+        ModInt::<8>::new(2).inv();
+    }
+}
@@ -2,9 +2,9 @@
 //! proc-macro crate cannot export anything but procedural macros.
 
 pub use hax_lib_macros::{
-    attributes, decreases, ensures, exclude, impl_fn_decoration, include, lemma, loop_decreases,
-    loop_invariant, opaque, opaque_type, refinement_type, requires, trait_fn_decoration,
-    transparent,
+    attributes, check_loops, decreases, ensures, exclude, ghost, ghost_expr, impl_fn_decoration,
+    include, invariant, lemma, loop_decreases, loop_invariant, modeled_by, modifies, opaque,
+    opaque_type, property_test, pure, refinement_type, requires, trait_fn_decoration, transparent,
 };
 
 pub use hax_lib_macros::{
@@ -32,7 +32,7 @@ pub mod constructors {
     }
 
     pub fn implies(lhs: Prop, other: Prop) -> Prop {
-        Prop(lhs.0 || !other.0)
+        Prop(!lhs.0 || other.0)
     }
 
     pub fn forall<A, F: Fn(A) -> Prop>(_pred: F) -> Prop {
@@ -73,6 +73,13 @@ impl Prop {
     pub fn implies(self, other: impl Into<Self>) -> Self {
         constructors::implies(self, other.into())
     }
+    /// Evaluates the proposition at run-time, for the run-time checks
+    /// of refinement types. Quantifiers cannot be computed: they
+    /// evaluate to `true`.
+    #[doc(hidden)]
+    pub const fn _internal_to_bool(self) -> bool {
+        self.0
+    }
 }
 
 impl Abstraction for bool {
@@ -144,3 +151,21 @@ pub fn implies(lhs: impl Into<Prop>, rhs: impl Into<Prop>) -> Prop {
 }
 
 pub use constructors::eq;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn implication() {
+        for (lhs, rhs, expected) in [
+            (false, false, true),
+            (false, true, true),
+            (true, false, false),
+            (true, true, true),
+        ] {
+            assert_eq!(implies(lhs, rhs).0, expected, "{lhs} ==> {rhs}");
+            assert_eq!(Prop::from(lhs).implies(rhs).0, expected, "{lhs} ==> {rhs}");
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use core::fmt;
+
+/// The error returned by the fallible constructors of refinement
+/// types (see [`Refinement::try_new`]) when a value does not satisfy
+/// the invariant of the refinement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RefinementError {
+    refined_type: &'static str,
+}
+
+impl RefinementError {
+    /// Error for a value that does not satisfy the invariant of `T`.
+    pub fn new<T: ?Sized>() -> Self {
+        Self {
+            refined_type: core::any::type_name::<T>(),
+        }
+    }
+    /// The name of the refinement type whose invariant was violated.
+    pub fn refined_type(&self) -> &'static str {
+        self.refined_type
+    }
+}
+
+impl fmt::Display for RefinementError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "value does not satisfy the invariant of refinement type `{}`",
+            self.refined_type
+        )
+    }
+}
+
+impl core::error::Error for RefinementError {}
+
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub use serde as _serde;
+
+/// Expands to its input when the feature `serde` of `hax-lib` is
+/// enabled, and to nothing otherwise. This is used by the
+/// `refinement_type` macro.
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _internal_if_serde {
+    ($($tt:tt)*) => { $($tt)* };
+}
+
+/// Expands to its input when the feature `serde` of `hax-lib` is
+/// enabled, and to nothing otherwise. This is used by the
+/// `refinement_type` macro.
+#[cfg(not(feature = "serde"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! _internal_if_serde {
+    ($($tt:tt)*) => {};
+}
+
+/// A value whose refinement is checked in debug mode by the `new`
+/// constructors generated by the `refinement_type` macro. The check
+/// evaluates the invariant on a clone of the value: it is skipped
+/// when the type of the value does not implement `Clone` (see
+/// [`_InternalDebugCheckCloned`] and [`_InternalDebugCheckSkipped`]).
+#[doc(hidden)]
+pub struct _InternalDebugCheck<'a, T>(pub &'a T);
+
+/// Checks the invariant of a value that can be cloned. This is
+/// preferred by method resolution over [`_InternalDebugCheckSkipped`].
+#[doc(hidden)]
+pub trait _InternalDebugCheckCloned<T> {
+    fn check(&self, invariant: fn(T) -> crate::Prop) -> bool;
+}
+
+impl<T: Clone> _InternalDebugCheckCloned<T> for _InternalDebugCheck<'_, T> {
+    fn check(&self, invariant: fn(T) -> crate::Prop) -> bool {
+        invariant(self.0.clone())._internal_to_bool()
+    }
+}
+
+/// Skips the check of the invariant of a value that cannot be cloned.
+#[doc(hidden)]
+pub trait _InternalDebugCheckSkipped<T> {
+    fn check(&self, _invariant: fn(T) -> crate::Prop) -> bool {
+        true
+    }
+}
+
+impl<T> _InternalDebugCheckSkipped<T> for &_InternalDebugCheck<'_, T> {}
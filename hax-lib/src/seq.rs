@@ -0,0 +1,123 @@
+use crate::abstraction::*;
+use alloc::vec::Vec;
+
+/// Finite mathematical sequences, for writing specifications about
+/// slices, arrays and vectors, e.g. `output.lift() ==
+/// input.lift().update(i, x)`. Operations on sequences are pure: they
+/// consume their operands and return new sequences.
+///
+/// `Seq` computes natively in Rust (this requires the `alloc`
+/// feature), and is extracted to the sequences of the backends.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Seq<T>(Vec<T>);
+
+impl<T> Seq<T> {
+    /// The empty sequence.
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// The length of `self`.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Tells whether `self` is empty.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The `i`-th element of `self`. This requires `i < self.len()`.
+    #[allow(clippy::should_implement_trait)]
+    pub fn index(&self, i: usize) -> &T {
+        &self.0[i]
+    }
+
+    /// `self` where the `i`-th element is replaced by `x`. This
+    /// requires `i < self.len()`.
+    pub fn update(mut self, i: usize, x: T) -> Self {
+        self.0[i] = x;
+        self
+    }
+
+    /// The elements of `self` in `start..end`. This requires `start <=
+    /// end <= self.len()`.
+    pub fn slice(mut self, start: usize, end: usize) -> Self {
+        self.0.truncate(end);
+        self.0.drain(..start);
+        self
+    }
+
+    /// The elements of `self` followed by the elements of `other`.
+    pub fn concat(mut self, mut other: Self) -> Self {
+        self.0.append(&mut other.0);
+        self
+    }
+}
+
+impl<T> Default for Seq<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Abstraction for &[T] {
+    type AbstractType = Seq<T>;
+    fn lift(self) -> Self::AbstractType {
+        Seq(self.to_vec())
+    }
+}
+
+impl<T, const N: usize> Abstraction for [T; N] {
+    type AbstractType = Seq<T>;
+    fn lift(self) -> Self::AbstractType {
+        Seq(Vec::from(self))
+    }
+}
+
+impl<T> Abstraction for Vec<T> {
+    type AbstractType = Seq<T>;
+    fn lift(self) -> Self::AbstractType {
+        Seq(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn index_update_slice_concat() {
+        let s = vec![1, 2, 3, 4].lift();
+        assert_eq!(s.len(), 4);
+        assert!(!s.is_empty());
+        assert_eq!(s.index(2), &3);
+        let s = s.update(0, 0);
+        assert_eq!(s, [0, 2, 3, 4].lift());
+        assert_eq!(s.clone().slice(1, 3), [2, 3].lift());
+        assert_eq!(s.clone().slice(2, 2), Seq::new());
+        assert_eq!(s.clone().slice(0, 4), s);
+        assert_eq!([1, 2].lift().concat([3].lift()), [1, 2, 3].lift());
+        assert!(Seq::<u8>::default().is_empty());
+    }
+
+    #[test]
+    fn lift() {
+        let v = vec![1u8, 2, 3];
+        assert_eq!(v.as_slice().lift(), v.clone().lift());
+        assert_eq!([1u8, 2, 3].lift(), v.lift());
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_out_of_bounds() {
+        [1, 2].lift().index(2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn update_out_of_bounds() {
+        [1, 2].lift().update(2, 0);
+    }
+}
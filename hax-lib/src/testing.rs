@@ -0,0 +1,155 @@
+//! Property-based testing of lemmas and contracts outside of hax (see
+//! `hax_lib::property_test`): sampling of inputs, and evaluation of
+//! properties on these inputs.
+
+use core::fmt::Debug;
+
+/// A deterministic pseudo-random number generator (SplitMix64), so
+/// that failing tests are reproducible.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`, for a positive `n`.
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+}
+
+/// Types whose values can be sampled. Samples favor edge cases (e.g.
+/// `0`, `1` and the bounds of integer types) and small values, which
+/// are the most likely counterexamples to wrong properties.
+pub trait Arbitrary: Sized {
+    fn arbitrary(rng: &mut Rng) -> Self;
+}
+
+macro_rules! arbitrary_int {
+    ($($t:ty),*) => {
+        $(
+            impl Arbitrary for $t {
+                fn arbitrary(rng: &mut Rng) -> Self {
+                    match rng.below(8) {
+                        0 => [0, 1, <$t>::MIN, <$t>::MAX][rng.below(4) as usize],
+                        1..=3 => (rng.below(32) as $t).wrapping_sub((<$t>::MIN != 0) as $t * 16),
+                        _ => {
+                            let high = rng.next_u64() as u128;
+                            let low = rng.next_u64() as u128;
+                            ((high << 64) | low) as $t
+                        }
+                    }
+                }
+            }
+        )*
+    };
+}
+
+arbitrary_int!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl Arbitrary for bool {
+    fn arbitrary(rng: &mut Rng) -> Self {
+        rng.below(2) == 0
+    }
+}
+
+impl Arbitrary for () {
+    fn arbitrary(_rng: &mut Rng) -> Self {}
+}
+
+impl<T: Arbitrary> Arbitrary for Option<T> {
+    fn arbitrary(rng: &mut Rng) -> Self {
+        (rng.below(4) != 0).then(|| T::arbitrary(rng))
+    }
+}
+
+impl<T: Arbitrary, const N: usize> Arbitrary for [T; N] {
+    fn arbitrary(rng: &mut Rng) -> Self {
+        core::array::from_fn(|_| T::arbitrary(rng))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Arbitrary> Arbitrary for alloc::vec::Vec<T> {
+    fn arbitrary(rng: &mut Rng) -> Self {
+        let len = match rng.below(4) {
+            0 => 0,
+            1 | 2 => rng.below(8),
+            _ => rng.below(64),
+        };
+        (0..len).map(|_| T::arbitrary(rng)).collect()
+    }
+}
+
+macro_rules! arbitrary_tuple {
+    ($($t:ident),*) => {
+        impl<$($t: Arbitrary),*> Arbitrary for ($($t,)*) {
+            fn arbitrary(rng: &mut Rng) -> Self {
+                ($($t::arbitrary(rng),)*)
+            }
+        }
+    };
+}
+
+arbitrary_tuple!(A);
+arbitrary_tuple!(A, B);
+arbitrary_tuple!(A, B, C);
+arbitrary_tuple!(A, B, C, D);
+arbitrary_tuple!(A, B, C, D, E);
+arbitrary_tuple!(A, B, C, D, E, F);
+arbitrary_tuple!(A, B, C, D, E, F, G);
+arbitrary_tuple!(A, B, C, D, E, F, G, H);
+
+/// Checks the property `name` on `cases` sampled inputs. On an input,
+/// `property` returns `None` if the input does not satisfy the
+/// precondition of the property, and otherwise whether the property
+/// holds. Panics with a counterexample if the property does not hold
+/// or panics, or if no sampled input satisfies the precondition.
+pub fn check<T: Arbitrary + Clone + Debug>(
+    name: &str,
+    cases: usize,
+    property: impl Fn(T) -> Option<bool>,
+) {
+    // Seed the generator with a hash of `name` (FNV-1a), so that the
+    // properties are tested on different inputs.
+    let seed = name.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    let mut rng = Rng::new(seed);
+    let mut satisfying = 0;
+    // Give up after `16 * cases` samples, when most of them do not
+    // satisfy the precondition.
+    for _ in 0..16 * cases {
+        if satisfying == cases {
+            break;
+        }
+        let input = T::arbitrary(&mut rng);
+        // With `std`, report the inputs on which the property panics.
+        #[cfg(feature = "std")]
+        let outcome =
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| property(input.clone())))
+                .unwrap_or_else(|payload| {
+                    std::eprintln!("property `{name}` panics on the input {input:?}");
+                    std::panic::resume_unwind(payload)
+                });
+        #[cfg(not(feature = "std"))]
+        let outcome = property(input.clone());
+        match outcome {
+            None => (),
+            Some(true) => satisfying += 1,
+            Some(false) => panic!("property `{name}` does not hold on the input {input:?}"),
+        }
+    }
+    if satisfying == 0 {
+        panic!("no sampled input satisfies the precondition of the property `{name}`")
+    }
+}
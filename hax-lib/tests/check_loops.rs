@@ -0,0 +1,168 @@
+//! Run-time checks of loop specifications by `check_loops`, which
+//! only happen outside of hax: this workspace builds with `--cfg hax`
+//! (see `.cargo/config.toml`), run these tests with e.g.
+//! `RUSTFLAGS= cargo test -p hax-lib --features alloc,runtime-contracts`.
+#![cfg(all(not(hax), debug_assertions))]
+// `while let` loops are checked as such.
+#![allow(clippy::while_let_on_iterator)]
+
+/// Specifications on `Int`s are only evaluated with the
+/// `runtime-contracts` feature, which backs them with `i128`s.
+#[cfg(feature = "runtime-contracts")]
+mod with_ints {
+    use hax_lib::int::ToInt;
+
+    #[hax_lib::check_loops]
+    fn count_up(n: u32) -> u32 {
+        let mut i = 0;
+        while i < n {
+            hax_lib::loop_invariant!(i.to_int() <= n.to_int() && i.to_int() > hax_lib::int!(-1));
+            hax_lib::loop_decreases!((n - i).to_int());
+            i += 1;
+        }
+        i
+    }
+
+    #[hax_lib::check_loops]
+    fn count_up_wrong_invariant(n: u32) -> u32 {
+        let mut i = 0;
+        while i < n {
+            hax_lib::loop_invariant!(i.to_int() <= 3.to_int());
+            i += 1;
+        }
+        i
+    }
+
+    #[hax_lib::check_loops]
+    fn count_up_wrong_measure(n: u32) -> u32 {
+        let mut i = 0;
+        while i < n {
+            hax_lib::loop_decreases!(i.to_int());
+            i += 1;
+        }
+        i
+    }
+
+    #[hax_lib::check_loops]
+    fn count_down_negative_measure(n: i32) -> i32 {
+        let mut i = n;
+        while i > -2 {
+            hax_lib::loop_decreases!(i.to_int());
+            i -= 1;
+        }
+        i
+    }
+
+    #[hax_lib::check_loops]
+    fn sum_range(n: usize) -> usize {
+        let mut sum = 0;
+        for i in 0..n {
+            hax_lib::loop_invariant!(
+                |i: usize| sum.to_int() * 2.to_int() == i.to_int() * (i.to_int() - 1.to_int())
+            );
+            sum += i;
+        }
+        sum
+    }
+
+    #[test]
+    fn int_specs_hold() {
+        assert_eq!(count_up(5), 5);
+        assert_eq!(sum_range(10), 45);
+    }
+
+    #[test]
+    #[should_panic(expected = "loop invariant violated")]
+    fn int_invariant_violated() {
+        count_up_wrong_invariant(5);
+    }
+
+    #[test]
+    #[should_panic(expected = "does not decrease")]
+    fn int_measure_increases() {
+        count_up_wrong_measure(5);
+    }
+
+    #[test]
+    #[should_panic(expected = "is negative")]
+    fn int_measure_negative() {
+        count_down_negative_measure(3);
+    }
+}
+
+/// One-argument invariants of loops over general iterators only count
+/// iterations: they need neither `alloc` nor `Clone` items.
+struct NotClone;
+
+#[hax_lib::check_loops]
+fn count_items(items: &[NotClone]) -> usize {
+    let mut count = 0;
+    for _ in items.iter() {
+        hax_lib::loop_invariant!(|iterations: usize| count == iterations);
+        count += 1;
+    }
+    count
+}
+
+#[hax_lib::check_loops]
+fn count_items_wrong(items: &[u8]) -> usize {
+    let mut count = 0;
+    let mut it = items.iter();
+    while let Some(_) = it.next() {
+        hax_lib::loop_invariant!(|iterations: usize| count == iterations);
+        count += 2;
+    }
+    count
+}
+
+#[test]
+fn index_invariants_hold() {
+    assert_eq!(count_items(&[NotClone, NotClone, NotClone]), 3);
+}
+
+#[test]
+#[should_panic(expected = "loop invariant violated")]
+fn index_invariant_violated() {
+    count_items_wrong(&[1, 2, 3]);
+}
+
+#[cfg(feature = "alloc")]
+mod with_items {
+
+    #[hax_lib::check_loops]
+    fn sum_items(items: &[u8]) -> u32 {
+        let mut sum = 0u32;
+        for item in items.iter().copied() {
+            hax_lib::loop_invariant!(|iterations: usize, prefix: hax_lib::Seq<u8>| {
+                prefix.len() == iterations
+            });
+            sum += item as u32;
+        }
+        sum
+    }
+
+    #[hax_lib::check_loops]
+    fn first_items_small(items: &[u8]) -> usize {
+        let mut count = 0;
+        let mut it = items.iter().copied();
+        while let Some(item) = it.next() {
+            hax_lib::loop_invariant!(|_: usize, prefix: hax_lib::Seq<u8>| {
+                prefix.is_empty() || *prefix.index(prefix.len() - 1) < 10
+            });
+            count += usize::from(item);
+        }
+        count
+    }
+
+    #[test]
+    fn prefix_invariants_hold() {
+        assert_eq!(sum_items(&[1, 2, 3]), 6);
+        assert_eq!(first_items_small(&[1, 2, 3]), 6);
+    }
+
+    #[test]
+    #[should_panic(expected = "loop invariant violated")]
+    fn prefix_invariant_violated() {
+        first_items_small(&[1, 20, 3]);
+    }
+}
@@ -0,0 +1,67 @@
+//! Erasure of ghost code outside of hax: this workspace builds with
+//! `--cfg hax` (see `.cargo/config.toml`), run these tests with e.g.
+//! `RUSTFLAGS= cargo test -p hax-lib`.
+#![cfg(not(hax))]
+
+use hax_lib::*;
+
+#[ghost]
+fn triangle(n: u32) -> u64 {
+    if n == 0 {
+        0
+    } else {
+        triangle(n - 1) + n as u64
+    }
+}
+
+#[ghost]
+fn panics() -> u8 {
+    panic!("ghost code is executed")
+}
+
+#[attributes]
+mod summation {
+    use hax_lib::*;
+
+    #[ghost]
+    fn square(n: u64) -> u64 {
+        n * n
+    }
+
+    #[ghost]
+    fn lemma_square(n: u64) {
+        let _ = square(n);
+    }
+
+    pub struct Sums;
+
+    impl Sums {
+        #[ghost]
+        fn bound(&self, n: usize) -> u64 {
+            square(n as u64)
+        }
+
+        pub fn sum(&self, v: &[u32], #[ghost] limit: u32) -> u64 {
+            let mut s: u64 = 0;
+            #[ghost]
+            let mut steps: u64 = square(0);
+            for x in v {
+                s += *x as u64;
+                #[ghost]
+                steps += self.bound(1) + limit as u64;
+                #[ghost]
+                lemma_square(steps);
+            }
+            assume!(steps as usize == v.len());
+            s
+        }
+    }
+}
+
+#[test]
+fn ghost_functions_are_not_executed() {
+    let _: Ghost<u64> = triangle(3);
+    let _: Ghost<u8> = panics();
+    let sum = summation::Sums.sum(&[1, 2, 3], ghost_expr!(3));
+    assert_eq!(sum, 6);
+}
@@ -0,0 +1,98 @@
+//! Run-time checks of frame conditions by `modifies`, which only
+//! happen outside of hax with the `runtime-contracts` feature: this
+//! workspace builds with `--cfg hax` (see `.cargo/config.toml`), run
+//! these tests with e.g.
+//! `RUSTFLAGS= cargo test -p hax-lib --features runtime-contracts`.
+#![cfg(all(not(hax), feature = "runtime-contracts"))]
+
+use hax_lib::modifies;
+
+#[derive(Clone, PartialEq)]
+struct Pair {
+    first: u8,
+    second: (u8, u8),
+}
+
+#[modifies(x[1..], y.second.0)]
+fn bump_tail(x: &mut [u8], y: &mut Pair, z: &mut u8) -> usize {
+    for x in &mut x[1..] {
+        *x = x.wrapping_add(*z);
+    }
+    y.second.0 = *z;
+    x.len()
+}
+
+#[modifies(x[1..])]
+fn bump_head(x: &mut [u8]) {
+    x[0] += 1;
+}
+
+#[modifies(x[i])]
+fn set(x: &mut Vec<u8>, i: usize, value: u8, wrong: bool) {
+    x[if wrong { i + 1 } else { i }] = value;
+}
+
+#[modifies(y.second.0)]
+fn set_first(y: &mut Pair) {
+    y.first = 1;
+}
+
+#[modifies()]
+fn touch(z: &mut u8) {
+    *z += 1;
+}
+
+#[derive(Clone, PartialEq)]
+struct Counter(u32, u32);
+
+impl Counter {
+    #[modifies(self.0)]
+    fn increment(&mut self) -> u32 {
+        self.0 += 1;
+        self.0
+    }
+}
+
+#[test]
+fn frames_respected() {
+    let mut x = [1, 2, 3];
+    let mut y = Pair {
+        first: 0,
+        second: (0, 0),
+    };
+    let mut z = 5;
+    assert_eq!(bump_tail(&mut x, &mut y, &mut z), 3);
+    assert!(x == [1, 7, 8] && y.second == (5, 0) && z == 5);
+    let mut v = vec![0; 3];
+    set(&mut v, 1, 4, false);
+    assert_eq!(v, [0, 4, 0]);
+    let mut counter = Counter(0, 0);
+    assert_eq!(counter.increment(), 1);
+}
+
+#[test]
+#[should_panic(expected = "`bump_head` modifies `x` outside of its `modifies` clause")]
+fn index_outside_of_the_frame() {
+    bump_head(&mut [1, 2, 3]);
+}
+
+#[test]
+#[should_panic(expected = "`set` modifies `x` outside of its `modifies` clause")]
+fn index_outside_of_the_frame_vec() {
+    set(&mut vec![0; 3], 1, 4, true);
+}
+
+#[test]
+#[should_panic(expected = "`set_first` modifies `y` outside of its `modifies` clause")]
+fn field_outside_of_the_frame() {
+    set_first(&mut Pair {
+        first: 0,
+        second: (0, 0),
+    });
+}
+
+#[test]
+#[should_panic(expected = "`touch` modifies `z` outside of its `modifies` clause")]
+fn empty_frame() {
+    touch(&mut 0);
+}
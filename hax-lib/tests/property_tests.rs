@@ -0,0 +1,46 @@
+//! Property tests generated by `property_test`, which only exist
+//! outside of hax: this workspace builds with `--cfg hax` (see
+//! `.cargo/config.toml`), run these tests with e.g.
+//! `RUSTFLAGS= cargo test -p hax-lib --features runtime-contracts`.
+#![cfg(not(hax))]
+
+use hax_lib::*;
+
+#[property_test]
+#[requires(x < 100)]
+#[ensures(|result| result >= x && result % 2 == 0)]
+pub fn double(x: u32) -> u32 {
+    x * 2
+}
+
+#[cfg(feature = "alloc")]
+#[property_test]
+#[requires(v.len() < 4)]
+#[ensures(|_| future(v).len() == v.len() + 1)]
+pub fn push_zero(v: &mut Vec<u8>) {
+    v.push(0)
+}
+
+/// Specifications on `Int`s are only evaluated with the
+/// `runtime-contracts` feature, which backs them with `i128`s.
+#[cfg(feature = "runtime-contracts")]
+mod with_ints {
+    use hax_lib::int::ToInt;
+    use hax_lib::*;
+
+    #[property_test]
+    #[ensures(|result| result.to_int() == x.to_int() + y.to_int())]
+    pub fn widening_add(x: u64, y: u64) -> u128 {
+        x as u128 + y as u128
+    }
+
+    // Holds on mathematical integers, which `Int`s are.
+    #[property_test(cases = 1000)]
+    #[lemma]
+    pub fn successor_is_greater(x: u64) -> Proof<{ x.to_int() + 1.to_int() > x.to_int() }> {}
+}
+
+// Quantifiers cannot be evaluated: the test is ignored.
+#[property_test]
+#[lemma]
+pub fn all_are_small(x: u8) -> Proof<{ forall(|y: u8| y <= x) }> {}
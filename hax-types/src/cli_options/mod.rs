@@ -51,6 +51,50 @@ pub enum PathOrDash {
     Path(PathBuf),
 }
 
+/// A position in a file. Lines and columns start at 1.
+#[derive_group(Serializers)]
+#[derive(JsonSchema, Debug, Clone, PartialEq, Eq)]
+pub struct FilePosition {
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: Option<usize>,
+}
+
+impl std::str::FromStr for FilePosition {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let number = |n: &str| n.parse::<usize>().ok().filter(|n| *n > 0);
+        let invalid = || format!("expected `<FILE>:<LINE>` or `<FILE>:<LINE>:<COLUMN>`, got `{s}`");
+        let (rest, last) = s.rsplit_once(':').ok_or_else(invalid)?;
+        let last = number(last).ok_or_else(invalid)?;
+        let line_and_column = rest
+            .rsplit_once(':')
+            .and_then(|(path, line)| Some((path, number(line)?)));
+        Ok(match line_and_column {
+            Some((path, line)) => FilePosition {
+                path: path.into(),
+                line,
+                column: Some(last),
+            },
+            None => FilePosition {
+                path: rest.into(),
+                line: last,
+                column: None,
+            },
+        })
+    }
+}
+
+impl fmt::Display for FilePosition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.path.display(), self.line)?;
+        if let Some(column) = self.column {
+            write!(f, ":{column}")?;
+        }
+        Ok(())
+    }
+}
+
 impl std::convert::From<&str> for PathOrDash {
     fn from(s: &str) -> Self {
         match s {
@@ -333,6 +377,23 @@ pub struct BackendOptions<E: Extension> {
     #[arg(long)]
     pub stats: bool,
 
+    /// Writes a JSON report to `<REPORT>` giving the status of every
+    /// item: translated, failed (with the error codes), excluded (by
+    /// an inclusion clause or by `#[hax_lib::exclude]`), opaque or
+    /// signature-only. A summary grouped by module is also printed.
+    #[arg(long)]
+    pub report: Option<PathBuf>,
+
+    /// Maximum number of errors reported for a single item. Further
+    /// errors are not displayed, but are counted in a summary.
+    #[arg(long)]
+    pub max_errors_per_item: Option<usize>,
+
+    /// Maximum number of errors reported for a single error code.
+    /// Further errors are not displayed, but are counted in a summary.
+    #[arg(long)]
+    pub max_errors_per_code: Option<usize>,
+
     /// Enables profiling for the engine: for each phase of the
     /// engine, time and memory usage are recorded and reported.
     #[arg(long)]
@@ -420,6 +481,22 @@ pub enum Command<E: Extension> {
         include_extra: bool,
     },
 
+    /// Map a position in a file produced by a backend back to the Rust
+    /// code it was translated from, or a position in a Rust file to
+    /// the backend code translated from it. This relies on the source
+    /// maps (`.map` files) written next to the translated files.
+    Locate {
+        /// Position to look up, as `<FILE>:<LINE>` or
+        /// `<FILE>:<LINE>:<COLUMN>`. When `<FILE>` has no source map,
+        /// it is considered to be a Rust file.
+        position: FilePosition,
+
+        /// Directory searched for source maps when looking up a
+        /// position in a Rust file.
+        #[arg(long, default_value = ".")]
+        dir: PathBuf,
+    },
+
     #[command(flatten)]
     CliExtension(E::Command),
 }
@@ -521,6 +598,10 @@ impl<E: Extension> NormalizePaths for Command<E> {
         use Command::*;
         match self {
             JSON { output_file, .. } => output_file.normalize_paths(),
+            Backend(BackendOptions {
+                report: Some(report),
+                ..
+            }) => report.normalize_paths(),
             _ => (),
         }
     }
@@ -542,3 +623,41 @@ impl From<Options> for hax_frontend_exporter_options::Options {
 }
 
 pub const ENV_VAR_OPTIONS_FRONTEND: &str = "DRIVER_HAX_FRONTEND_OPTS";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn position(path: &str, line: usize, column: Option<usize>) -> FilePosition {
+        FilePosition {
+            path: path.into(),
+            line,
+            column,
+        }
+    }
+
+    #[test]
+    fn file_position_from_str() {
+        let parse = |s: &str| s.parse::<FilePosition>();
+        assert_eq!(parse("src/lib.rs:3"), Ok(position("src/lib.rs", 3, None)));
+        assert_eq!(
+            parse("src/lib.rs:3:7"),
+            Ok(position("src/lib.rs", 3, Some(7)))
+        );
+        // Only the trailing numbers are positions.
+        assert_eq!(parse("a:b:3"), Ok(position("a:b", 3, None)));
+        assert_eq!(parse("a:2:b:3"), Ok(position("a:2:b", 3, None)));
+        // Lines and columns start at 1.
+        assert!(parse("src/lib.rs:0").is_err());
+        assert!(parse("src/lib.rs:3:0").is_err());
+        assert!(parse("src/lib.rs").is_err());
+        assert!(parse("src/lib.rs:x").is_err());
+    }
+
+    #[test]
+    fn file_position_display() {
+        for s in ["src/lib.rs:3", "src/lib.rs:3:7"] {
+            assert_eq!(s.parse::<FilePosition>().unwrap().to_string(), s);
+        }
+    }
+}
@@ -27,6 +27,32 @@ pub enum HaxMessage {
     Stats {
         errors_per_item: Vec<(hax_frontend_exporter::DefId, usize)>,
     } = 6,
+    TranslationReport {
+        path: PathBuf,
+        summary: Vec<crate::translation_report::ModuleSummary>,
+    } = 7,
+    TranslationReportFailure {
+        path: PathBuf,
+        message: String,
+    } = 8,
+    DriverError {
+        crate_name: String,
+        message: String,
+    } = 9,
+    SuppressedDiagnostics {
+        /// Number of diagnostics identical to an already reported one
+        duplicates: usize,
+        /// Number of diagnostics dropped because of a budget
+        /// (`--max-errors-per-item` or `--max-errors-per-code`), by
+        /// error code
+        over_budget: Vec<(String, usize)>,
+    } = 10,
+    Located {
+        position: crate::cli_options::FilePosition,
+        /// Positions corresponding to `position`, in Rust code or in
+        /// translated code
+        found: Vec<crate::cli_options::FilePosition>,
+    } = 11,
 }
 
 impl HaxMessage {
@@ -1,6 +1,10 @@
 use crate::prelude::*;
 
-pub const HAX_DRIVER_STDERR_PREFIX: &str = "::hax-driver::";
+/// Extension of the files in which the driver writes its messages for
+/// `cargo hax`. Such a file is written next to the `.rmeta` file of
+/// the crate being compiled: for `libfoo-1234.rmeta`, the messages
+/// are written to `libfoo-1234.hax-driver.json`.
+pub const HAX_DRIVER_MESSAGES_EXTENSION: &str = "hax-driver.json";
 
 #[derive_group(Serializers)]
 #[derive(Debug, Clone)]
@@ -13,6 +17,64 @@ pub struct EmitHaxMetaMessage {
 #[derive(Debug, Clone)]
 pub enum HaxDriverMessage {
     EmitHaxMeta(EmitHaxMetaMessage),
+    /// The driver could not export a crate
+    Error {
+        crate_name: String,
+        message: String,
+    },
+    /// Time spent by the driver on a crate
+    ProfilingData(crate::engine_api::ProfilingData),
+}
+
+/// The messages emitted by the driver while compiling one crate.
+///
+/// Those messages are not printed on stderr: they are written in a
+/// file next to the artifacts of the crate (see
+/// [`HAX_DRIVER_MESSAGES_EXTENSION`]). When `cargo` decides a crate
+/// is fresh, the driver is not run again, and `cargo hax` reuses the
+/// messages of the previous compilation. Thus, `key` identifies the
+/// options the messages were produced with: stale messages produced
+/// with different options are ignored.
+#[derive_group(Serializers)]
+#[derive(Debug, Clone)]
+pub struct HaxDriverMessages {
+    pub key: u64,
+    pub messages: Vec<HaxDriverMessage>,
+}
+
+impl HaxDriverMessages {
+    /// Computes the key of a set of messages given the options (as
+    /// serialized in the environment variable
+    /// [`crate::cli_options::ENV_VAR_OPTIONS_FRONTEND`]) and the cache
+    /// key of hax.
+    pub fn key(options: &str, cache_key: &str) -> u64 {
+        use std::hash::{DefaultHasher, Hash, Hasher};
+        let mut s = DefaultHasher::new();
+        (options, cache_key).hash(&mut s);
+        s.finish()
+    }
+
+    /// The path of the messages file corresponding to the `.rmeta`
+    /// file `rmeta`.
+    pub fn path(rmeta: &Path) -> PathBuf {
+        rmeta.with_extension(HAX_DRIVER_MESSAGES_EXTENSION)
+    }
+
+    pub fn write(&self, path: &Path) -> std::io::Result<()> {
+        // Write in a temporary file first so that a reader never sees
+        // partial messages.
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_vec(self)?)?;
+        std::fs::rename(tmp, path)
+    }
+
+    /// Reads the messages at `path`, if they exist and if they were
+    /// produced with the key `key`.
+    pub fn read(path: &Path, key: u64) -> Option<Self> {
+        let contents = std::fs::read(path).ok()?;
+        let messages: Self = serde_json::from_slice(&contents).ok()?;
+        (messages.key == key).then_some(messages)
+    }
 }
 
 #[derive_group(Serializers)]
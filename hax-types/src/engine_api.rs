@@ -28,7 +28,78 @@ pub struct SourceMap {
     pub file: String,
 }
 
+/// A segment of a source map: a position in a generated file and the
+/// position in `sources[source]` it was generated from. Lines and
+/// columns start at 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceMapSegment {
+    pub gen_line: usize,
+    pub gen_column: usize,
+    pub source: usize,
+    pub src_line: usize,
+    pub src_column: usize,
+}
+
+/// Decodes a base64 VLQ (as used in source maps) into a list of
+/// numbers. Returns `None` on invalid digits, on numbers that do not
+/// fit in 63 bits and on a truncated last number.
+fn decode_vlq(s: &str) -> Option<Vec<i64>> {
+    const BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut values = vec![];
+    let (mut value, mut shift) = (0u64, 0u32);
+    for c in s.bytes() {
+        let digit = BASE64.iter().position(|b| *b == c)? as u64;
+        let chunk = (digit & 0b11111).checked_shl(shift)?;
+        if chunk >> shift != digit & 0b11111 {
+            return None;
+        }
+        value |= chunk;
+        shift += 5;
+        if digit & 0b100000 == 0 {
+            let magnitude = (value >> 1) as i64;
+            values.push(if value & 1 == 1 {
+                -magnitude
+            } else {
+                magnitude
+            });
+            (value, shift) = (0, 0);
+        }
+    }
+    (shift == 0).then_some(values)
+}
+
 impl SourceMap {
+    /// Decodes the `mappings` field of the source map. Returns `None`
+    /// if it is malformed.
+    pub fn segments(&self) -> Option<Vec<SourceMapSegment>> {
+        let mut segments = vec![];
+        // Every field but the generated column is relative to the
+        // previous segment, the generated column is relative to the
+        // previous segment on the same line.
+        let (mut source, mut src_line, mut src_column) = (0i64, 0i64, 0i64);
+        for (gen_line, line) in self.mappings.split(';').enumerate() {
+            let mut gen_column = 0i64;
+            for segment in line.split(',').filter(|segment| !segment.is_empty()) {
+                let fields = decode_vlq(segment)?;
+                gen_column += fields.first()?;
+                let [_, d_source, d_src_line, d_src_column, ..] = fields[..] else {
+                    continue;
+                };
+                source += d_source;
+                src_line += d_src_line;
+                src_column += d_src_column;
+                segments.push(SourceMapSegment {
+                    gen_line,
+                    gen_column: gen_column.try_into().ok()?,
+                    source: source.try_into().ok()?,
+                    src_line: src_line.try_into().ok()?,
+                    src_column: src_column.try_into().ok()?,
+                });
+            }
+        }
+        Some(segments)
+    }
+
     pub fn inline_sources_content(&mut self) {
         self.sourcesContent = vec![];
         for source in &self.sources {
@@ -78,6 +149,24 @@ pub struct ProfilingData {
     pub errored: bool,
 }
 
+/// How the engine decided to handle an item before translating it.
+#[derive_group(Serializers)]
+#[derive(JsonSchema, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemInclusion {
+    /// The item is translated with its body
+    IncludedWithBody,
+    /// Only the signature of the item is translated (`+:` inclusion
+    /// clause)
+    IncludedSignatureOnly,
+    /// The item is translated as an opaque definition
+    /// (e.g. `#[hax_lib::opaque]`)
+    IncludedOpaque,
+    /// The item was dropped by an inclusion clause (`-i`)
+    ExcludedByClause,
+    /// The item was dropped by an attribute (`#[hax_lib::exclude]`)
+    ExcludedByAttribute,
+}
+
 pub mod protocol {
     use super::*;
 
@@ -92,6 +181,9 @@ pub mod protocol {
         ProfilingData(ProfilingData),
         /// Declares a list of items that will be processed by the engine
         ItemProcessed(Vec<hax_frontend_exporter::DefId>),
+        /// Declares how each imported item is handled by the engine,
+        /// sent only when a report is requested (`--report`)
+        ItemInclusions(Vec<(hax_frontend_exporter::DefId, ItemInclusion)>),
         Exit,
         Ping,
     }
@@ -117,3 +209,66 @@ pub struct WithDefIds<Body: hax_frontend_exporter::IsBody> {
     pub items: Vec<hax_frontend_exporter::Item<Body>>,
     pub comments: Vec<(hax_frontend_exporter::Span, String)>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vlq() {
+        assert_eq!(decode_vlq("AAAA"), Some(vec![0, 0, 0, 0]));
+        assert_eq!(decode_vlq("CDgBhB"), Some(vec![1, -1, 16, -16]));
+        assert_eq!(decode_vlq(""), Some(vec![]));
+        // Invalid digit
+        assert_eq!(decode_vlq("A!"), None);
+        // Truncated number
+        assert_eq!(decode_vlq("Ag"), None);
+        // Numbers longer than 63 bits
+        assert_eq!(decode_vlq(&format!("{}Q", "g".repeat(12))), None);
+        assert_eq!(decode_vlq(&format!("{}B", "g".repeat(13))), None);
+        assert_eq!(decode_vlq(&format!("{}B", "g".repeat(100))), None);
+        // The largest number fits
+        assert_eq!(
+            decode_vlq(&format!("+{}P", "/".repeat(11))),
+            Some(vec![i64::MAX])
+        );
+    }
+
+    fn source_map(mappings: &str) -> SourceMap {
+        SourceMap {
+            mappings: mappings.to_string(),
+            sourceRoot: String::new(),
+            sources: vec!["a.rs".to_string(), "b.rs".to_string()],
+            sourcesContent: vec![],
+            names: vec![],
+            version: 3,
+            file: "out.fst".to_string(),
+        }
+    }
+
+    #[test]
+    fn segments() {
+        let segment = |gen_line, gen_column, source, src_line, src_column| SourceMapSegment {
+            gen_line,
+            gen_column,
+            source,
+            src_line,
+            src_column,
+        };
+        // The generated column is relative to the previous segment of
+        // the line, the other fields to the previous segment.
+        assert_eq!(
+            source_map("AAAA,EAAE;;ECEC,CAAD").segments(),
+            Some(vec![
+                segment(0, 0, 0, 0, 0),
+                segment(0, 2, 0, 0, 2),
+                segment(2, 2, 1, 2, 3),
+                segment(2, 3, 1, 2, 2),
+            ])
+        );
+        // Segments without a source are skipped.
+        assert_eq!(source_map("A,CAAA").segments().map(|s| s.len()), Some(1));
+        assert_eq!(source_map("AAAA,DAAA").segments(), None);
+        assert_eq!(source_map("AAAA;ggggggggggggggB").segments(), None);
+    }
+}
@@ -27,5 +27,8 @@ pub mod driver_api;
 /// `hax-engine`.
 pub mod engine_api;
 
+/// The per-item report produced by `cargo hax into --report`.
+pub mod translation_report;
+
 /// Compile-time version of hax
 pub const HAX_VERSION: &str = env!("HAX_VERSION");
@@ -0,0 +1,224 @@
+use crate::engine_api::ItemInclusion;
+use crate::prelude::*;
+use hax_frontend_exporter::{DefId, DefKind, DefPathItem};
+use std::collections::BTreeMap;
+
+/// The status of an item after a run of `cargo hax into`.
+#[derive_group(Serializers)]
+#[derive(JsonSchema, Debug, Clone, PartialEq, Eq)]
+pub enum ItemStatus {
+    /// The item was translated successfully
+    Translated,
+    /// Only the signature of the item was translated
+    SignatureOnly,
+    /// The item was translated as an opaque definition
+    Opaque,
+    /// The translation of the item failed with the given error codes
+    Failed { codes: Vec<String> },
+    /// The item was excluded by an inclusion clause (`-i`)
+    ExcludedByClause,
+    /// The item was excluded by `#[hax_lib::exclude]`
+    ExcludedByAttribute,
+}
+
+impl ItemStatus {
+    /// Computes the status of an item given how the engine included
+    /// it and the error codes reported for it.
+    pub fn new(inclusion: ItemInclusion, codes: Vec<String>) -> Self {
+        match inclusion {
+            ItemInclusion::ExcludedByClause => Self::ExcludedByClause,
+            ItemInclusion::ExcludedByAttribute => Self::ExcludedByAttribute,
+            _ if !codes.is_empty() => Self::Failed { codes },
+            ItemInclusion::IncludedWithBody => Self::Translated,
+            ItemInclusion::IncludedSignatureOnly => Self::SignatureOnly,
+            ItemInclusion::IncludedOpaque => Self::Opaque,
+        }
+    }
+}
+
+#[derive_group(Serializers)]
+#[derive(JsonSchema, Debug, Clone)]
+pub struct ItemReport {
+    /// Rust path of the item, e.g. `krate::module::function`
+    pub path: String,
+    /// Rust path of the module the item belongs to
+    pub module: String,
+    pub status: ItemStatus,
+}
+
+impl ItemReport {
+    pub fn new(def_id: &DefId, status: ItemStatus) -> Self {
+        let module = std::iter::successors(def_id.parent.as_ref(), |def_id| def_id.parent.as_ref())
+            .find(|def_id| matches!(def_id.kind, DefKind::Mod))
+            .map(def_id_to_string)
+            .unwrap_or_else(|| def_id.krate.clone());
+        Self {
+            path: def_id_to_string(def_id),
+            module,
+            status,
+        }
+    }
+}
+
+/// Renders a `DefId` as a Rust-like path.
+fn def_id_to_string(def_id: &DefId) -> String {
+    std::iter::once(def_id.krate.clone())
+        .chain(def_id.path.iter().map(|item| match &item.data {
+            DefPathItem::TypeNs(s)
+            | DefPathItem::ValueNs(s)
+            | DefPathItem::MacroNs(s)
+            | DefPathItem::LifetimeNs(s) => s.clone(),
+            DefPathItem::Impl => format!("{{impl#{}}}", item.disambiguator),
+            data => format!("{{{data:?}#{}}}", item.disambiguator),
+        }))
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+/// A machine-readable report of a translation, written by `cargo hax
+/// into --report <FILE>`.
+#[derive_group(Serializers)]
+#[derive(JsonSchema, Debug, Clone)]
+pub struct TranslationReport {
+    pub hax_version: String,
+    pub backend: String,
+    pub items: Vec<ItemReport>,
+}
+
+/// Number of items per status in a module.
+#[derive_group(Serializers)]
+#[derive(JsonSchema, Debug, Clone, Default)]
+pub struct ModuleSummary {
+    pub module: String,
+    pub translated: usize,
+    pub signature_only: usize,
+    pub opaque: usize,
+    pub failed: usize,
+    pub excluded: usize,
+}
+
+impl TranslationReport {
+    /// Writes the report as pretty-printed JSON to `path`.
+    pub fn write(&self, path: &Path) -> std::io::Result<()> {
+        use std::io::Write;
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        serde_json::to_writer_pretty(&mut file, self)?;
+        file.flush()
+    }
+
+    /// Groups the items of the report by module.
+    pub fn summary(&self) -> Vec<ModuleSummary> {
+        let mut modules: BTreeMap<&str, ModuleSummary> = BTreeMap::new();
+        for item in &self.items {
+            let summary = modules
+                .entry(&item.module)
+                .or_insert_with(|| ModuleSummary {
+                    module: item.module.clone(),
+                    ..Default::default()
+                });
+            match item.status {
+                ItemStatus::Translated => summary.translated += 1,
+                ItemStatus::SignatureOnly => summary.signature_only += 1,
+                ItemStatus::Opaque => summary.opaque += 1,
+                ItemStatus::Failed { .. } => summary.failed += 1,
+                ItemStatus::ExcludedByClause | ItemStatus::ExcludedByAttribute => {
+                    summary.excluded += 1
+                }
+            }
+        }
+        modules.into_values().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(module: &str, name: &str, status: ItemStatus) -> ItemReport {
+        ItemReport {
+            path: format!("{module}::{name}"),
+            module: module.to_string(),
+            status,
+        }
+    }
+
+    fn report() -> TranslationReport {
+        TranslationReport {
+            hax_version: "0.0.0".to_string(),
+            backend: "fstar".to_string(),
+            items: vec![
+                item("krate::a", "f", ItemStatus::Translated),
+                item(
+                    "krate::a",
+                    "g",
+                    ItemStatus::Failed {
+                        codes: vec!["HAX0001".to_string()],
+                    },
+                ),
+                item("krate::a", "h", ItemStatus::ExcludedByAttribute),
+                item("krate", "main", ItemStatus::Opaque),
+                item("krate", "S", ItemStatus::SignatureOnly),
+                item("krate", "T", ItemStatus::ExcludedByClause),
+            ],
+        }
+    }
+
+    #[test]
+    fn status_of_inclusion() {
+        let codes = vec!["HAX0001".to_string()];
+        assert_eq!(
+            ItemStatus::new(ItemInclusion::IncludedWithBody, vec![]),
+            ItemStatus::Translated
+        );
+        assert_eq!(
+            ItemStatus::new(ItemInclusion::IncludedWithBody, codes.clone()),
+            ItemStatus::Failed {
+                codes: codes.clone()
+            }
+        );
+        // Excluded items have no error, whatever the codes.
+        assert_eq!(
+            ItemStatus::new(ItemInclusion::ExcludedByClause, codes),
+            ItemStatus::ExcludedByClause
+        );
+    }
+
+    #[test]
+    fn summary_by_module() {
+        let summary = report().summary();
+        let modules: Vec<_> = summary.iter().map(|m| m.module.as_str()).collect();
+        assert_eq!(modules, ["krate", "krate::a"]);
+        let (krate, a) = (&summary[0], &summary[1]);
+        assert_eq!(
+            (
+                krate.translated,
+                krate.signature_only,
+                krate.opaque,
+                krate.excluded
+            ),
+            (0, 1, 1, 1)
+        );
+        assert_eq!((a.translated, a.failed, a.excluded), (1, 1, 1));
+    }
+
+    #[test]
+    fn write_report() {
+        let path = std::env::temp_dir().join(format!("hax-report-{}.json", std::process::id()));
+        report().write(&path).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(json["backend"], "fstar");
+        assert_eq!(json["items"][0]["path"], "krate::a::f");
+        assert_eq!(json["items"][0]["status"], "Translated");
+        assert_eq!(json["items"][1]["status"]["Failed"]["codes"][0], "HAX0001");
+    }
+
+    #[test]
+    fn write_report_to_missing_directory() {
+        let path = std::env::temp_dir()
+            .join("hax-missing-directory")
+            .join("report.json");
+        assert!(report().write(&path).is_err());
+    }
+}
@@ -0,0 +1,32 @@
+#![allow(dead_code)]
+
+#[hax_lib::decreases((m, n))]
+fn ackermann(m: u64, n: u64) -> u64 {
+    if m == 0 {
+        n + 1
+    } else if n == 0 {
+        ackermann(m - 1, 1)
+    } else {
+        ackermann(m - 1, ackermann(m, n - 1))
+    }
+}
+
+#[hax_lib::decreases(input.len(), rank = 1)]
+fn parse_list(input: &[u8]) -> usize {
+    if input.is_empty() {
+        0
+    } else {
+        parse_item(input)
+    }
+}
+
+#[hax_lib::decreases(input.len(), rank = 0)]
+fn parse_item(input: &[u8]) -> usize {
+    if input.is_empty() {
+        0
+    } else if input[0] == b'(' {
+        1 + parse_list(&input[1..])
+    } else {
+        1 + parse_item(&input[1..])
+    }
+}
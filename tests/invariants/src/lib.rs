@@ -0,0 +1,31 @@
+#![allow(dead_code)]
+use hax_lib::Invariant;
+
+#[hax_lib::invariant(|self_| self_.lo <= self_.hi)]
+struct Interval {
+    lo: u64,
+    hi: u64,
+}
+
+#[hax_lib::requires(lo <= hi)]
+fn interval(lo: u64, hi: u64) -> Interval {
+    Interval { lo, hi }.checked()
+}
+
+fn width(i: Interval) -> u64 {
+    i.hi - i.lo
+}
+
+#[hax_lib::invariant(|self_| match self_ {
+    Shape::Circle(radius) => *radius > 0,
+    Shape::Rectangle(width, height) => *width > 0 && *height > 0,
+})]
+enum Shape {
+    Circle(u32),
+    Rectangle(u32, u32),
+}
+
+#[hax_lib::requires(radius > 0)]
+fn circle(radius: u32) -> Shape {
+    Shape::Circle(radius).checked()
+}
@@ -299,3 +299,15 @@ mod and_mut_side_effect_loop {
         }
     }
 }
+
+mod while_let_loops {
+    // `it` is dead after the loop, which is reconstructed as a `for` loop.
+    fn consumed_iterator(arr: &[u8]) -> u32 {
+        let mut acc = 0;
+        let mut it = arr.iter();
+        while let Some(item) = it.next() {
+            acc = acc + *item as u32;
+        }
+        acc
+    }
+}
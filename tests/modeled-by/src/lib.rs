@@ -0,0 +1,106 @@
+//! Items replaced with their models by `#[hax_lib::modeled_by]`: the
+//! call sites of the modeled items call the models in the extraction.
+
+/// A model of `add`, in plain Rust.
+fn add_model(x: [u32; 4], y: [u32; 4]) -> [u32; 4] {
+    core::array::from_fn(|i| x[i].wrapping_add(y[i]))
+}
+
+#[hax_lib::modeled_by(add_model)]
+pub fn add(x: [u32; 4], y: [u32; 4]) -> [u32; 4] {
+    let mut result = [0; 4];
+    for i in 0..4 {
+        result[i] = x[i].wrapping_add(y[i]);
+    }
+    result
+}
+
+pub fn double(x: [u32; 4]) -> [u32; 4] {
+    add(x, x)
+}
+
+pub struct Counter {
+    value: u32,
+}
+
+fn new_model(value: u32) -> Counter {
+    Counter { value }
+}
+
+fn get_model(counter: &Counter) -> u32 {
+    counter.value
+}
+
+fn increment_model(counter: &mut Counter, by: u32) {
+    counter.value = counter.value.wrapping_add(by)
+}
+
+fn into_inner_model(counter: Counter) -> u32 {
+    counter.value
+}
+
+#[hax_lib::attributes]
+impl Counter {
+    #[hax_lib::modeled_by(new_model)]
+    pub fn new(value: u32) -> Self {
+        Self { value }
+    }
+
+    #[hax_lib::modeled_by(get_model)]
+    pub fn get(&self) -> u32 {
+        self.value
+    }
+
+    #[hax_lib::modeled_by(increment_model)]
+    pub fn increment(&mut self, by: u32) {
+        self.value = self.value.wrapping_add(by)
+    }
+
+    #[hax_lib::modeled_by(into_inner_model)]
+    pub fn into_inner(self) -> u32 {
+        self.value
+    }
+}
+
+pub fn use_counter(value: u32) -> u32 {
+    let mut counter = Counter::new(value);
+    counter.increment(2);
+    let current = counter.get();
+    current.wrapping_add(counter.into_inner())
+}
+
+pub struct Wrapper<T>(T);
+
+fn wrapper_get_model<T: Clone>(wrapper: &Wrapper<T>) -> T {
+    wrapper.0.clone()
+}
+
+#[hax_lib::attributes]
+impl<T: Clone> Wrapper<T> {
+    #[hax_lib::modeled_by(wrapper_get_model)]
+    pub fn get(&self) -> T {
+        self.0.clone()
+    }
+}
+
+pub fn use_wrapper(wrapper: &Wrapper<u8>) -> u8 {
+    wrapper.get()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modeled_items_run() {
+        assert_eq!(double([1, 2, 3, u32::MAX]), [2, 4, 6, u32::MAX - 1]);
+        assert_eq!(use_counter(3), 10);
+        assert_eq!(use_wrapper(&Wrapper(4)), 4);
+    }
+
+    #[test]
+    fn models_agree() {
+        let (x, y) = ([1, 2, 3, u32::MAX], [4, 5, 6, 2]);
+        assert_eq!(add(x, y), add_model(x, y));
+    }
+}
@@ -56,18 +56,18 @@ pub fn initialize_initiator(
     s: KeyPair,
     e: KeyPair,
     rs: &[u8],
-) -> HandshakeStateI0 {
-    let st = initialize_symmetric(&Noise_KKpsk0_25519_ChaChaPoly_SHA256.0);
-    let st = mix_hash(st, prologue);
-    let st = mix_hash(st, &s.public_key);
-    let st = mix_hash(st, rs);
-    HandshakeStateI0 {
+) -> Result<HandshakeStateI0, Error> {
+    let st = initialize_symmetric(&Noise_KKpsk0_25519_ChaChaPoly_SHA256.0)?;
+    let st = mix_hash(st, prologue)?;
+    let st = mix_hash(st, &s.public_key)?;
+    let st = mix_hash(st, rs)?;
+    Ok(HandshakeStateI0 {
         psk,
         st,
         s,
         e,
         rs: rs.to_vec(),
-    }
+    })
 }
 
 pub fn initialize_responder(
@@ -76,18 +76,18 @@ pub fn initialize_responder(
     s: KeyPair,
     e: KeyPair,
     rs: &[u8],
-) -> HandshakeStateR0 {
-    let st = initialize_symmetric(&Noise_KKpsk0_25519_ChaChaPoly_SHA256.0);
-    let st = mix_hash(st, prologue);
-    let st = mix_hash(st, rs);
-    let st = mix_hash(st, &s.public_key);
-    HandshakeStateR0 {
+) -> Result<HandshakeStateR0, Error> {
+    let st = initialize_symmetric(&Noise_KKpsk0_25519_ChaChaPoly_SHA256.0)?;
+    let st = mix_hash(st, prologue)?;
+    let st = mix_hash(st, rs)?;
+    let st = mix_hash(st, &s.public_key)?;
+    Ok(HandshakeStateR0 {
         st,
         psk,
         s,
         e,
         rs: rs.to_vec(),
-    }
+    })
 }
 
 ///  KKpsk0:
@@ -98,13 +98,13 @@ pub fn write_message1(
     payload: &[u8],
 ) -> Result<(HandshakeStateI1, Vec<u8>), Error> {
     let HandshakeStateI0 { st, psk, s, e, rs } = hs;
-    let st = mix_key_and_hash(st, &psk);
-    let st = mix_hash(st, &e.public_key);
-    let st = mix_key(st, &e.public_key);
-    let es = dh(&e, &rs);
-    let st = mix_key(st, &es);
-    let ss = dh(&s, &rs);
-    let st = mix_key(st, &ss);
+    let st = mix_key_and_hash(st, &psk)?;
+    let st = mix_hash(st, &e.public_key)?;
+    let st = mix_key(st, &e.public_key)?;
+    let es = dh(&e, &rs)?;
+    let st = mix_key(st, &es)?;
+    let ss = dh(&s, &rs)?;
+    let st = mix_key(st, &ss)?;
     let (st, ciphertext) = encrypt_and_hash(st, payload)?;
     let hs = HandshakeStateI1 { st, s, e };
     Ok((hs, ciphertext))
@@ -117,13 +117,13 @@ pub fn read_message1(
     let HandshakeStateR0 { st, psk, s, e, rs } = hs;
     let re = &ciphertext[0..DHLEN];
     let ciphertext = &ciphertext[DHLEN..ciphertext.len()];
-    let st = mix_key_and_hash(st, &psk);
-    let st = mix_hash(st, re);
-    let st = mix_key(st, re);
-    let es = dh(&s, re);
-    let st = mix_key(st, &es);
-    let ss = dh(&s, &rs);
-    let st = mix_key(st, &ss);
+    let st = mix_key_and_hash(st, &psk)?;
+    let st = mix_hash(st, re)?;
+    let st = mix_key(st, re)?;
+    let es = dh(&s, re)?;
+    let st = mix_key(st, &es)?;
+    let ss = dh(&s, &rs)?;
+    let st = mix_key(st, &ss)?;
     let (st, plaintext) = decrypt_and_hash(st, ciphertext)?;
     let hs = HandshakeStateR1 {
         st,
@@ -139,14 +139,14 @@ pub fn read_message1(
 ///     <- e, ee, se
 pub fn write_message2(hs: HandshakeStateR1, payload: &[u8]) -> Result<(Transport, Vec<u8>), Error> {
     let HandshakeStateR1 { st, e, rs, re } = hs;
-    let st = mix_hash(st, &e.public_key);
-    let st = mix_key(st, &e.public_key);
-    let ee = dh(&e, &re);
-    let st = mix_key(st, &ee);
-    let se = dh(&e, &rs);
-    let st = mix_key(st, &se);
+    let st = mix_hash(st, &e.public_key)?;
+    let st = mix_key(st, &e.public_key)?;
+    let ee = dh(&e, &re)?;
+    let st = mix_key(st, &ee)?;
+    let se = dh(&e, &rs)?;
+    let st = mix_key(st, &se)?;
     let (st, ciphertext) = encrypt_and_hash(st, payload)?;
-    let (c1, c2, h) = split(st);
+    let (c1, c2, h) = split(st)?;
     let tx = Transport {
         send: c2,
         recv: c1,
@@ -162,14 +162,14 @@ pub fn read_message2(
     let HandshakeStateI1 { st, s, e } = hs;
     let re = &ciphertext[0..DHLEN];
     let ciphertext = &ciphertext[DHLEN..ciphertext.len()];
-    let st = mix_hash(st, re);
-    let st = mix_key(st, re);
-    let ee = dh(&e, re);
-    let st = mix_key(st, &ee);
-    let se = dh(&s, re);
-    let st = mix_key(st, &se);
+    let st = mix_hash(st, re)?;
+    let st = mix_key(st, re)?;
+    let ee = dh(&e, re)?;
+    let st = mix_key(st, &ee)?;
+    let se = dh(&s, re)?;
+    let st = mix_key(st, &se)?;
     let (st, plaintext) = decrypt_and_hash(st, ciphertext)?;
-    let (c1, c2, h) = split(st);
+    let (c1, c2, h) = split(st)?;
     let tx = Transport {
         send: c1,
         recv: c2,
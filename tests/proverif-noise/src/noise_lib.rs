@@ -43,7 +43,7 @@ pub fn encrypt_with_ad(
     } else {
         match k {
             Some(k) => {
-                let cip = encrypt(&k, n, ad, plaintext);
+                let cip = encrypt(&k, n, ad, plaintext)?;
                 Ok((
                     CipherState {
                         k: Some(k),
@@ -86,7 +86,7 @@ pub fn rekey(cs: CipherState) -> Result<CipherState, Error> {
     let CipherState { k, n } = cs;
     match k {
         Some(k) => {
-            let new_k = noise_crypto::rekey(&k);
+            let new_k = noise_crypto::rekey(&k)?;
             Ok(CipherState { k: Some(new_k), n })
         }
         None => Err(Error::CryptoError),
@@ -95,57 +95,60 @@ pub fn rekey(cs: CipherState) -> Result<CipherState, Error> {
 
 /// 5.2: The SymmetricState Object
 
-pub fn initialize_symmetric(protocol_name: &[u8]) -> SymmetricState {
+pub fn initialize_symmetric(protocol_name: &[u8]) -> Result<SymmetricState, Error> {
     let pnlen = protocol_name.len();
     let hv: Vec<u8> = if pnlen < HASHLEN {
         [protocol_name, &vec![0u8; 32 - pnlen]].concat()
     } else {
-        hash(protocol_name)
+        hash(protocol_name)?
     };
     let ck = hv.clone();
-    SymmetricState {
+    Ok(SymmetricState {
         cs: initialize_key(None),
         ck,
         h: hv,
-    }
+    })
 }
 
-pub fn mix_key(st: SymmetricState, input_key_material: &[u8]) -> SymmetricState {
+pub fn mix_key(st: SymmetricState, input_key_material: &[u8]) -> Result<SymmetricState, Error> {
     let SymmetricState { cs: _, ck, h } = st;
-    let (ck, mut temp_k) = hkdf2(&ck, input_key_material);
+    let (ck, mut temp_k) = hkdf2(&ck, input_key_material)?;
     if HASHLEN == 64 {
         temp_k.truncate(32);
     }
-    SymmetricState {
+    Ok(SymmetricState {
         cs: initialize_key(Some(temp_k)),
         ck,
         h,
-    }
+    })
 }
 
-pub fn mix_hash(st: SymmetricState, data: &[u8]) -> SymmetricState {
+pub fn mix_hash(st: SymmetricState, data: &[u8]) -> Result<SymmetricState, Error> {
     let SymmetricState { cs, ck, h } = st;
-    SymmetricState {
+    Ok(SymmetricState {
         cs,
         ck,
-        h: hash(&[&h, data].concat()),
-    }
+        h: hash(&[&h, data].concat())?,
+    })
 }
 
-pub fn mix_key_and_hash(st: SymmetricState, input_key_material: &[u8]) -> SymmetricState {
+pub fn mix_key_and_hash(
+    st: SymmetricState,
+    input_key_material: &[u8],
+) -> Result<SymmetricState, Error> {
     let SymmetricState { cs: _, ck, h } = st;
-    let (ck, temp_h, mut temp_k) = hkdf3(&ck, input_key_material);
+    let (ck, temp_h, mut temp_k) = hkdf3(&ck, input_key_material)?;
     let mut new_h = h;
     new_h.extend_from_slice(&temp_h);
-    let new_h = hash(&new_h);
+    let new_h = hash(&new_h)?;
     if HASHLEN == 64 {
         temp_k.truncate(32);
     }
-    SymmetricState {
+    Ok(SymmetricState {
         cs: initialize_key(Some(temp_k)),
         ck,
         h: new_h,
-    }
+    })
 }
 
 /// Unclear if we need a special function for psk or we can reuse mix_key_and_hash above
@@ -162,7 +165,7 @@ pub fn encrypt_and_hash(
     let (new_cs, ciphertext) = encrypt_with_ad(st.cs, &st.h, plaintext)?;
     let mut new_h = st.h.clone();
     new_h.extend_from_slice(&ciphertext);
-    let new_h = hash(&new_h);
+    let new_h = hash(&new_h)?;
     Ok((
         SymmetricState {
             cs: new_cs,
@@ -180,7 +183,7 @@ pub fn decrypt_and_hash(
     let (new_cs, plaintext) = decrypt_with_ad(st.cs, &st.h, ciphertext)?;
     let mut new_h = st.h.clone();
     new_h.extend_from_slice(ciphertext);
-    let new_h = hash(&new_h);
+    let new_h = hash(&new_h)?;
     Ok((
         SymmetricState {
             cs: new_cs,
@@ -191,15 +194,15 @@ pub fn decrypt_and_hash(
     ))
 }
 
-pub fn split(st: SymmetricState) -> (CipherState, CipherState, Vec<u8>) {
-    let (mut temp_k1, mut temp_k2) = hkdf2(&st.ck, &Vec::new());
+pub fn split(st: SymmetricState) -> Result<(CipherState, CipherState, Vec<u8>), Error> {
+    let (mut temp_k1, mut temp_k2) = hkdf2(&st.ck, &Vec::new())?;
     if HASHLEN == 64 {
         temp_k1.truncate(32);
         temp_k2.truncate(32);
     }
-    (
+    Ok((
         initialize_key(Some(temp_k1)),
         initialize_key(Some(temp_k2)),
         st.h,
-    )
+    ))
 }
@@ -2,6 +2,7 @@ mod a;
 mod b;
 
 #[hax_lib::protocol_messages]
+#[derive(Debug, PartialEq, hax_lib_protocol_macros::Message)]
 pub enum Message {
     Ping(u8),
     Pong(u8),
@@ -19,5 +20,6 @@ fn run() {
     let b: B1 = b.read(msg).unwrap();
 
     let (_b, msg) = b.write().unwrap();
+    assert_eq!(decode_message(&encode_message(&msg)).unwrap(), msg);
     let _a = a.read(msg).unwrap();
 }
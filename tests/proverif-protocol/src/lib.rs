@@ -0,0 +1,226 @@
+//! A ping-pong protocol declared with `protocol!`, whose two roles
+//! implement their transitions with functions of the same names.
+//!
+//! The macro rejects incoherent protocols, e.g.:
+//!
+//! ```compile_fail
+//! // `Pong` is written but never read.
+//! use hax_lib_protocol::ProtocolResult;
+//! # fn main() {}
+//! hax_lib_protocol_macros::protocol! {
+//!     pub enum Message { Ping(u8), Pong(u8) }
+//!     role Initiator {
+//!         pub struct A0 {}
+//!         pub struct A1 {}
+//!         init_empty A0 with init_a;
+//!         write A0 -> A1 (Ping) with write_a;
+//!     }
+//!     role Responder {
+//!         pub struct B0 {}
+//!         pub struct B1 {}
+//!         pub struct B2 {}
+//!         init_empty B0 with init_b;
+//!         read B0 -> B1 (Ping) with read_b;
+//!         write B1 -> B2 (Pong) with write_b;
+//!     }
+//! }
+//! fn init_a() -> ProtocolResult<A0> { Ok(A0 {}) }
+//! fn write_a(_: A0) -> ProtocolResult<(A1, Message)> { Ok((A1 {}, Message::Ping(0))) }
+//! fn init_b() -> ProtocolResult<B0> { Ok(B0 {}) }
+//! fn read_b(_: B0, _: Message) -> ProtocolResult<B1> { Ok(B1 {}) }
+//! fn write_b(_: B1) -> ProtocolResult<(B2, Message)> { Ok((B2 {}, Message::Pong(0))) }
+//! ```
+//!
+//! ```compile_fail
+//! // The responder is never initialized.
+//! use hax_lib_protocol::ProtocolResult;
+//! # fn main() {}
+//! hax_lib_protocol_macros::protocol! {
+//!     pub enum Message { Ping(u8) }
+//!     role Initiator {
+//!         pub struct A0 {}
+//!         pub struct A1 {}
+//!         init_empty A0 with init_a;
+//!         write A0 -> A1 (Ping) with write_a;
+//!     }
+//!     role Responder {
+//!         pub struct B0 {}
+//!         pub struct B1 {}
+//!         read B0 -> B1 (Ping) with read_b;
+//!     }
+//! }
+//! fn init_a() -> ProtocolResult<A0> { Ok(A0 {}) }
+//! fn write_a(_: A0) -> ProtocolResult<(A1, Message)> { Ok((A1 {}, Message::Ping(0))) }
+//! fn read_b(_: B0, _: Message) -> ProtocolResult<B1> { Ok(B1 {}) }
+//! ```
+//!
+//! ```compile_fail
+//! // `B1` is not a state of the initiator.
+//! use hax_lib_protocol::ProtocolResult;
+//! # fn main() {}
+//! hax_lib_protocol_macros::protocol! {
+//!     pub enum Message { Ping(u8) }
+//!     role Initiator {
+//!         pub struct A0 {}
+//!         init_empty A0 with init_a;
+//!         write A0 -> B1 (Ping) with write_a;
+//!     }
+//!     role Responder {
+//!         pub struct B0 {}
+//!         pub struct B1 {}
+//!         init_empty B0 with init_b;
+//!         read B0 -> B1 (Ping) with read_b;
+//!     }
+//! }
+//! fn init_a() -> ProtocolResult<A0> { Ok(A0 {}) }
+//! fn write_a(_: A0) -> ProtocolResult<(B1, Message)> { Ok((B1 {}, Message::Ping(0))) }
+//! fn init_b() -> ProtocolResult<B0> { Ok(B0 {}) }
+//! fn read_b(_: B0, _: Message) -> ProtocolResult<B1> { Ok(B1 {}) }
+//! ```
+//!
+//! ```compile_fail
+//! // Both reads would generate a process named `process_read_b_c_d`.
+//! use hax_lib_protocol::ProtocolResult;
+//! # fn main() {}
+//! hax_lib_protocol_macros::protocol! {
+//!     pub enum Message { Ping(u8) }
+//!     role Initiator {
+//!         pub struct A0 {}
+//!         pub struct A1 {}
+//!         init_empty A0 with init_a;
+//!         write A0 -> A1 (Ping) with write_a;
+//!     }
+//!     role Responder {
+//!         pub struct B {}
+//!         pub struct CD {}
+//!         pub struct BC {}
+//!         pub struct D {}
+//!         init_empty B with init_b;
+//!         read B -> CD (Ping) with read_b;
+//!         read BC -> D (Ping) with read_bc;
+//!     }
+//! }
+//! fn init_a() -> ProtocolResult<A0> { Ok(A0 {}) }
+//! fn write_a(_: A0) -> ProtocolResult<(A1, Message)> { Ok((A1 {}, Message::Ping(0))) }
+//! fn init_b() -> ProtocolResult<B> { Ok(B {}) }
+//! fn read_b(_: B, _: Message) -> ProtocolResult<CD> { Ok(CD {}) }
+//! fn read_bc(_: BC, _: Message) -> ProtocolResult<D> { Ok(D {}) }
+//! ```
+
+pub mod initiator {
+    use crate::{Message, A0, A1, A2};
+    use hax_lib_protocol::{ProtocolError, ProtocolResult};
+
+    pub fn init(prologue: Vec<u8>) -> ProtocolResult<A0> {
+        match prologue.as_slice() {
+            [data] => Ok(A0 { data: *data }),
+            _ => Err(ProtocolError::InvalidPrologue),
+        }
+    }
+
+    pub fn write(state: A0) -> ProtocolResult<(A1, Message)> {
+        Ok((A1 { sent: state.data }, Message::Ping(state.data)))
+    }
+
+    pub fn read(state: A1, msg: Message) -> ProtocolResult<A2> {
+        match msg {
+            Message::Pong(received) if received == state.sent => Ok(A2 { received }),
+            _ => Err(ProtocolError::InvalidMessage),
+        }
+    }
+}
+
+pub mod responder {
+    use crate::{Message, B0, B1, B2};
+    use hax_lib_protocol::{ProtocolError, ProtocolResult};
+
+    pub fn init() -> ProtocolResult<B0> {
+        Ok(B0 {})
+    }
+
+    pub fn read(_state: B0, msg: Message) -> ProtocolResult<B1> {
+        match msg {
+            Message::Ping(received) => Ok(B1 { received }),
+            _ => Err(ProtocolError::InvalidMessage),
+        }
+    }
+
+    pub fn write(state: B1) -> ProtocolResult<(B2, Message)> {
+        Ok((B2 {}, Message::Pong(state.received)))
+    }
+}
+
+hax_lib_protocol_macros::protocol! {
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum Message {
+        Ping(u8),
+        Pong(u8),
+    }
+
+    role Initiator {
+        pub struct A0 { data: u8 }
+        pub struct A1 { sent: u8 }
+        pub struct A2 { pub received: u8 }
+
+        init A0 with initiator::init;
+        write A0 -> A1 (Ping) with initiator::write;
+        read A1 -> A2 (Pong) with initiator::read;
+    }
+
+    role Responder {
+        pub struct B0 {}
+        pub struct B1 { received: u8 }
+        pub struct B2 {}
+
+        init_empty B0 with responder::init;
+        read B0 -> B1 (Ping) with responder::read;
+        write B1 -> B2 (Pong) with responder::write;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hax_lib_protocol::runner::{Party, Runner};
+    use hax_lib_protocol::state_machine::InitialState;
+    use hax_lib_protocol::ProtocolResult;
+
+    #[test]
+    fn run() {
+        let mut runner = Runner::new();
+        let a = A0::init(Some(vec![7])).unwrap();
+        let b = B0::init(None).unwrap();
+        let (a, b) = runner.exchange(Party::Initiator, a, b).unwrap();
+        let (B2 {}, A2 { received }) = runner.exchange(Party::Responder, b, a).unwrap();
+        assert_eq!(received, 7);
+        assert_eq!(runner.transcript().entries().len(), 2);
+    }
+
+    #[test]
+    fn wrong_prologue() {
+        assert!(A0::init(None).is_err());
+        assert!(A0::init(Some(vec![])).is_err());
+        assert!(B0::init(Some(vec![1])).is_err());
+    }
+
+    #[test]
+    fn wrong_message() {
+        let mut runner = Runner::new();
+        let a = A0::init(Some(vec![7])).unwrap();
+        let (a, _) = runner
+            .exchange(Party::Initiator, a, B0::init(None).unwrap())
+            .unwrap();
+        let result: ProtocolResult<A2> = runner.inject(Party::Responder, a, Message::Ping(7));
+        assert!(result.is_err());
+    }
+
+    /// The generated processes are named after the transitions.
+    #[test]
+    fn process_names() {
+        let a = process_init_a0(vec![1]).unwrap();
+        let (a, msg) = process_write_a0_a1(a).unwrap();
+        let b = process_read_b0_b1(process_init_b0().unwrap(), msg).unwrap();
+        let (_, msg) = process_write_b1_b2(b).unwrap();
+        assert_eq!(process_read_a1_a2(a, msg).unwrap().received, 1);
+    }
+}
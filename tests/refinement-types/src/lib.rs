@@ -0,0 +1,96 @@
+//! Run-time behavior of refinement types: fallible construction,
+//! `TryFrom`, serde and the debug run-time check.
+
+use hax_lib::Refinement;
+
+/// Even `u8` numbers.
+#[hax_lib::refinement_type(debug, display, |x| x % 2 == 0)]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Even(u8);
+
+/// Non-empty vectors, whose `Debug` implementation is derived.
+#[hax_lib::refinement_type(|v| !v.is_empty())]
+#[derive(Debug)]
+pub struct NonEmpty(Vec<u8>);
+
+/// A value that cannot be cloned.
+#[derive(Debug)]
+pub struct Token(pub u8);
+
+/// Refinement of a type that does not implement `Clone`: its
+/// refinement is not checked at run-time in debug mode.
+#[hax_lib::refinement_type(|t| t.0 > 0)]
+pub struct PositiveToken(Token);
+
+pub fn double(x: u8) -> Option<Even> {
+    if x < 128 {
+        Some(Even::new(x * 2))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hax_lib::{RefineAs, RefinementError};
+
+    #[test]
+    fn try_new() {
+        assert_eq!(Even::try_new(4).map(Refinement::get), Ok(4));
+        assert_eq!(
+            Even::try_new(5).map(Refinement::get),
+            Err(RefinementError::new::<Even>())
+        );
+        assert_eq!(4u8.try_into_checked().map(|x: Even| x.get()), Ok(4));
+        assert!(RefineAs::<Even>::try_into_checked(3u8).is_err());
+        assert!(NonEmpty::try_new(vec![]).is_err());
+        assert_eq!(double(21).map(Refinement::get), Some(42));
+        assert_eq!(double(200), None);
+    }
+
+    #[test]
+    fn try_from() {
+        assert_eq!(Even::try_from(8).map(|x| *x), Ok(8));
+        let err = Even::try_from(7).unwrap_err();
+        assert!(err.refined_type().ends_with("Even"));
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "value does not satisfy the invariant of refinement type `{}`",
+                err.refined_type()
+            )
+        );
+    }
+
+    #[test]
+    fn debug_and_display() {
+        let x = Even::new(6);
+        assert_eq!(format!("{x:?}"), "6");
+        assert_eq!(format!("{x}"), "6");
+        assert_eq!(format!("{:?}", NonEmpty::new(vec![1])), "NonEmpty([1])");
+    }
+
+    #[test]
+    fn serde() {
+        assert_eq!(serde_json::to_string(&Even::new(10)).unwrap(), "10");
+        let x: Even = serde_json::from_str("12").unwrap();
+        assert_eq!(x.get(), 12);
+        let err = serde_json::from_str::<Even>("13").unwrap_err();
+        assert!(err.to_string().contains("does not satisfy the invariant"));
+        assert!(serde_json::from_str::<NonEmpty>("[]").is_err());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "does not satisfy the invariant of refinement type")]
+    fn debug_runtime_check() {
+        let _ = Even::new(3);
+    }
+
+    #[test]
+    fn no_clone() {
+        // `Token` is not `Clone`: the refinement cannot be checked.
+        assert_eq!(PositiveToken::new(Token(0)).get().0, 0);
+    }
+}
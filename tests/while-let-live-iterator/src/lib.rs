@@ -0,0 +1,13 @@
+//! A `while let Some(item) = it.next()` loop is not reconstructed as a
+//! `for` loop over `it` when `it` is used after the loop: the `for`
+//! loop would consume `it`. The loop stays an unconditional loop, which
+//! is not supported.
+
+pub fn sum_and_count(arr: &[u8]) -> (u32, usize) {
+    let mut acc = 0;
+    let mut it = arr.iter();
+    while let Some(item) = it.next() {
+        acc = acc + *item as u32;
+    }
+    (acc, it.count())
+}